@@ -0,0 +1,251 @@
+use anyhow::{Context, Result};
+use boosty_api::api_client::ApiClient;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use crate::config::AppConfig;
+use crate::proxy::ProxyConfig;
+use crate::{auth, throttle};
+use crate::{log_debug, log_warn};
+
+const CURRENT_USER_PATH: &str = "/v1/user/current";
+const SUBSCRIPTIONS_PATH: &str = "/v1/user/subscriptions?limit=100&with_follow=false";
+/// Stops a listing whose pages never say they are the last.
+const MAX_SUBSCRIPTION_PAGES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStatus {
+    Valid,
+    /// Was expired and got refreshed during the check.
+    Refreshed,
+    Expired,
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountUser {
+    #[serde(default)]
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub blog_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Subscription {
+    pub blog: String,
+    pub blog_title: String,
+    pub level_name: String,
+    pub level_price: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountInfo {
+    pub profile: String,
+    pub token_status: TokenStatus,
+    pub token_expires_at: Option<i64>,
+    /// Why the expired token could not be refreshed.
+    pub refresh_error: Option<String>,
+    pub user: Option<AccountUser>,
+    pub subscriptions: Vec<Subscription>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubscriptionsPage {
+    #[serde(default)]
+    data: Vec<RawSubscription>,
+    #[serde(default)]
+    extra: PageExtra,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageExtra {
+    #[serde(default = "default_is_last")]
+    is_last: bool,
+    /// A number or a string, passed back as is for the next page.
+    #[serde(default)]
+    offset: Option<serde_json::Value>,
+}
+
+impl Default for PageExtra {
+    fn default() -> Self {
+        Self {
+            is_last: true,
+            offset: None,
+        }
+    }
+}
+
+fn default_is_last() -> bool {
+    true
+}
+
+impl SubscriptionsPage {
+    fn into_subscriptions(self) -> Vec<Subscription> {
+        self.data
+            .into_iter()
+            .map(|s| Subscription {
+                blog: s.blog.blog_url,
+                blog_title: s.blog.title,
+                level_name: s.name,
+                level_price: s.price,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSubscription {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    price: i64,
+    blog: RawBlog,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBlog {
+    blog_url: String,
+    #[serde(default)]
+    title: String,
+}
+
+/// Validates the credentials the client is using and describes the account behind them.
+pub async fn check_auth(client: &ApiClient, cfg: &AppConfig) -> Result<AccountInfo> {
+    let profile = auth::profile_in_use(cfg);
+    // A session in another scope may have put another profile on the shared client.
+    auth::use_profile(client, &profile).await?;
+    let http = crate::api_http_client(&cfg.proxy)?;
+    let creds = cfg.credentials(&profile).unwrap_or_default();
+    let mut info = AccountInfo {
+        profile,
+        token_status: TokenStatus::Missing,
+        token_expires_at: creds.token_expires_at,
+        refresh_error: None,
+        user: None,
+        subscriptions: Vec::new(),
+    };
+    if creds.access_token.is_empty() && creds.refresh_token.is_empty() {
+        return Ok(info);
+    }
+
+    let user = match get_json::<AccountUser>(&http, client, CURRENT_USER_PATH).await? {
+        Some(user) => {
+            info.token_status = TokenStatus::Valid;
+            Some(user)
+        }
+        None => match auth::refresh_expired(client).await {
+            Ok(()) => {
+                info.token_status = TokenStatus::Refreshed;
+                info.token_expires_at = None;
+                get_json::<AccountUser>(&http, client, CURRENT_USER_PATH).await?
+            }
+            Err(e) => {
+                log_warn!("Access token could not be refreshed: {e:#}");
+                info.refresh_error = Some(format!("{e:#}"));
+                None
+            }
+        },
+    };
+    let Some(user) = user else {
+        info.token_status = TokenStatus::Expired;
+        return Ok(info);
+    };
+    info.user = Some(user);
+
+    info.subscriptions = get_subscriptions(&http, client).await?.unwrap_or_default();
+
+    Ok(info)
+}
+
+/// Active subscriptions of the account the client is logged in as, requested through `proxy`.
+pub async fn fetch_subscriptions(
+    client: &ApiClient,
+    proxy: &ProxyConfig,
+) -> Result<Vec<Subscription>> {
+    let http = crate::api_http_client(proxy)?;
+    match get_subscriptions(&http, client).await? {
+        Some(subscriptions) => Ok(subscriptions),
+        None => {
+            auth::refresh_expired(client)
+                .await
+                .context("Not authorized to list subscriptions")?;
+            get_subscriptions(&http, client)
+                .await?
+                .context("Not authorized to list subscriptions")
+        }
+    }
+}
+
+/// Every page of the subscription list; `None` when the API answers 401.
+async fn get_subscriptions(http: &Client, client: &ApiClient) -> Result<Option<Vec<Subscription>>> {
+    let mut subscriptions = Vec::new();
+    let mut path = SUBSCRIPTIONS_PATH.to_string();
+    for _ in 0..MAX_SUBSCRIPTION_PAGES {
+        let Some(page) = get_json::<SubscriptionsPage>(http, client, &path).await? else {
+            return Ok(None);
+        };
+        let next = match page.extra.offset.as_ref() {
+            Some(serde_json::Value::String(offset)) => Some(offset.clone()),
+            Some(serde_json::Value::Number(offset)) => Some(offset.to_string()),
+            _ => None,
+        };
+        let last = page.extra.is_last || page.data.is_empty();
+        subscriptions.extend(page.into_subscriptions());
+        match next {
+            Some(offset) if !last => {
+                let offset: String =
+                    url::form_urlencoded::byte_serialize(offset.as_bytes()).collect();
+                path = format!("{SUBSCRIPTIONS_PATH}&offset={offset}");
+            }
+            _ => return Ok(Some(subscriptions)),
+        }
+    }
+    log_warn!(
+        "Subscription list is longer than {MAX_SUBSCRIPTION_PAGES} pages, the rest is skipped"
+    );
+    Ok(Some(subscriptions))
+}
+
+/// GET through `http` with the auth headers of `client`; `None` when the API answers 401.
+async fn get_json<T: DeserializeOwned>(
+    http: &Client,
+    client: &ApiClient,
+    path: &str,
+) -> Result<Option<T>> {
+    let mut request = http.get(format!("{}{path}", crate::API_URL));
+    for (name, value) in client.headers_as_map() {
+        request = request.header(name, value);
+    }
+
+    throttle::api_request().await;
+    let started = Instant::now();
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Request to '{path}' failed"))?;
+    log_debug!(
+        "GET {path} -> {} in {} ms",
+        response.status(),
+        started.elapsed().as_millis()
+    );
+    if response.status() == StatusCode::UNAUTHORIZED {
+        return Ok(None);
+    }
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("Request to '{path}' failed"))?;
+    let body = response
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse response of '{path}'"))?;
+    Ok(Some(body))
+}
@@ -0,0 +1,407 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{self, AppConfig, get_download_path};
+use crate::file_handler::part_file_path;
+use crate::{blog_index, log_info, log_warn, paths, progress_reporter, storage};
+
+const JOURNAL_FILE: &str = "archive_move.json";
+const COPY_CHUNK: usize = 1024 * 1024;
+
+/// Progress of a move, kept in the app data folder so an interrupted move can be resumed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveJournal {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub started_at: i64,
+    /// Blogs already in place at `to` and removed from `from`.
+    #[serde(default)]
+    pub moved_blogs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MoveArchiveResult {
+    pub from: String,
+    pub to: String,
+    pub blogs: usize,
+    /// Files copied (blogs on the same filesystem are renamed and not counted here).
+    pub files_copied: u64,
+    pub bytes_copied: u64,
+    pub resumed: bool,
+}
+
+struct FileEntry {
+    rel: PathBuf,
+    size: u64,
+}
+
+fn journal_path() -> Result<PathBuf> {
+    paths::app_file_path(JOURNAL_FILE)
+}
+
+/// The unfinished move, if the last one was interrupted.
+pub async fn pending_move() -> Result<Option<MoveJournal>> {
+    let path = journal_path()?;
+    if !fs::try_exists(&path).await.unwrap_or(false) {
+        return Ok(None);
+    }
+    let data = fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read move journal '{}'", path.display()))?;
+    let journal = serde_json::from_slice(&data)
+        .with_context(|| format!("Failed to parse move journal '{}'", path.display()))?;
+    Ok(Some(journal))
+}
+
+async fn save_journal(journal: &MoveJournal) -> Result<()> {
+    let path = journal_path()?;
+    let data = serde_json::to_vec_pretty(journal).context("Failed to serialize move journal")?;
+    fs::write(&path, data)
+        .await
+        .with_context(|| format!("Failed to write move journal '{}'", path.display()))
+}
+
+/// Forgets an unfinished move; blogs already moved stay in its target folder.
+pub async fn discard_pending_move() -> Result<()> {
+    remove_journal().await
+}
+
+async fn remove_journal() -> Result<()> {
+    let path = journal_path()?;
+    if fs::try_exists(&path).await.unwrap_or(false) {
+        fs::remove_file(&path)
+            .await
+            .with_context(|| format!("Failed to remove move journal '{}'", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Moves every downloaded blog to `target` and makes it the download path.
+///
+/// Blogs are renamed when both folders are on one filesystem, otherwise copied file by
+/// file, compared with the original and only then deleted. Finished blogs are recorded in
+/// a journal, so a cancelled or crashed move continues where it stopped when started again
+/// with the same target.
+pub async fn move_archive(
+    cfg: &AppConfig,
+    target: &Path,
+    cancel_token: &CancellationToken,
+) -> Result<MoveArchiveResult> {
+    let target = std::path::absolute(target)
+        .with_context(|| format!("Invalid target folder '{}'", target.display()))?;
+
+    let (mut journal, resumed) = match pending_move().await? {
+        Some(journal) if journal.to == target => (journal, true),
+        Some(journal) => anyhow::bail!(
+            "An unfinished move to '{}' exists, finish it first",
+            journal.to.display()
+        ),
+        None => {
+            let from = std::path::absolute(get_download_path(cfg))?;
+            check_targets(&from, &target).await?;
+            let journal = MoveJournal {
+                from,
+                to: target.clone(),
+                started_at: Utc::now().timestamp(),
+                moved_blogs: Vec::new(),
+            };
+            (journal, false)
+        }
+    };
+    let source = journal.from.clone();
+
+    fs::create_dir_all(&target)
+        .await
+        .with_context(|| format!("Failed to create '{}'", target.display()))?;
+    save_journal(&journal).await?;
+
+    let blogs: Vec<String> = blog_index::list_blog_names(&source)
+        .await?
+        .into_iter()
+        .filter(|b| !journal.moved_blogs.contains(b))
+        .collect();
+    let mut files = Vec::with_capacity(blogs.len());
+    for blog in &blogs {
+        files.push(list_files(&source.join(blog)).await?);
+    }
+
+    log_info!(
+        "Moving {} blogs from '{}' to '{}'{}",
+        blogs.len(),
+        source.display(),
+        target.display(),
+        if resumed { " (resumed)" } else { "" }
+    );
+    let total_files = files.iter().map(|f| f.len() as u64).sum();
+    let _progress = progress_reporter::SessionGuard::new(total_files);
+
+    let mut result = MoveArchiveResult {
+        from: source.to_string_lossy().into_owned(),
+        to: target.to_string_lossy().into_owned(),
+        resumed,
+        ..MoveArchiveResult::default()
+    };
+    for (blog, blog_files) in blogs.into_iter().zip(files) {
+        crate::pause::wait_if_paused(cancel_token).await?;
+        let from = source.join(&blog);
+        let to = target.join(&blog);
+
+        if fs::try_exists(&to).await.unwrap_or(false) || fs::rename(&from, &to).await.is_err() {
+            copy_blog(&from, &to, &blog_files, cancel_token, &mut result).await?;
+            fs::remove_dir_all(&from)
+                .await
+                .with_context(|| format!("Failed to remove '{}' after copying", from.display()))?;
+        } else {
+            for _ in &blog_files {
+                progress_reporter::finish_file();
+            }
+        }
+
+        log_info!("Moved blog '{blog}'");
+        journal.moved_blogs.push(blog);
+        result.blogs += 1;
+        save_journal(&journal).await?;
+    }
+
+    let new_path = target.to_string_lossy().into_owned();
+    config::update_config(|cfg| cfg.download_path = Some(new_path))
+        .await
+        .context("Blogs are moved, but the download path could not be saved")?;
+    if config::is_overridden("download_path") {
+        log_warn!(
+            "download_path is set by an environment variable, point it to '{}'",
+            target.display()
+        );
+    }
+    remove_journal().await?;
+    storage::forget_archive_sizes();
+    // The old root is removed only if nothing else was kept there.
+    let _ = fs::remove_dir(&source).await;
+
+    log_info!(
+        "Archive moved: {} blogs, {} files copied",
+        result.blogs,
+        result.files_copied
+    );
+    Ok(result)
+}
+
+/// A new move must not nest the folders or overwrite blogs already in the target.
+async fn check_targets(source: &Path, target: &Path) -> Result<()> {
+    if source == target {
+        anyhow::bail!("The archive is already in '{}'", target.display());
+    }
+    if target.starts_with(source) || source.starts_with(target) {
+        anyhow::bail!(
+            "'{}' and '{}' are inside one another",
+            source.display(),
+            target.display()
+        );
+    }
+    let mut taken = Vec::new();
+    for blog in blog_index::list_blog_names(source).await? {
+        if fs::try_exists(target.join(&blog)).await.unwrap_or(false) {
+            taken.push(blog);
+        }
+    }
+    if !taken.is_empty() {
+        anyhow::bail!(
+            "'{}' already has folders for: {}",
+            target.display(),
+            taken.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Files under `dir`, relative to it, in a stable order.
+async fn list_files(dir: &Path) -> Result<Vec<FileEntry>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(rel_dir) = pending.pop() {
+        let abs = dir.join(&rel_dir);
+        let mut rd = fs::read_dir(&abs)
+            .await
+            .with_context(|| format!("Failed to read folder '{}'", abs.display()))?;
+        while let Some(entry) = rd.next_entry().await? {
+            let rel = rel_dir.join(entry.file_name());
+            let meta = entry.metadata().await?;
+            if meta.is_dir() {
+                pending.push(rel);
+            } else {
+                files.push(FileEntry {
+                    rel,
+                    size: meta.len(),
+                });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.rel.cmp(&b.rel));
+    Ok(files)
+}
+
+async fn copy_blog(
+    from: &Path,
+    to: &Path,
+    files: &[FileEntry],
+    cancel_token: &CancellationToken,
+    result: &mut MoveArchiveResult,
+) -> Result<()> {
+    for file in files {
+        crate::pause::wait_if_paused(cancel_token).await?;
+        let src = from.join(&file.rel);
+        let dst = to.join(&file.rel);
+
+        // Left over from an interrupted run and already verified then.
+        if same_contents(&src, &dst).await.unwrap_or(false) {
+            progress_reporter::finish_file();
+            continue;
+        }
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+        }
+
+        let name = file.rel.to_string_lossy();
+        progress_reporter::start_file(&name, file.size);
+        let part = part_file_path(&dst);
+        if let Err(e) = copy_file(&src, &part, cancel_token).await {
+            progress_reporter::abandon_file();
+            let _ = fs::remove_file(&part).await;
+            return Err(e);
+        }
+        if !same_contents(&src, &part).await? {
+            progress_reporter::abandon_file();
+            let _ = fs::remove_file(&part).await;
+            anyhow::bail!("Copy of '{}' doesn't match the original", src.display());
+        }
+        fs::rename(&part, &dst)
+            .await
+            .with_context(|| format!("Failed to move copy to '{}'", dst.display()))?;
+        progress_reporter::finish_file();
+        result.files_copied += 1;
+        result.bytes_copied += file.size;
+    }
+    Ok(())
+}
+
+async fn copy_file(src: &Path, dst: &Path, cancel_token: &CancellationToken) -> Result<()> {
+    let mut input = fs::File::open(src)
+        .await
+        .with_context(|| format!("Failed to open '{}'", src.display()))?;
+    let mut output = fs::File::create(dst)
+        .await
+        .with_context(|| format!("Failed to create '{}'", dst.display()))?;
+    let mut buf = vec![0u8; COPY_CHUNK];
+    loop {
+        crate::ensure_not_cancelled(cancel_token)?;
+        let n = input.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        output.write_all(&buf[..n]).await?;
+        progress_reporter::inc(n as u64);
+    }
+    output.flush().await?;
+    output.sync_all().await?;
+    Ok(())
+}
+
+/// Byte-for-byte comparison; `false` when either file is missing or the sizes differ.
+async fn same_contents(a: &Path, b: &Path) -> Result<bool> {
+    let (Ok(meta_a), Ok(meta_b)) = (fs::metadata(a).await, fs::metadata(b).await) else {
+        return Ok(false);
+    };
+    if meta_a.len() != meta_b.len() {
+        return Ok(false);
+    }
+    let mut file_a = fs::File::open(a).await?;
+    let mut file_b = fs::File::open(b).await?;
+    let mut buf_a = vec![0u8; COPY_CHUNK];
+    let mut buf_b = vec![0u8; COPY_CHUNK];
+    loop {
+        let n = read_full(&mut file_a, &mut buf_a).await?;
+        let m = read_full(&mut file_b, &mut buf_b).await?;
+        if n != m || buf_a[..n] != buf_b[..m] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+async fn read_full(reader: &mut (impl AsyncRead + Unpin), buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        std::env::temp_dir().join(format!("boosty_move_{name}_{}_{nanos}", std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn copy_blog_verifies_and_skips_finished_files() {
+        let from = temp_dir("from");
+        let to = temp_dir("to");
+        fs::create_dir_all(from.join("post [1]")).await.unwrap();
+        fs::write(from.join("post [1]/a.jpg"), b"image")
+            .await
+            .unwrap();
+        fs::write(from.join(".boosty.json"), b"{}").await.unwrap();
+        fs::create_dir_all(to.join("post [1]")).await.unwrap();
+        fs::write(to.join("post [1]/a.jpg"), b"image")
+            .await
+            .unwrap();
+
+        let files = list_files(&from).await.unwrap();
+        assert_eq!(files.len(), 2);
+        let mut result = MoveArchiveResult::default();
+        copy_blog(&from, &to, &files, &CancellationToken::new(), &mut result)
+            .await
+            .unwrap();
+
+        assert_eq!(result.files_copied, 1);
+        assert_eq!(fs::read(to.join(".boosty.json")).await.unwrap(), b"{}");
+        assert!(
+            same_contents(&from.join("post [1]/a.jpg"), &to.join("post [1]/a.jpg"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !same_contents(&from.join(".boosty.json"), &to.join("post [1]/a.jpg"))
+                .await
+                .unwrap()
+        );
+
+        let _ = fs::remove_dir_all(&from).await;
+        let _ = fs::remove_dir_all(&to).await;
+    }
+
+    #[tokio::test]
+    async fn nested_targets_are_rejected() {
+        let root = temp_dir("nested");
+        assert!(check_targets(&root, &root.join("inner")).await.is_err());
+        assert!(check_targets(&root, &root).await.is_err());
+        assert!(check_targets(&root, &temp_dir("other")).await.is_ok());
+    }
+}
@@ -0,0 +1,182 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use boosty_api::api_client::ApiClient;
+use boosty_api::error::ApiError;
+
+use crate::config::{AppConfig, AuthProfile};
+use crate::{context, events, log_debug, log_info, log_trace, log_warn, logger, throttle};
+
+/// Records that the client of the current event scope now has the credentials of `name`.
+/// Other scopes may share the client, so they put their own profile back on it before
+/// their next session.
+pub(crate) fn set_current_profile(name: &str) {
+    let current = events::current();
+    for scope in events::live_scopes() {
+        let profile = Arc::ptr_eq(&scope, &current).then(|| name.to_string());
+        *scope.profile.lock().unwrap() = profile;
+    }
+}
+
+/// Profile whose credentials the current scope put on the client; `None` until it does.
+fn current_profile() -> Option<String> {
+    events::current().profile.lock().unwrap().clone()
+}
+
+/// Switches the client to the profile the blog is mapped to (or the active one).
+pub async fn use_profile_for_blog(client: &ApiClient, cfg: &AppConfig, blog: &str) -> Result<()> {
+    use_profile(client, cfg.profile_for_blog(blog)).await
+}
+
+/// Puts the credentials of `profile` on the client, unless they are already there.
+///
+/// Credentials are read from the scope's context (the saved config for the CLI and GUI),
+/// so tokens refreshed earlier in the session are picked up. A profile with only a refresh token gets a new access token.
+pub async fn use_profile(client: &ApiClient, profile: &str) -> Result<()> {
+    if current_profile().as_deref() == Some(profile) {
+        return Ok(());
+    }
+    let context = context::current();
+    let _guard = context.refresh_lock.lock().await;
+
+    let cfg = context.config().await?;
+    let creds = cfg
+        .credentials(profile)
+        .with_context(|| format!("Auth profile '{profile}' not found"))?;
+
+    client.clear_access_token().await;
+    client.clear_refresh_and_device_id().await;
+    if !creds.access_token.is_empty() {
+        client.set_bearer_token(&creds.access_token).await?;
+    } else if !creds.refresh_token.is_empty() && !creds.device_id.is_empty() {
+        client
+            .set_refresh_token_and_device_id(&creds.refresh_token, &creds.device_id)
+            .await?;
+        let pair = client.refresh_tokens().await?;
+        context
+            .save_credentials(
+                profile,
+                AuthProfile {
+                    access_token: pair.access_token,
+                    refresh_token: pair.refresh_token,
+                    device_id: creds.device_id,
+                    token_expires_at: None,
+                },
+            )
+            .await?;
+    }
+
+    set_current_profile(profile);
+    log_info!("Using auth profile '{profile}'");
+    Ok(())
+}
+
+/// Runs an API call to `endpoint`, waiting for its slot under the request rate limit, and,
+/// if the access token expired, refreshes it and runs the call again.
+///
+/// The refresh goes through the refresh token and device id of the profile in use;
+/// the new pair is saved back to that profile in the scope's context right away. Without them the original
+/// error is returned.
+pub async fn call<T, F, Fut>(client: &ApiClient, endpoint: &str, request: F) -> Result<T, ApiError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, ApiError>>,
+{
+    let generation = context::current()
+        .refresh_generation
+        .load(Ordering::Acquire);
+    throttle::api_request().await;
+    match timed(endpoint, request()).await {
+        Err(ApiError::Unauthorized) => {
+            if let Err(e) = refresh(client, generation).await {
+                log_warn!("Access token expired and could not be refreshed: {e:#}");
+                return Err(ApiError::Unauthorized);
+            }
+            throttle::api_request().await;
+            timed(endpoint, request()).await
+        }
+        result => result,
+    }
+}
+
+async fn timed<T>(
+    endpoint: &str,
+    request: impl Future<Output = Result<T, ApiError>>,
+) -> Result<T, ApiError> {
+    let started = Instant::now();
+    let result = request.await;
+    let elapsed = started.elapsed().as_millis();
+    let endpoint = logger::redact_urls(endpoint);
+    match &result {
+        Ok(_) => log_trace!("GET {endpoint} -> OK in {elapsed} ms"),
+        Err(e) => log_debug!("GET {endpoint} -> {} in {elapsed} ms", failure(e)),
+    }
+    result
+}
+
+/// Status of a failed call, or what kind of failure it was.
+fn failure(e: &ApiError) -> String {
+    match e {
+        ApiError::HttpStatus { status, .. } => status.to_string(),
+        ApiError::Unauthorized => "401 Unauthorized".to_string(),
+        e => logger::redact_urls(&format!("error: {e}")),
+    }
+}
+
+/// Profile whose credentials the client is using, the active one if none was applied yet.
+pub(crate) fn profile_in_use(cfg: &AppConfig) -> String {
+    current_profile().unwrap_or_else(|| cfg.active_profile.clone())
+}
+
+/// Refreshes the tokens of the profile in use, for requests made outside [`call`].
+pub(crate) async fn refresh_expired(client: &ApiClient) -> Result<()> {
+    let generation = context::current()
+        .refresh_generation
+        .load(Ordering::Acquire);
+    refresh(client, generation).await
+}
+
+async fn refresh(client: &ApiClient, seen_generation: u64) -> Result<()> {
+    let context = context::current();
+    let _guard = context.refresh_lock.lock().await;
+    if context.refresh_generation.load(Ordering::Acquire) != seen_generation {
+        // Someone refreshed while we were waiting for the lock.
+        return Ok(());
+    }
+
+    let cfg = context.config().await?;
+    let profile = profile_in_use(&cfg);
+    let creds = cfg
+        .credentials(&profile)
+        .with_context(|| format!("Auth profile '{profile}' not found"))?;
+    if creds.refresh_token.is_empty() || creds.device_id.is_empty() {
+        anyhow::bail!("refresh token and device id are not set for profile '{profile}'");
+    }
+
+    client
+        .set_refresh_token_and_device_id(&creds.refresh_token, &creds.device_id)
+        .await?;
+    let pair = client
+        .refresh_tokens()
+        .await
+        .context("Failed to refresh access token")?;
+
+    context
+        .save_credentials(
+            &profile,
+            AuthProfile {
+                access_token: pair.access_token,
+                refresh_token: pair.refresh_token,
+                device_id: creds.device_id,
+                token_expires_at: None,
+            },
+        )
+        .await?;
+    context.refresh_generation.fetch_add(1, Ordering::AcqRel);
+
+    log_info!("Access token expired, refreshed it");
+    Ok(())
+}
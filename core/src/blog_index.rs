@@ -1,11 +1,13 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
-use crate::download_options::{DownloadOption, DownloadOptions, ordered_options};
+use crate::blog_settings::BlogSettings;
+use crate::download_options::{DownloadOption, ordered_options};
+use crate::session_report::UrlKind;
 
 pub const SIDECAR_NAME: &str = ".boosty.json";
 
@@ -16,6 +18,24 @@ pub struct BlogIndex {
     pub last_checked_at: Option<i64>,
     #[serde(default)]
     pub posts: HashMap<String, PostRecord>,
+    #[serde(default)]
+    pub settings: BlogSettings,
+    /// What failed in the last session that went through each post.
+    #[serde(default)]
+    pub failures: Vec<FailedItem>,
+}
+
+/// A post, or one file of it, that failed to download.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedItem {
+    pub post_id: String,
+    pub title: String,
+    /// `None` when the whole post failed.
+    #[serde(default)]
+    pub item_id: Option<String>,
+    #[serde(default)]
+    pub url_kind: Option<UrlKind>,
+    pub error: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +50,10 @@ pub struct PostRecord {
     pub last_checked_at: Option<i64>,
     #[serde(default)]
     pub is_paid: bool,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 pub fn sidecar_path(download_path: &Path, blog: &str) -> PathBuf {
@@ -69,6 +93,45 @@ pub async fn save(download_path: &Path, index: &BlogIndex) -> Result<()> {
     Ok(())
 }
 
+pub async fn load_settings(download_path: &Path, blog: &str) -> Result<BlogSettings> {
+    Ok(load(download_path, blog).await?.settings)
+}
+
+pub async fn save_settings(download_path: &Path, blog: &str, settings: BlogSettings) -> Result<()> {
+    let mut index = load(download_path, blog).await?;
+    index.settings = settings;
+    save(download_path, &index).await
+}
+
+/// Replaces the failures of the posts a session went through with the ones it ended with.
+pub async fn record_failures(
+    download_path: &Path,
+    blog: &str,
+    attempted: &HashSet<String>,
+    failures: Vec<FailedItem>,
+) -> Result<()> {
+    let mut index = load(download_path, blog).await?;
+    let before = index.failures.len();
+    index.failures.retain(|f| !attempted.contains(&f.post_id));
+    if before == index.failures.len() && failures.is_empty() {
+        return Ok(());
+    }
+    index.failures.extend(failures);
+    save(download_path, &index).await
+}
+
+/// Posts with failures, in the order they failed.
+pub async fn failed_post_ids(download_path: &Path, blog: &str) -> Result<Vec<String>> {
+    let index = load(download_path, blog).await?;
+    let mut seen = HashSet::new();
+    Ok(index
+        .failures
+        .into_iter()
+        .filter(|f| seen.insert(f.post_id.clone()))
+        .map(|f| f.post_id)
+        .collect())
+}
+
 pub async fn resolve_post_folder(
     download_path: &Path,
     blog: &str,
@@ -86,43 +149,41 @@ pub async fn resolve_post_folder(
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Folders of the blog's posts other than `post_id`, relative to the blog folder.
+pub async fn folders_of_other_posts(
+    download_path: &Path,
+    blog: &str,
+    post_id: &str,
+) -> Result<HashSet<String>> {
+    let index = load(download_path, blog).await?;
+    Ok(index
+        .posts
+        .iter()
+        .filter(|(id, _)| id.as_str() != post_id)
+        .map(|(_, record)| record.folder.clone())
+        .collect())
+}
+
+/// Stores `record` for the post; its `downloaded_options` are added to the ones already recorded.
 pub async fn upsert_post(
     download_path: &Path,
     blog: &str,
     post_id: &str,
-    title: &str,
-    folder_name: &str,
-    created_at: i64,
-    updated_at: i64,
-    downloaded_options: &DownloadOptions,
-    is_paid: bool,
+    mut record: PostRecord,
 ) -> Result<()> {
     let mut index = load(download_path, blog).await?;
     index.blog = blog.to_string();
 
-    let merged = match index.posts.get(post_id) {
-        Some(existing) => {
-            let mut set: std::collections::HashSet<_> =
-                existing.downloaded_options.iter().cloned().collect();
-            set.extend(downloaded_options.iter().cloned());
-            ordered_options(set)
-        }
-        None => ordered_options(downloaded_options.iter().cloned()),
-    };
+    if let Some(existing) = index.posts.get(post_id) {
+        let mut set: HashSet<_> = existing.downloaded_options.iter().cloned().collect();
+        set.extend(record.downloaded_options);
+        record.downloaded_options = ordered_options(set);
+    } else {
+        record.downloaded_options = ordered_options(record.downloaded_options);
+    }
+    record.last_checked_at = Some(Utc::now().timestamp());
 
-    index.posts.insert(
-        post_id.to_string(),
-        PostRecord {
-            title: title.to_string(),
-            folder: folder_name.to_string(),
-            created_at,
-            updated_at,
-            downloaded_options: merged,
-            last_checked_at: Some(Utc::now().timestamp()),
-            is_paid,
-        },
-    );
+    index.posts.insert(post_id.to_string(), record);
 
     save(download_path, &index).await
 }
@@ -130,7 +191,8 @@ pub async fn upsert_post(
 pub async fn remove_post(download_path: &Path, blog: &str, post_id: &str) -> Result<()> {
     let mut index = load(download_path, blog).await?;
     index.posts.remove(post_id);
-    if index.posts.is_empty() {
+    index.failures.retain(|f| f.post_id != post_id);
+    if index.posts.is_empty() && index.settings.is_empty() && index.failures.is_empty() {
         let path = sidecar_path(download_path, blog);
         if fs::try_exists(&path).await.unwrap_or(false) {
             fs::remove_file(&path).await.ok();
@@ -188,10 +250,27 @@ pub async fn list_blog_names(download_path: &Path) -> Result<Vec<String>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashSet;
-    use std::sync::Arc;
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    fn record(
+        title: &str,
+        folder: &str,
+        updated_at: i64,
+        options: &[DownloadOption],
+    ) -> PostRecord {
+        PostRecord {
+            title: title.to_string(),
+            folder: folder.to_string(),
+            created_at: 1,
+            updated_at,
+            downloaded_options: options.to_vec(),
+            last_checked_at: None,
+            is_paid: false,
+            author: String::new(),
+            tags: Vec::new(),
+        }
+    }
+
     fn temp_base() -> PathBuf {
         let nanos = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -212,10 +291,14 @@ mod tests {
         let post_dir = base.join(blog).join(folder);
         fs::create_dir_all(&post_dir).await.unwrap();
 
-        let opts: DownloadOptions = Arc::new(HashSet::from([DownloadOption::Images]));
-        upsert_post(&base, blog, post_id, "Title", folder, 1, 10, &opts, false)
-            .await
-            .unwrap();
+        upsert_post(
+            &base,
+            blog,
+            post_id,
+            record("Title", folder, 10, &[DownloadOption::Images]),
+        )
+        .await
+        .unwrap();
 
         let resolved = resolve_post_folder(&base, blog, post_id)
             .await
@@ -223,17 +306,14 @@ mod tests {
             .unwrap();
         assert_eq!(resolved, post_dir);
 
-        let more: DownloadOptions = Arc::new(HashSet::from([DownloadOption::Video]));
         upsert_post(
             &base,
             blog,
             post_id,
-            "New title",
-            folder,
-            1,
-            20,
-            &more,
-            true,
+            PostRecord {
+                is_paid: true,
+                ..record("New title", folder, 20, &[DownloadOption::Video])
+            },
         )
         .await
         .unwrap();
@@ -256,8 +336,7 @@ mod tests {
         let base = temp_base();
         let blog = "author";
         let folder = "gone [id]";
-        let opts: DownloadOptions = Arc::new(HashSet::new());
-        upsert_post(&base, blog, "id", "t", folder, 1, 1, &opts, false)
+        upsert_post(&base, blog, "id", record("t", folder, 1, &[]))
             .await
             .unwrap();
 
@@ -268,4 +347,43 @@ mod tests {
 
         let _ = fs::remove_dir_all(&base).await;
     }
+
+    #[tokio::test]
+    async fn failures_are_replaced_only_for_attempted_posts() {
+        let base = temp_base();
+        let blog = "author";
+        let failed = |post_id: &str, item_id: Option<&str>| FailedItem {
+            post_id: post_id.to_string(),
+            title: post_id.to_string(),
+            item_id: item_id.map(str::to_string),
+            url_kind: item_id.map(|_| UrlKind::File),
+            error: "HTTP 500".to_string(),
+        };
+        let attempted =
+            |ids: &[&str]| -> HashSet<String> { ids.iter().map(|id| id.to_string()).collect() };
+
+        record_failures(
+            &base,
+            blog,
+            &attempted(&["a", "b"]),
+            vec![
+                failed("a", Some("1")),
+                failed("b", None),
+                failed("a", Some("2")),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(failed_post_ids(&base, blog).await.unwrap(), ["a", "b"]);
+
+        record_failures(&base, blog, &attempted(&["a"]), Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(failed_post_ids(&base, blog).await.unwrap(), ["b"]);
+
+        remove_post(&base, blog, "b").await.unwrap();
+        assert!(!fs::try_exists(sidecar_path(&base, blog)).await.unwrap());
+
+        let _ = fs::remove_dir_all(&base).await;
+    }
 }
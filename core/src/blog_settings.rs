@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use boosty_api::model::Post;
+use boosty_api::traits::HasTitle;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::download_options::{DownloadOption, DownloadOptions, ordered_options};
+
+/// Settings of one blog that take precedence over the global config when it is downloaded
+/// or checked. Stored in the blog's `.boosty.json`; `None` fields follow the global config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlogSettings {
+    /// Content types to download instead of the ones chosen for the run.
+    pub download_options: Option<Vec<DownloadOption>>,
+    pub comments_enabled: Option<bool>,
+    pub reply_limit: Option<u32>,
+    pub comments_limit: Option<u32>,
+    pub posts_limit: Option<usize>,
+    pub filter: PostFilter,
+    /// Files written for each post; `index.html` alone when empty.
+    pub output_formats: Vec<OutputFormat>,
+    /// Largest size of the blog folder, in MB, instead of the global blog quota.
+    pub quota_mb: Option<u64>,
+}
+
+/// Which posts of the blog feed are downloaded. An empty filter lets every post through.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostFilter {
+    /// Keep posts whose title contains any of these (case-insensitive).
+    pub title_includes: Vec<String>,
+    /// Drop posts whose title contains any of these (case-insensitive).
+    pub title_excludes: Vec<String>,
+    /// Keep posts with at least one of these tags.
+    pub tags: Vec<String>,
+    pub skip_paid: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// `index.html` with the post and its comments.
+    Html,
+    /// `post.txt` with the post text, links and comments.
+    Text,
+}
+
+impl BlogSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The global config with this blog's comment, limit and quota overrides applied.
+    pub fn apply_to(&self, cfg: &AppConfig) -> AppConfig {
+        let mut cfg = cfg.clone();
+        if let Some(limit) = self.posts_limit {
+            cfg.posts_limit = limit;
+        }
+        if let Some(enabled) = self.comments_enabled {
+            cfg.comments.enabled = enabled;
+        }
+        if let Some(limit) = self.reply_limit {
+            cfg.comments.reply_limit = Some(limit);
+        }
+        if let Some(limit) = self.comments_limit {
+            cfg.comments.limit = Some(limit);
+        }
+        if let Some(quota) = self.quota_mb {
+            cfg.storage.blog_quota_mb = Some(quota);
+        }
+        cfg
+    }
+
+    pub fn download_options(&self) -> Option<DownloadOptions> {
+        self.download_options
+            .as_ref()
+            .map(|options| Arc::new(options.iter().cloned().collect::<HashSet<_>>()))
+    }
+
+    pub fn set_download_options(&mut self, options: Option<&DownloadOptions>) {
+        self.download_options = options.map(|set| ordered_options(set.iter().cloned()));
+    }
+
+    pub fn output_formats(&self) -> Vec<OutputFormat> {
+        if self.output_formats.is_empty() {
+            vec![OutputFormat::Html]
+        } else {
+            self.output_formats.clone()
+        }
+    }
+}
+
+impl PostFilter {
+    pub fn matches(&self, post: &Post) -> bool {
+        let tags: Vec<&str> = post.tags.iter().map(|t| t.title.as_str()).collect();
+        self.matches_fields(&post.safe_title(), &tags, post.price > 0.0)
+    }
+
+    fn matches_fields(&self, title: &str, tags: &[&str], is_paid: bool) -> bool {
+        if self.skip_paid && is_paid {
+            return false;
+        }
+        let title = title.to_lowercase();
+        let in_title = |word: &String| title.contains(&word.to_lowercase());
+        if !self.title_includes.is_empty() && !self.title_includes.iter().any(in_title) {
+            return false;
+        }
+        if self.title_excludes.iter().any(in_title) {
+            return false;
+        }
+        self.tags.is_empty()
+            || self
+                .tags
+                .iter()
+                .any(|tag| tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_checks_title_tags_and_price() {
+        let filter = PostFilter {
+            title_includes: vec!["Episode".into()],
+            title_excludes: vec!["trailer".into()],
+            tags: vec!["podcast".into()],
+            skip_paid: true,
+        };
+        assert!(filter.matches_fields("episode 12", &["Podcast"], false));
+        assert!(!filter.matches_fields("Episode 12 trailer", &["podcast"], false));
+        assert!(!filter.matches_fields("Stream", &["podcast"], false));
+        assert!(!filter.matches_fields("Episode 13", &["news"], false));
+        assert!(!filter.matches_fields("Episode 14", &["podcast"], true));
+        assert!(PostFilter::default().matches_fields("anything", &[], true));
+    }
+
+    #[test]
+    fn overrides_apply_only_where_set() {
+        let settings = BlogSettings {
+            comments_enabled: Some(false),
+            posts_limit: Some(5),
+            ..BlogSettings::default()
+        };
+        let cfg = settings.apply_to(&AppConfig::default());
+        assert_eq!(cfg.posts_limit, 5);
+        assert!(!cfg.comments.enabled);
+        assert_eq!(cfg.comments.reply_limit, Some(10));
+        assert!(settings.download_options().is_none());
+        assert_eq!(settings.output_formats(), vec![OutputFormat::Html]);
+        assert!(BlogSettings::default().is_empty());
+    }
+}
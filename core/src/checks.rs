@@ -3,17 +3,38 @@ use boosty_api::api_client::ApiClient;
 use std::time::Duration;
 use tokio::time::timeout;
 
+use crate::proxy::{self, ProxyConfig};
+
 /// Check if the Boosty API is reachable
 ///
 /// # Errors
 ///
-/// Returns an error if the Boosty API is not reachable
-pub async fn check_api(client: &ApiClient) -> anyhow::Result<()> {
+/// Returns an error if the Boosty API is not reachable, naming the proxy when the
+/// connection goes through one
+pub async fn check_api(client: &ApiClient, proxy: &ProxyConfig) -> anyhow::Result<()> {
     let fake_blog = "nonexistent";
     let fut = client.get_posts(fake_blog, 1, None, None);
     match timeout(Duration::from_secs(5), fut).await {
         Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) if proxy.enabled => {
+            let err = anyhow::Error::from(e);
+            let hint = if proxy::is_connect_error(&err) {
+                ": the proxy is unreachable or refused the connection, check its address, port and credentials"
+            } else {
+                ""
+            };
+            Err(err).with_context(|| {
+                format!(
+                    "Failed to reach Boosty API through proxy {}{hint}",
+                    proxy.display_url()
+                )
+            })
+        }
         Ok(Err(e)) => Err(e).with_context(|| "Failed to reach Boosty API"),
+        Err(_) if proxy.enabled => Err(anyhow::anyhow!(
+            "Timeout when connecting to Boosty API through proxy {}",
+            proxy.display_url()
+        )),
         Err(_) => Err(anyhow::anyhow!("Timeout when connecting to Boosty API")),
     }
 }
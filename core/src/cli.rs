@@ -1,12 +1,32 @@
 use crate::{
-    DownloadOption, DownloadOptions, file_handler::DownloadResult, log_error, log_info, log_warn,
+    DownloadOption, DownloadOptions,
+    blog_settings::{BlogSettings, OutputFormat},
+    download_options::ordered_options,
+    file_handler::DownloadResult,
+    log_error, log_info, log_warn,
+    logger::LogVerbosity,
+    media_client::MediaClientConfig,
+    naming::{CollisionPolicy, NamingConfig, RenameReport},
+    retry::RetryConfig,
+    session_report::SessionReport,
+    storage::StorageConfig,
+    throttle::ThrottleConfig,
 };
 use anyhow::Error;
-use dialoguer::{Confirm, Input, MultiSelect, Select, theme::ColorfulTheme};
+use chrono::DateTime;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use dialoguer::{Confirm, Input, MultiSelect, Password, Select, theme::ColorfulTheme};
 use std::{
     collections::{HashMap, HashSet},
+    io::IsTerminal,
     path::Path,
-    sync::Arc,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+    time::Duration,
 };
 
 pub const ENTER_URL: &str = "Enter URL:";
@@ -17,8 +37,22 @@ pub const ENTER_ACCESS_TOKEN: &str = "Enter access token:";
 pub const ENTER_REFRESH_TOKEN: &str = "Enter refresh token:";
 pub const ENTER_CLIENT_ID: &str = "Enter client id:";
 pub const ENTER_POSTS_LIMIT: &str = "Enter posts limit:";
-pub const ENTER_DOWNLOAD_PATH: &str =
-    "Enter download path (or press enter to use default - binary folder):";
+pub const ENTER_DOWNLOAD_PATH: &str = "Enter download path (or press enter to use the default):";
+pub const PAUSE_KEY_HINT: &str = "(p: pause)";
+pub const PAUSED_HINT: &str = "PAUSED (p: resume)";
+
+const PAUSE_KEY_POLL: Duration = Duration::from_millis(200);
+
+/// Held by the pause key listener while it reads a key and by prompts shown during a
+/// session, so the two never read stdin at the same time.
+static STDIN: Mutex<()> = Mutex::new(());
+
+/// Keeps the pause key listener off stdin until dropped; every prompt takes it first.
+fn hold_stdin() -> MutexGuard<'static, ()> {
+    STDIN
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
 
 pub fn info(msg: &str) {
     println!("\x1b[34mInfo:\x1b[0m {msg}");
@@ -32,22 +66,95 @@ pub fn warning(msg: &str) {
     println!("\x1b[33mWarning:\x1b[0m {msg}");
 }
 
-pub fn read_input_menu() -> i8 {
-    let items = vec![
-        "Download content from URL (blog or post)",
-        "Download content from a list of URLs (file)",
-        "Manage downloaded content",
-        "Enter access token",
-        "Enter refresh token and client id",
-        "Clear tokens and client id",
-        "Change posts limit",
-        "Change download path",
-        "Toggle comments download",
-        "Show API client headers",
-        "Show config",
-        "Exit",
+pub fn debug(level: &str, msg: &str) {
+    println!("\x1b[90m{level}: {msg}\x1b[0m");
+}
+
+/// Entries of the main menu, in the order they are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    DownloadUrl,
+    DownloadBatch,
+    DownloadSubscriptions,
+    ManageDownloaded,
+    AccessToken,
+    RefreshToken,
+    ClearTokens,
+    AuthProfiles,
+    PostsLimit,
+    DownloadPath,
+    Naming,
+    StorageLimits,
+    SpeedLimits,
+    ToggleComments,
+    ToggleAllReplies,
+    ToggleSessionReports,
+    LogVerbosity,
+    ExportDiagnostics,
+    ShowHeaders,
+    CheckAuth,
+    ShowConfig,
+    Exit,
+}
+
+impl MenuItem {
+    const ALL: [Self; 22] = [
+        Self::DownloadUrl,
+        Self::DownloadBatch,
+        Self::DownloadSubscriptions,
+        Self::ManageDownloaded,
+        Self::AccessToken,
+        Self::RefreshToken,
+        Self::ClearTokens,
+        Self::AuthProfiles,
+        Self::PostsLimit,
+        Self::DownloadPath,
+        Self::Naming,
+        Self::StorageLimits,
+        Self::SpeedLimits,
+        Self::ToggleComments,
+        Self::ToggleAllReplies,
+        Self::ToggleSessionReports,
+        Self::LogVerbosity,
+        Self::ExportDiagnostics,
+        Self::ShowHeaders,
+        Self::CheckAuth,
+        Self::ShowConfig,
+        Self::Exit,
     ];
 
+    fn label(self) -> &'static str {
+        match self {
+            Self::DownloadUrl => "Download content from URL (blog or post)",
+            Self::DownloadBatch => "Download content from a list of URLs (file)",
+            Self::DownloadSubscriptions => "Download all my subscriptions",
+            Self::ManageDownloaded => "Manage downloaded content",
+            Self::AccessToken => "Enter access token",
+            Self::RefreshToken => "Enter refresh token and client id",
+            Self::ClearTokens => "Clear tokens and client id",
+            Self::AuthProfiles => "Auth profiles",
+            Self::PostsLimit => "Change posts limit",
+            Self::DownloadPath => "Change download path",
+            Self::Naming => "File naming templates",
+            Self::StorageLimits => "Storage limits",
+            Self::SpeedLimits => "Speed and request limits",
+            Self::ToggleComments => "Toggle comments download",
+            Self::ToggleAllReplies => "Toggle fetching all comment replies",
+            Self::ToggleSessionReports => "Toggle saving session reports",
+            Self::LogVerbosity => "Log verbosity",
+            Self::ExportDiagnostics => "Export diagnostics",
+            Self::ShowHeaders => "Show API client headers",
+            Self::CheckAuth => "Check auth / account info",
+            Self::ShowConfig => "Show config",
+            Self::Exit => "Exit",
+        }
+    }
+}
+
+pub fn read_input_menu() -> MenuItem {
+    let _stdin = hold_stdin();
+    let items: Vec<&str> = MenuItem::ALL.iter().map(|item| item.label()).collect();
+
     let selection = Select::with_theme(&ColorfulTheme::default())
         .with_prompt("Select an option")
         .items(&items)
@@ -55,12 +162,13 @@ pub fn read_input_menu() -> i8 {
         .interact_opt();
 
     match selection {
-        Ok(Some(index)) => (index) as i8,
-        _ => 11,
+        Ok(Some(index)) => MenuItem::ALL[index],
+        _ => MenuItem::Exit,
     }
 }
 
 pub fn read_download_url_and_offset() -> Option<(String, String)> {
+    let _stdin = hold_stdin();
     let url: String = Input::<String>::with_theme(&ColorfulTheme::default())
         .with_prompt(ENTER_URL)
         .interact_text()
@@ -80,6 +188,7 @@ pub fn read_download_url_and_offset() -> Option<(String, String)> {
 }
 
 pub fn read_batch_file_path() -> Option<String> {
+    let _stdin = hold_stdin();
     let result: Result<String, _> = Input::<String>::with_theme(&ColorfulTheme::default())
         .with_prompt(ENTER_URLS_FILE)
         .interact_text();
@@ -98,6 +207,7 @@ pub fn read_batch_file_path() -> Option<String> {
 }
 
 pub fn read_comments_status(current_enabled: bool) -> Option<bool> {
+    let _stdin = hold_stdin();
     let options = vec!["Enabled", "Disabled"];
 
     let default_index = if current_enabled { 0 } else { 1 };
@@ -114,7 +224,80 @@ pub fn read_comments_status(current_enabled: bool) -> Option<bool> {
     }
 }
 
+pub fn read_all_replies_status(current_enabled: bool) -> Option<bool> {
+    let _stdin = hold_stdin();
+    let options = vec!["Enabled", "Disabled"];
+
+    let default_index = if current_enabled { 0 } else { 1 };
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Fetch every reply of long comment threads (paged, rate limited)")
+        .items(&options)
+        .default(default_index)
+        .interact_opt();
+
+    match selection {
+        Ok(Some(index)) => Some(index == 0),
+        _ => None,
+    }
+}
+
+pub fn read_session_report_status(current_enabled: bool) -> Option<bool> {
+    let _stdin = hold_stdin();
+    let options = vec!["Enabled", "Disabled"];
+
+    let default_index = if current_enabled { 0 } else { 1 };
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Save a JSON report of each download session to the blog folder")
+        .items(&options)
+        .default(default_index)
+        .interact_opt();
+
+    match selection {
+        Ok(Some(index)) => Some(index == 0),
+        _ => None,
+    }
+}
+
+pub fn read_log_verbosity(current: LogVerbosity) -> Option<LogVerbosity> {
+    let _stdin = hold_stdin();
+    let options = [
+        (LogVerbosity::Normal, "Normal: info, warnings and errors"),
+        (
+            LogVerbosity::Debug,
+            "Debug: also HTTP statuses, URLs and timings",
+        ),
+        (LogVerbosity::Trace, "Trace: also every API call"),
+    ];
+    let labels: Vec<&str> = options.iter().map(|(_, label)| *label).collect();
+    let default_index = options.iter().position(|(v, _)| *v == current).unwrap_or(0);
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Log verbosity")
+        .items(&labels)
+        .default(default_index)
+        .interact_opt();
+
+    match selection {
+        Ok(Some(index)) => Some(options[index].0),
+        _ => None,
+    }
+}
+
+pub fn read_diagnostics_dir(default: &Path) -> Option<String> {
+    let _stdin = hold_stdin();
+    Input::<String>::with_theme(&ColorfulTheme::default())
+        .with_prompt("Folder to save the diagnostics file to")
+        .default(default.display().to_string())
+        .interact_text()
+        .ok()
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty())
+}
+
 pub fn read_posts_limit(current_limit: usize) -> Option<usize> {
+    let _stdin = hold_stdin();
     let prompt = format!("{} (current: {})", ENTER_POSTS_LIMIT, current_limit);
 
     let result: Result<usize, _> = Input::with_theme(&ColorfulTheme::default())
@@ -126,7 +309,8 @@ pub fn read_posts_limit(current_limit: usize) -> Option<usize> {
 }
 
 pub fn read_download_path(current_path: Option<&str>) -> Option<Option<String>> {
-    let default_display = current_path.unwrap_or("(default - binary folder)");
+    let _stdin = hold_stdin();
+    let default_display = current_path.unwrap_or("(default)");
     let prompt = format!("{} (current: {})", ENTER_DOWNLOAD_PATH, default_display);
 
     let result: Result<String, _> = Input::with_theme(&ColorfulTheme::default())
@@ -154,6 +338,7 @@ pub fn read_download_path(current_path: Option<&str>) -> Option<Option<String>>
 }
 
 pub fn read_access_token() -> Option<String> {
+    let _stdin = hold_stdin();
     let result: Result<String, _> = Input::<String>::with_theme(&ColorfulTheme::default())
         .with_prompt(ENTER_ACCESS_TOKEN)
         .interact();
@@ -172,6 +357,7 @@ pub fn read_access_token() -> Option<String> {
 }
 
 pub fn read_refresh_and_client_id() -> Option<(String, String)> {
+    let _stdin = hold_stdin();
     let refresh_token: String = Input::<String>::with_theme(&ColorfulTheme::default())
         .with_prompt(ENTER_REFRESH_TOKEN)
         .interact()
@@ -198,6 +384,7 @@ pub fn read_refresh_and_client_id() -> Option<(String, String)> {
 }
 
 pub fn read_download_options() -> Option<DownloadOptions> {
+    let _stdin = hold_stdin();
     let options = [
         (DownloadOption::Video, "Video (Vide content)"),
         (DownloadOption::Audio, "Audio (Audio content)"),
@@ -231,6 +418,108 @@ pub fn read_download_options() -> Option<DownloadOptions> {
     }
 }
 
+/// Background reader of the pause key while a download session is shown in the terminal.
+pub struct PauseKeyListener {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PauseKeyListener {
+    pub fn spawn() -> Option<Self> {
+        if !std::io::stdin().is_terminal() {
+            return None;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                // A prompt is reading stdin; wait for it instead of taking its keys.
+                let Ok(_stdin) = STDIN.try_lock() else {
+                    std::thread::sleep(PAUSE_KEY_POLL);
+                    continue;
+                };
+                // Dropped before the lock, so prompts always get the terminal back as it was.
+                let Some(keys) = SingleKeys::enable() else {
+                    return;
+                };
+                if !event::poll(PAUSE_KEY_POLL).unwrap_or(false) {
+                    continue;
+                }
+                let Ok(Event::Key(key)) = event::read() else {
+                    continue;
+                };
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('p') | KeyCode::Char('P') => {
+                        if crate::pause::toggle() {
+                            log_info!("Download paused");
+                        } else {
+                            log_info!("Download resumed");
+                        }
+                    }
+                    // Raw mode turns Ctrl+C into a key where it doesn't keep signals.
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        drop(keys);
+                        std::process::exit(130);
+                    }
+                    _ => {}
+                }
+            }
+        });
+        Some(Self {
+            stop,
+            handle: Some(handle),
+        })
+    }
+}
+
+/// Raw mode, so the pause key works without Enter, until dropped. On Unix output processing
+/// and signals stay on: log lines and progress bars printed meanwhile still start at the
+/// left edge, and Ctrl+C still stops the program.
+struct SingleKeys;
+
+impl SingleKeys {
+    fn enable() -> Option<Self> {
+        terminal::enable_raw_mode().ok()?;
+        #[cfg(unix)]
+        keep_output_and_signals();
+        Some(Self)
+    }
+}
+
+impl Drop for SingleKeys {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(unix)]
+fn keep_output_and_signals() {
+    use std::os::fd::AsRawFd;
+
+    let fd = std::io::stdin().as_raw_fd();
+    // SAFETY: `termios` is plain data filled by `tcgetattr` before it is read.
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) == 0 {
+            termios.c_oflag |= libc::OPOST;
+            termios.c_lflag |= libc::ISIG;
+            libc::tcsetattr(fd, libc::TCSANOW, &termios);
+        }
+    }
+}
+
+impl Drop for PauseKeyListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
 pub fn exit_message() {
     println!("Exiting...");
 }
@@ -261,6 +550,62 @@ pub fn show_api_client_headers(headers: &HashMap<String, String>) {
     println!()
 }
 
+pub fn print_subscriptions_result(result: &crate::subscriptions::SubscriptionsResult) {
+    if !result.new_blogs.is_empty() {
+        info(&format!(
+            "New subscriptions: {}",
+            result.new_blogs.join(", ")
+        ));
+    }
+    info(&format!(
+        "Subscribed blogs downloaded: {}, posts: {}",
+        result.blogs, result.posts
+    ));
+    if !result.failed_blogs.is_empty() {
+        warning(&format!("Failed blogs: {}", result.failed_blogs.join(", ")));
+    }
+}
+
+pub fn print_account_info(account: &crate::account::AccountInfo) {
+    use crate::account::TokenStatus;
+
+    info(&format!("Auth profile: {}", account.profile));
+    let status = match account.token_status {
+        TokenStatus::Valid => "valid",
+        TokenStatus::Refreshed => "was expired, refreshed",
+        TokenStatus::Expired => "expired or revoked, enter new tokens",
+        TokenStatus::Missing => "not set",
+    };
+    println!("  Token: {status}");
+    if let Some(error) = &account.refresh_error {
+        println!("  Refresh failed: {error}");
+    }
+    match account
+        .token_expires_at
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+    {
+        Some(expires) => println!("  Token expires: {}", expires.format("%Y-%m-%d %H:%M UTC")),
+        None => println!("  Token expires: unknown"),
+    }
+    if let Some(user) = &account.user {
+        println!("  Logged in as: {} (id {})", user.name, user.id);
+    }
+    if account.user.is_some() {
+        if account.subscriptions.is_empty() {
+            println!("  No active subscriptions");
+        } else {
+            println!("  Subscriptions:");
+            for sub in &account.subscriptions {
+                println!(
+                    "    {} ({}): {}, {} RUB",
+                    sub.blog, sub.blog_title, sub.level_name, sub.level_price
+                );
+            }
+        }
+    }
+    println!()
+}
+
 fn masked_str(s: &str) -> String {
     if s.is_empty() {
         return s.to_string();
@@ -287,14 +632,64 @@ pub fn show_config(config: &crate::config::AppConfig) {
     println!("  Access token: {}", masked_str(&config.access_token));
     println!("  Refresh token: {}", masked_str(&config.refresh_token));
     println!("  Client id: {}", config.device_id);
+    println!("  Token storage: {:?}", config.token_storage);
+    println!("  Active profile: {}", config.active_profile);
+    if !config.profiles.is_empty() {
+        let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        println!("  Other profiles: {}", names.join(", "));
+    }
+    for (blog, profile) in &config.blog_profiles {
+        println!("  Blog '{blog}' uses profile: {profile}");
+    }
     println!("  Posts limit: {}", config.posts_limit);
     println!(
         "  Download path: {}",
-        config
-            .download_path
-            .as_deref()
-            .unwrap_or("(default - binary folder)")
+        config.download_path.as_deref().unwrap_or("(default)")
+    );
+    println!("  Post folder template: {}", config.naming.post_folder);
+    println!("  Save session reports: {}", config.save_session_report);
+    println!("  Log verbosity: {:?}", config.log_verbosity);
+    print_storage(&config.storage);
+    print_throttle(&config.throttle);
+    print_retry(&config.retry);
+    print_media_client(&config.media_client);
+    if config.proxy.enabled {
+        println!("  Proxy: {}", config.proxy.display_url());
+        if !config.proxy.no_proxy.is_empty() {
+            println!("  Without proxy: {}", config.proxy.no_proxy.join(", "));
+        }
+    }
+}
+
+fn print_media_client(media: &MediaClientConfig) {
+    println!("Media downloads:");
+    println!(
+        "  Timeouts: connect {} s, read {} s",
+        media.connect_timeout_secs, media.read_timeout_secs
     );
+    println!("  User agent: {}", media.user_agent);
+    for (name, value) in &media.extra_headers {
+        println!("  Header {name}: {value}");
+    }
+    println!("  HTTP/2: {}", media.http2);
+    for path in &media.ca_certificates {
+        println!("  CA certificate: {path}");
+    }
+}
+
+pub fn tokens_moved_to_secret_store() {
+    info("Tokens moved out of the config file into the secret store");
+}
+
+/// Asked once at startup, see [`ask_secrets_passphrase`](crate::ask_secrets_passphrase).
+pub fn read_secrets_passphrase(new_file: bool) -> Option<String> {
+    let _stdin = hold_stdin();
+    let mut prompt = Password::with_theme(&ColorfulTheme::default())
+        .with_prompt("Passphrase for the encrypted tokens file");
+    if new_file {
+        prompt = prompt.with_confirmation("Repeat passphrase", "Passphrases don't match");
+    }
+    prompt.interact().ok().filter(|p| !p.is_empty())
 }
 
 pub fn tokens_and_client_id_cleared() {
@@ -313,9 +708,75 @@ pub fn comments_toggled(status: &str) {
     info(&format!("Downloading comments {status}"));
 }
 
-pub fn error_while_loading_config(e: &Error) {
-    error(&format!("Error while loading config: {e}"));
-    warning("Config will be reset to default");
+pub fn all_replies_toggled(status: &str) {
+    info(&format!("Fetching all comment replies {status}"));
+}
+
+pub fn session_report_toggled(status: &str) {
+    info(&format!("Saving session reports {status}"));
+}
+
+pub fn log_verbosity_changed(verbosity: LogVerbosity, log_file: Option<&Path>) {
+    info(&format!("Log verbosity: {verbosity:?}"));
+    if let Some(path) = log_file {
+        info(&format!("Log file: {}", path.display()));
+    }
+}
+
+pub fn diagnostics_exported(path: &Path) {
+    info(&format!(
+        "Diagnostics saved to '{}'. Attach it to the bug report.",
+        path.display()
+    ));
+}
+
+pub fn print_session_report(report: &SessionReport) {
+    info(&format!(
+        "Session of '{}' finished in {:.1} s",
+        report.blog,
+        report.duration_ms as f64 / 1000.0
+    ));
+    println!(
+        "  Posts: {} downloaded, {} skipped, {} unavailable, {} failed",
+        report.posts_downloaded,
+        report.posts_skipped,
+        report.posts_unavailable.len(),
+        report.posts_failed.len()
+    );
+    println!(
+        "  Files: {} downloaded, {} skipped, {} failed, {:.1} MB written",
+        report.files_downloaded,
+        report.files_skipped,
+        report.files_failed.len(),
+        report.bytes as f64 / (1024.0 * 1024.0)
+    );
+    for post in &report.posts_failed {
+        println!("  Post '{}' failed: {}", post.title, post.reason);
+    }
+    for file in &report.files_failed {
+        println!(
+            "  File '{}' of post '{}' failed: {}",
+            file.file, file.post_title, file.reason
+        );
+    }
+}
+
+pub fn config_reset(e: &Error, backup: &Path) {
+    error(&format!("Error while loading config: {e:#}"));
+    warning(&format!(
+        "Config was reset to default, the unreadable file is saved as '{}'",
+        backup.display()
+    ));
+}
+
+pub fn config_upgraded(from: u32, changes: &[String], backup: &Path) {
+    warning(&format!(
+        "Config was updated from schema version {from}, the previous file is saved as '{}'",
+        backup.display()
+    ));
+    for change in changes {
+        warning(&format!("  {change}"));
+    }
 }
 
 pub fn print_error(e: &Error) {
@@ -329,14 +790,17 @@ pub fn print_error(e: &Error) {
 }
 
 pub fn read_downloaded_blog_action() -> i8 {
+    let _stdin = hold_stdin();
     let items = vec![
         "Check blog",
         "Show posts",
         "Download new posts",
         "Download / resume post",
         "Redownload post",
+        "Retry failed downloads",
         "Delete post",
         "Delete blog",
+        "Blog settings",
         "Back",
     ];
     let selection = Select::with_theme(&ColorfulTheme::default())
@@ -344,13 +808,473 @@ pub fn read_downloaded_blog_action() -> i8 {
         .items(&items)
         .default(0)
         .interact_opt();
+    match selection {
+        Ok(Some(index)) => index as i8,
+        _ => 9,
+    }
+}
+
+pub fn using_blog_download_options(blog: &str, options: &DownloadOptions) {
+    let names: Vec<String> = ordered_options(options.iter().cloned())
+        .iter()
+        .map(|o| format!("{o:?}"))
+        .collect();
+    info(&format!(
+        "Using content types saved for '{blog}': {}",
+        names.join(", ")
+    ));
+}
+
+pub fn print_blog_settings(blog: &str, settings: &BlogSettings) {
+    fn or_global<T: std::fmt::Debug>(value: &Option<T>) -> String {
+        value
+            .as_ref()
+            .map_or("(global)".to_string(), |v| format!("{v:?}"))
+    }
+    let list = |words: &[String]| {
+        if words.is_empty() {
+            "-".to_string()
+        } else {
+            words.join(", ")
+        }
+    };
+
+    println!("Settings of '{blog}':");
+    println!("  Content types: {}", or_global(&settings.download_options));
+    println!("  Comments: {}", or_global(&settings.comments_enabled));
+    println!("  Posts limit: {}", or_global(&settings.posts_limit));
+    println!("  Comments limit: {}", or_global(&settings.comments_limit));
+    println!("  Reply limit: {}", or_global(&settings.reply_limit));
+    println!(
+        "  Titles containing: {}",
+        list(&settings.filter.title_includes)
+    );
+    println!(
+        "  Titles not containing: {}",
+        list(&settings.filter.title_excludes)
+    );
+    println!("  Tags: {}", list(&settings.filter.tags));
+    println!("  Skip paid posts: {}", settings.filter.skip_paid);
+    println!("  Output formats: {:?}", settings.output_formats());
+    println!("  Storage quota, MB: {}", or_global(&settings.quota_mb));
+}
+
+pub fn read_blog_settings_action() -> i8 {
+    let _stdin = hold_stdin();
+    let items = vec![
+        "Content types",
+        "Comments on/off",
+        "Posts limit",
+        "Comments limit",
+        "Reply limit",
+        "Title must contain",
+        "Title must not contain",
+        "Tags",
+        "Skip paid posts",
+        "Output formats",
+        "Storage quota",
+        "Reset to global settings",
+        "Back",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Blog settings")
+        .items(&items)
+        .default(0)
+        .interact_opt();
+    match selection {
+        Ok(Some(index)) => index as i8,
+        _ => 12,
+    }
+}
+
+/// `Some(None)` to ask for content types on each download, `Some(Some(..))` to keep them.
+pub fn read_blog_download_options(has_own: bool) -> Option<Option<DownloadOptions>> {
+    let stdin = hold_stdin();
+    let items = vec![
+        "Ask on each download",
+        "Always download the same types for this blog",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Content types")
+        .items(&items)
+        .default(usize::from(has_own))
+        .interact_opt();
+    // The content types prompt takes it again.
+    drop(stdin);
+    match selection {
+        Ok(Some(0)) => Some(None),
+        Ok(Some(_)) => read_download_options().map(Some),
+        _ => None,
+    }
+}
+
+pub fn read_override_status(prompt: &str, current: Option<bool>) -> Option<Option<bool>> {
+    let _stdin = hold_stdin();
+    let options = vec!["Global setting", "Enabled", "Disabled"];
+    let default_index = match current {
+        None => 0,
+        Some(true) => 1,
+        Some(false) => 2,
+    };
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(&options)
+        .default(default_index)
+        .interact_opt();
+    match selection {
+        Ok(Some(0)) => Some(None),
+        Ok(Some(index)) => Some(Some(index == 1)),
+        _ => None,
+    }
+}
+
+/// Empty input clears the override; `None` when cancelled or not a number.
+pub fn read_optional_limit<T>(prompt: &str, current: Option<T>) -> Option<Option<T>>
+where
+    T: std::str::FromStr + std::fmt::Display,
+{
+    let _stdin = hold_stdin();
+    let current = current.map_or("global".to_string(), |v| v.to_string());
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("{prompt} (current: {current}, empty for global)"))
+        .allow_empty(true)
+        .interact_text()
+        .ok()?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Some(None);
+    }
+    match input.parse() {
+        Ok(value) => Some(Some(value)),
+        Err(_) => {
+            warning(&format!("'{input}' is not a number"));
+            None
+        }
+    }
+}
+
+/// Comma-separated words; empty input clears the list.
+pub fn read_word_list(prompt: &str, current: &[String]) -> Option<Vec<String>> {
+    let _stdin = hold_stdin();
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("{prompt} (comma-separated, empty to clear)"))
+        .with_initial_text(current.join(", "))
+        .allow_empty(true)
+        .interact_text()
+        .ok()?;
+    Some(
+        input
+            .split(',')
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+pub fn read_skip_paid(current: bool) -> Option<bool> {
+    let _stdin = hold_stdin();
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Skip paid posts of this blog?")
+        .default(current)
+        .interact_opt()
+        .ok()
+        .flatten()
+}
+
+pub fn confirm_move_archive(current: &Path, new_path: &str) -> bool {
+    let _stdin = hold_stdin();
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Move the downloaded blogs from '{}' to '{new_path}'? (No: only new downloads go there)",
+            current.display()
+        ))
+        .default(true)
+        .interact()
+        .unwrap_or(false)
+}
+
+pub fn confirm_resume_move(journal: &crate::archive_move::MoveJournal) -> bool {
+    let _stdin = hold_stdin();
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Unfinished move of the archive from '{}' to '{}' found ({} blogs done). Continue it? \
+             (No: forget it, moved blogs stay in the new folder)",
+            journal.from.display(),
+            journal.to.display(),
+            journal.moved_blogs.len()
+        ))
+        .default(true)
+        .interact()
+        .unwrap_or(false)
+}
+
+pub fn print_move_result(result: &crate::archive_move::MoveArchiveResult) {
+    info(&format!(
+        "Moved {} blogs to '{}' ({} files copied, {} bytes); it is now the download path",
+        result.blogs, result.to, result.files_copied, result.bytes_copied
+    ));
+}
+
+pub fn print_naming(naming: &NamingConfig) {
+    println!("File naming:");
+    println!("  Post folder: {}", naming.post_folder);
+    println!("  Image file: {}", naming.image_file);
+    println!("  Video file: {}", naming.video_file);
+    println!("  Audio and other files: {}", naming.media_file);
+    println!("  On name collision: {:?}", naming.collision);
+    println!(
+        "  Placeholders: {{blog}} {{author}} {{date}} {{year}} {{month}} {{day}} {{time}} \
+         {{title}} {{id}} {{tag}}; for files also {{post_id}} {{index}} {{filename}}"
+    );
+}
+
+pub fn print_storage(storage: &StorageConfig) {
+    let quota = |mb: Option<u64>| mb.map_or("none".to_string(), |mb| format!("{mb} MB"));
+    println!("Storage:");
+    println!(
+        "  Check free space before downloading: {}",
+        storage.preflight_check
+    );
+    println!("  Disk space reserve: {} MB", storage.reserve_mb);
+    println!("  Quota of each blog: {}", quota(storage.blog_quota_mb));
+    println!(
+        "  Quota of the download folder: {}",
+        quota(storage.total_quota_mb)
+    );
+}
+
+pub fn read_storage_action() -> i8 {
+    let _stdin = hold_stdin();
+    let items = vec![
+        "Check free space before downloading",
+        "Disk space reserve",
+        "Quota of each blog",
+        "Quota of the whole download folder",
+        "Back",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Storage limits")
+        .items(&items)
+        .default(0)
+        .interact_opt();
+    match selection {
+        Ok(Some(index)) => index as i8,
+        _ => 4,
+    }
+}
+
+pub fn read_preflight_check(current: bool) -> Option<bool> {
+    let _stdin = hold_stdin();
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Estimate the download size and check free space before downloading?")
+        .default(current)
+        .interact_opt()
+        .ok()
+        .flatten()
+}
+
+pub fn read_reserve_mb(current: u64) -> Option<u64> {
+    let _stdin = hold_stdin();
+    Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Free space to keep on the disk, MB")
+        .default(current)
+        .interact_text()
+        .ok()
+}
+
+pub fn print_throttle(throttle: &ThrottleConfig) {
+    println!("Speed limits:");
+    println!(
+        "  Download speed cap: {}",
+        throttle
+            .download_bytes_per_sec
+            .map_or("none".to_string(), |b| format!("{} KB/s", b / 1024))
+    );
+    println!(
+        "  API requests per minute: {}",
+        throttle
+            .api_requests_per_minute
+            .map_or("no limit".to_string(), |rpm| rpm.to_string())
+    );
+    println!("  API request jitter: up to {} ms", throttle.api_jitter_ms);
+}
+
+pub fn print_retry(retry: &RetryConfig) {
+    println!(
+        "  Download attempts per file: {}, waiting {} ms and more between them, at most {} s",
+        retry.attempts(),
+        retry.base_delay_ms,
+        retry.max_delay_secs
+    );
+}
+
+pub fn read_retry_attempts(current: u32) -> Option<u32> {
+    let _stdin = hold_stdin();
+    Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Download attempts per file")
+        .default(current)
+        .validate_with(|n: &u32| if *n > 0 { Ok(()) } else { Err("at least 1") })
+        .interact_text()
+        .ok()
+}
+
+pub fn read_max_retry_delay(current: u64) -> Option<u64> {
+    let _stdin = hold_stdin();
+    Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Longest wait between download attempts, s (Retry-After included)")
+        .default(current)
+        .interact_text()
+        .ok()
+}
+
+pub fn read_throttle_action() -> i8 {
+    let _stdin = hold_stdin();
+    let items = vec![
+        "Download speed cap",
+        "API requests per minute",
+        "API request jitter",
+        "Download attempts per file",
+        "Longest wait between attempts",
+        "Back",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Speed and request limits")
+        .items(&items)
+        .default(0)
+        .interact_opt();
+    match selection {
+        Ok(Some(index)) => index as i8,
+        _ => 5,
+    }
+}
+
+pub fn read_jitter_ms(current: u64) -> Option<u64> {
+    let _stdin = hold_stdin();
+    Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Random extra delay before rate-limited API requests, up to ms")
+        .default(current)
+        .interact_text()
+        .ok()
+}
+
+/// Empty input removes the limit; `None` when cancelled or not a number.
+pub fn read_cap(prompt: &str, current: Option<u64>) -> Option<Option<u64>> {
+    let _stdin = hold_stdin();
+    let current = current.map_or("none".to_string(), |value| value.to_string());
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("{prompt} (current: {current}, empty for no limit)"))
+        .allow_empty(true)
+        .interact_text()
+        .ok()?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Some(None);
+    }
+    match input.parse() {
+        Ok(value) => Some(Some(value)),
+        Err(_) => {
+            warning(&format!("'{input}' is not a number"));
+            None
+        }
+    }
+}
+
+pub fn read_naming_action() -> i8 {
+    let _stdin = hold_stdin();
+    let items = vec![
+        "Post folder template",
+        "Image file template",
+        "Video file template",
+        "Audio and other files template",
+        "On name collision",
+        "Reset to defaults",
+        "Rename downloaded folders to the template",
+        "Back",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("File naming")
+        .items(&items)
+        .default(0)
+        .interact_opt();
     match selection {
         Ok(Some(index)) => index as i8,
         _ => 7,
     }
 }
 
+pub fn read_template(prompt: &str, current: &str) -> Option<String> {
+    let _stdin = hold_stdin();
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .with_initial_text(current)
+        .interact_text()
+        .ok()?;
+    let input = input.trim();
+    (!input.is_empty()).then(|| input.to_string())
+}
+
+pub fn read_collision_policy(current: CollisionPolicy) -> Option<CollisionPolicy> {
+    let _stdin = hold_stdin();
+    let items = vec!["Add a number: name (2)", "Skip the later post or file"];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("When two posts or files get the same name")
+        .items(&items)
+        .default(usize::from(current == CollisionPolicy::Skip))
+        .interact_opt();
+    match selection {
+        Ok(Some(0)) => Some(CollisionPolicy::Suffix),
+        Ok(Some(_)) => Some(CollisionPolicy::Skip),
+        _ => None,
+    }
+}
+
+pub fn print_rename_report(report: &RenameReport, dry_run: bool) {
+    let verb = if dry_run { "Will move" } else { "Moved" };
+    for m in &report.moves {
+        println!("  {verb} {}/{} -> {}/{}", m.blog, m.from, m.blog, m.to);
+    }
+    for skipped in &report.skipped {
+        warning(&format!("Name taken, left as is: {skipped}"));
+    }
+    for failed in &report.failed {
+        error(&format!("Failed to move {failed}"));
+    }
+    if report.moves.is_empty() && report.skipped.is_empty() && report.failed.is_empty() {
+        info("All downloaded folders already match the template");
+    } else if !dry_run {
+        info(&format!("{} folders renamed", report.moves.len()));
+    }
+}
+
+pub fn confirm_rename(count: usize) -> bool {
+    let _stdin = hold_stdin();
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Rename {count} folders?"))
+        .default(false)
+        .interact()
+        .unwrap_or(false)
+}
+
+pub fn read_output_formats(current: &[OutputFormat]) -> Option<Vec<OutputFormat>> {
+    let _stdin = hold_stdin();
+    let formats = [
+        (OutputFormat::Html, "HTML page (index.html)"),
+        (OutputFormat::Text, "Plain text (post.txt)"),
+    ];
+    let items: Vec<&str> = formats.iter().map(|(_, text)| *text).collect();
+    let defaults: Vec<bool> = formats.iter().map(|(f, _)| current.contains(f)).collect();
+    let indices = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Files to write for each post (Space to toggle, Enter to confirm)")
+        .items(&items)
+        .defaults(&defaults)
+        .interact_opt()
+        .ok()??;
+    Some(indices.into_iter().map(|i| formats[i].0).collect())
+}
+
 pub fn select_blog_name(blogs: &[String]) -> Option<String> {
+    let _stdin = hold_stdin();
     if blogs.is_empty() {
         return None;
     }
@@ -370,7 +1294,82 @@ pub fn select_blog_name(blogs: &[String]) -> Option<String> {
     }
 }
 
+pub fn read_profiles_action(active: &str) -> i8 {
+    let _stdin = hold_stdin();
+    let items = vec![
+        "Switch profile",
+        "Add profile",
+        "Delete profile",
+        "Assign blog to profile",
+        "Back",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Auth profiles (active: {active})"))
+        .items(&items)
+        .default(0)
+        .interact_opt();
+    match selection {
+        Ok(Some(index)) => index as i8,
+        _ => 4,
+    }
+}
+
+pub fn select_profile(prompt: &str, profiles: &[String]) -> Option<String> {
+    let _stdin = hold_stdin();
+    if profiles.is_empty() {
+        return None;
+    }
+    let mut items = profiles.to_vec();
+    items.push("(Back)".to_string());
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .ok()
+        .flatten()?;
+    profiles.get(selection).cloned()
+}
+
+pub fn read_profile_name() -> Option<String> {
+    let _stdin = hold_stdin();
+    let name: String = Input::<String>::with_theme(&ColorfulTheme::default())
+        .with_prompt("Enter profile name")
+        .interact_text()
+        .ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+pub fn read_blog_name() -> Option<String> {
+    let _stdin = hold_stdin();
+    let name: String = Input::<String>::with_theme(&ColorfulTheme::default())
+        .with_prompt("Enter blog name (as in the blog URL)")
+        .interact_text()
+        .ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+pub fn profile_switched(name: &str) {
+    info(&format!("Active auth profile: {name}"));
+}
+
+pub fn profile_created(name: &str) {
+    info(&format!(
+        "Profile '{name}' created and activated, enter its tokens from the main menu"
+    ));
+}
+
+pub fn blog_profile_assigned(blog: &str, profile: Option<&str>) {
+    match profile {
+        Some(profile) => info(&format!("Blog '{blog}' will use profile '{profile}'")),
+        None => info(&format!("Blog '{blog}' will use the active profile")),
+    }
+}
+
 pub fn select_post_index(labels: &[String]) -> Option<usize> {
+    let _stdin = hold_stdin();
     if labels.is_empty() {
         return None;
     }
@@ -391,6 +1390,7 @@ pub fn select_post_index(labels: &[String]) -> Option<usize> {
 }
 
 pub fn confirm_delete(what: &str) -> bool {
+    let _stdin = hold_stdin();
     Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt(format!("Delete {what}?"))
         .default(false)
@@ -398,7 +1398,37 @@ pub fn confirm_delete(what: &str) -> bool {
         .unwrap_or(false)
 }
 
+pub fn confirm_resume_session(state: &crate::session_state::SessionState) -> bool {
+    let _stdin = hold_stdin();
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Interrupted download of '{}' found ({}/{} posts done). Continue it?",
+            state.blog,
+            state.completed_posts.len(),
+            state.post_ids.len()
+        ))
+        .default(true)
+        .interact()
+        .unwrap_or(true)
+}
+
+pub fn session_resumed(state: &crate::session_state::SessionState) {
+    info(&format!(
+        "Continuing interrupted download of '{}': {}/{} posts done",
+        state.blog,
+        state.completed_posts.len(),
+        state.post_ids.len()
+    ));
+}
+
 pub fn print_download_posts_result(result: &crate::downloaded::DownloadPostsResult) {
+    if result.failed > 0 {
+        warning(&format!(
+            "Downloaded {}, skipped {}, failed {}",
+            result.downloaded, result.skipped, result.failed
+        ));
+        return;
+    }
     if result.downloaded == 0 && result.skipped > 0 {
         info("Post skipped: no access or empty content");
         return;
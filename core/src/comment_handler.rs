@@ -1,7 +1,8 @@
-use std::path::Path;
+use std::{collections::HashSet, path::Path, time::Duration};
 
 use anyhow::{Context, Result};
 use boosty_api::{
+    api_client::ApiClient,
     media_content::ContentItem,
     model::Comment,
     traits::{HasContent, IsAvailable},
@@ -9,21 +10,159 @@ use boosty_api::{
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    DownloadOptions, cli, content_items_handler, download_options, file_handler, log_error,
+    DownloadOptions, auth, cli,
+    config::CommentsConfig,
+    content_items_handler, download_options, file_handler, log_error, log_warn,
+    naming::{MediaNamer, NamingConfig, PostFields},
     post_page, progress_reporter,
+    session_state::Checkpoint,
 };
 use post_page::{CommentView, PostPage};
 
+const REPLIES_PAGE_SIZE: u32 = 100;
+const DEFAULT_REPLIES_PAGE_DELAY_MS: u64 = 300;
+
 pub struct CommentsResult {
     pub comments: Vec<Comment>,
+    /// The post has more top-level comments than were fetched.
+    pub truncated: bool,
     pub post_id: String,
     pub safe_post_title: String,
 }
 
+/// Comments of a post and whether top-level comments were left out by the limit.
+pub async fn fetch_comments(
+    client: &ApiClient,
+    cfg: &CommentsConfig,
+    blog: &str,
+    post_id: &str,
+    cancel_token: &CancellationToken,
+) -> Result<(Vec<Comment>, bool)> {
+    crate::ensure_not_cancelled(cancel_token)?;
+    // One comment over the limit tells whether the post has more.
+    let endpoint = format!("/v1/blog/{blog}/post/{post_id}/comment/");
+    let mut comments = auth::call(client, &endpoint, || {
+        client.get_all_comments(
+            blog,
+            post_id,
+            cfg.limit.map(|limit| limit.saturating_add(1)),
+            cfg.reply_limit,
+            cfg.order.as_deref(),
+        )
+    })
+    .await
+    .with_context(|| format!("Failed to fetch comments for post '{post_id}'"))?;
+    let truncated = cfg
+        .limit
+        .is_some_and(|limit| comments.len() > limit as usize);
+    if let Some(limit) = cfg.limit {
+        comments.truncate(limit as usize);
+    }
+
+    if cfg.fetch_all_replies {
+        let delay = Duration::from_millis(
+            cfg.replies_page_delay_ms
+                .unwrap_or(DEFAULT_REPLIES_PAGE_DELAY_MS),
+        );
+        for comment in comments.iter_mut() {
+            fetch_remaining_replies(client, blog, post_id, comment, delay, cancel_token).await?;
+        }
+    }
+
+    Ok((comments, truncated))
+}
+
+/// Pages through the replies of one thread, nested ones included, until the API reports
+/// the last page.
+///
+/// A failed page, or one with only replies already fetched, is logged and leaves the thread
+/// marked as truncated.
+async fn fetch_remaining_replies(
+    client: &ApiClient,
+    blog: &str,
+    post_id: &str,
+    comment: &mut Comment,
+    delay: Duration,
+    cancel_token: &CancellationToken,
+) -> Result<()> {
+    let Some(replies) = comment.replies.as_mut() else {
+        return Ok(());
+    };
+
+    let mut seen: HashSet<_> = replies.data.iter().map(|r| r.int_id).collect();
+    while !replies.extra.is_last {
+        crate::ensure_not_cancelled(cancel_token)?;
+        let offset = replies.data.last().map(|r| r.int_id);
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = cancel_token.cancelled() => return Err(crate::Cancelled.into()),
+        }
+
+        let endpoint = format!(
+            "/v1/blog/{blog}/post/{post_id}/comment/?parent_id={}",
+            comment.int_id
+        );
+        let page = match auth::call(client, &endpoint, || {
+            client.get_comment_replies(
+                blog,
+                post_id,
+                comment.int_id,
+                Some(REPLIES_PAGE_SIZE),
+                offset,
+            )
+        })
+        .await
+        {
+            Ok(page) => page,
+            Err(e) => {
+                log_warn!(
+                    "Failed to fetch more replies for comment '{}' of post '{post_id}': {e:#}",
+                    comment.id
+                );
+                break;
+            }
+        };
+
+        if page.data.is_empty() {
+            replies.extra.is_last = true;
+            break;
+        }
+        let fetched = replies.data.len();
+        replies
+            .data
+            .extend(page.data.into_iter().filter(|r| seen.insert(r.int_id)));
+        if replies.data.len() == fetched {
+            log_warn!(
+                "Replies to comment '{}' of post '{post_id}' keep repeating, not all of them are saved",
+                comment.id
+            );
+            break;
+        }
+        replies.extra.is_last = page.extra.is_last;
+    }
+
+    for reply in replies.data.iter_mut() {
+        Box::pin(fetch_remaining_replies(
+            client,
+            blog,
+            post_id,
+            reply,
+            delay,
+            cancel_token,
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
 pub async fn process_comments(
     results: Vec<CommentsResult>,
     pages: &mut [PostPage],
     download_options: DownloadOptions,
+    naming: &NamingConfig,
+    mut checkpoint: Option<&mut Checkpoint>,
     cancel_token: &CancellationToken,
 ) -> Result<()> {
     if results.is_empty() {
@@ -34,7 +173,7 @@ pub async fn process_comments(
     progress_reporter::add_files_total(extra_files);
 
     for result in results {
-        crate::ensure_not_cancelled(cancel_token)?;
+        crate::pause::wait_if_paused(cancel_token).await?;
         let Some(page) = pages.iter_mut().find(|p| p.post_id == result.post_id) else {
             log_error!(
                 "No downloaded post page for comments of '{}'",
@@ -43,8 +182,16 @@ pub async fn process_comments(
             continue;
         };
 
-        if let Err(e) = process_one(page, &result, download_options.clone(), cancel_token).await {
-            if crate::is_cancelled_error(&e) {
+        if let Err(e) = process_one(
+            page,
+            &result,
+            download_options.clone(),
+            naming,
+            cancel_token,
+        )
+        .await
+        {
+            if crate::is_stop_error(&e) {
                 return Err(e);
             }
             log_error!(
@@ -53,6 +200,9 @@ pub async fn process_comments(
                 e
             );
         }
+        if let Some(checkpoint) = checkpoint.as_deref_mut() {
+            checkpoint.comments_done(&result.post_id).await?;
+        }
     }
     Ok(())
 }
@@ -61,11 +211,12 @@ async fn process_one(
     page: &mut PostPage,
     result: &CommentsResult,
     download_options: DownloadOptions,
+    naming: &NamingConfig,
     cancel_token: &CancellationToken,
 ) -> Result<()> {
     crate::ensure_not_cancelled(cancel_token)?;
 
-    process(page, result, download_options, cancel_token)
+    process(page, result, download_options, naming, cancel_token)
         .await
         .with_context(|| {
             format!(
@@ -83,6 +234,7 @@ async fn process(
     page: &mut PostPage,
     cr: &CommentsResult,
     download_options: DownloadOptions,
+    naming: &NamingConfig,
     cancel_token: &CancellationToken,
 ) -> Result<()> {
     crate::ensure_not_cancelled(cancel_token)?;
@@ -92,12 +244,13 @@ async fn process(
 
     let comments_folder_path = file_handler::prepare_folder_path_for_comments(&page.folder).await?;
 
+    let namer = MediaNamer::new(naming, PostFields::of_page(page));
     let mut comments = Vec::new();
     for comment in cr.comments.iter().filter(|c| !c.not_available()) {
         collect_comment_views(
             comment,
             0,
-            &page.title,
+            &namer,
             &comments_folder_path,
             &download_options,
             cancel_token,
@@ -107,13 +260,14 @@ async fn process(
     }
 
     page.comments = comments;
+    page.comments_truncated = cr.truncated;
     Ok(())
 }
 
 async fn collect_comment_views(
     comment: &Comment,
     level: u8,
-    post_title: &str,
+    namer: &MediaNamer<'_>,
     comments_folder_path: &Path,
     download_options: &DownloadOptions,
     cancel_token: &CancellationToken,
@@ -125,7 +279,7 @@ async fn collect_comment_views(
     let filtered = download_options::filter_content_items(items, download_options);
     let blocks = content_items_handler::process_content_items(
         filtered,
-        post_title,
+        namer,
         comments_folder_path,
         "comments/",
         None,
@@ -138,6 +292,7 @@ async fn collect_comment_views(
         created_at: comment.created_at as i64,
         level,
         blocks,
+        truncated: comment.replies.as_ref().is_some_and(|r| !r.extra.is_last),
     });
 
     if let Some(replies) = &comment.replies {
@@ -145,7 +300,7 @@ async fn collect_comment_views(
             Box::pin(collect_comment_views(
                 reply,
                 level.saturating_add(1),
-                post_title,
+                namer,
                 comments_folder_path,
                 download_options,
                 cancel_token,
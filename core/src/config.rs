@@ -1,59 +1,351 @@
 use anyhow::{Context, Result};
 use boosty_api::api_client::ApiClient;
+use chrono::Local;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::{
+    collections::BTreeMap,
     env,
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 use tokio::fs;
 
-use crate::cli;
+use crate::logger::LogVerbosity;
+use crate::media_client::MediaClientConfig;
+use crate::naming::NamingConfig;
+use crate::paths::{self, config_path};
+use crate::proxy::ProxyConfig;
+use crate::retry::RetryConfig;
+use crate::secrets::{self, TokenStorage};
+use crate::storage::StorageConfig;
+use crate::throttle::ThrottleConfig;
+use crate::{auth, cli, log_warn};
+
+/// Schema version written to `config.json`; bump it together with a new entry in [`MIGRATIONS`].
+///
+/// Fields added with their defaults need no migration of their own, but still bump it, so
+/// older builds see that the file is newer than them and leave it alone.
+pub const CONFIG_VERSION: u32 = 2;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Schema version of the file, see [`CONFIG_VERSION`].
+    pub version: u32,
     pub posts_limit: usize,
     pub access_token: String,
     pub refresh_token: String,
     pub device_id: String,
     pub comments: CommentsConfig,
     pub download_path: Option<String>,
+    /// Name of the profile whose credentials are the top-level tokens above.
+    pub active_profile: String,
+    /// Credentials of the other (inactive) profiles, by name.
+    pub profiles: BTreeMap<String, AuthProfile>,
+    /// Blog name -> profile to use for it instead of the active one.
+    pub blog_profiles: BTreeMap<String, String>,
+    /// Where the tokens are kept; they are left out of the config file unless this is `plain`.
+    pub token_storage: TokenStorage,
+    /// Expiry of `access_token` (unix seconds), known when it was pasted as the whole `auth` value.
+    pub token_expires_at: Option<i64>,
+    /// Templates for post folders and media file names.
+    pub naming: NamingConfig,
+    /// Free space reserve and download quotas.
+    pub storage: StorageConfig,
+    /// Download speed cap and API request rate limit.
+    pub throttle: ThrottleConfig,
+    /// Timeouts, headers and certificates of the media download client.
+    pub media_client: MediaClientConfig,
+    /// Proxy for API and media requests.
+    pub proxy: ProxyConfig,
+    /// Attempts and backoff of failed file downloads.
+    pub retry: RetryConfig,
+    /// Write the report of each download session to the blog folder as JSON.
+    pub save_session_report: bool,
+    /// Debug and trace lines in the log file and the frontend's log.
+    pub log_verbosity: LogVerbosity,
+    /// Top-level settings of a config written by a newer version, written back as they were.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct AuthProfile {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub device_id: String,
+    pub token_expires_at: Option<i64>,
+}
+
+/// The `auth` value from boosty.to Local Storage.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredAuth {
+    access_token: String,
+    #[serde(default)]
+    expires_at: Option<i64>,
+}
+
+/// Splits a pasted `auth` Local Storage value into the access token and its expiry.
+/// A bare token is returned as is.
+pub fn parse_access_token(input: &str) -> (String, Option<i64>) {
+    let input = input.trim();
+    match serde_json::from_str::<StoredAuth>(input) {
+        // Boosty keeps the expiry in milliseconds.
+        Ok(auth) => (auth.access_token, auth.expires_at.map(|ms| ms / 1000)),
+        Err(_) => (input.to_string(), None),
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct CommentsConfig {
     pub enabled: bool,
     pub reply_limit: Option<u32>,
     pub limit: Option<u32>,
     pub order: Option<String>,
+    /// Page through every reply of every thread instead of stopping at `reply_limit`.
+    pub fetch_all_replies: bool,
+    /// Pause between reply pages, in milliseconds.
+    pub replies_page_delay_ms: Option<u64>,
+}
+
+impl Default for CommentsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reply_limit: Some(10),
+            limit: Some(300),
+            // top or bottom
+            order: Some("bottom".to_string()),
+            fetch_all_replies: false,
+            replies_page_delay_ms: Some(300),
+        }
+    }
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             posts_limit: 100,
             access_token: String::new(),
             refresh_token: String::new(),
             device_id: String::new(),
-            comments: CommentsConfig {
-                enabled: true,
-                reply_limit: Some(10),
-                limit: Some(300),
-                // top or bottom
-                order: Some("bottom".to_string()),
-            },
+            comments: CommentsConfig::default(),
             download_path: None,
+            active_profile: default_profile_name(),
+            profiles: BTreeMap::new(),
+            blog_profiles: BTreeMap::new(),
+            token_storage: TokenStorage::default(),
+            token_expires_at: None,
+            naming: NamingConfig::default(),
+            storage: StorageConfig::default(),
+            throttle: ThrottleConfig::default(),
+            media_client: MediaClientConfig::default(),
+            proxy: ProxyConfig::default(),
+            retry: RetryConfig::default(),
+            save_session_report: false,
+            log_verbosity: LogVerbosity::default(),
+            extra: Map::new(),
         }
     }
 }
 
-fn config_path() -> Result<PathBuf> {
-    let exe = std::env::current_exe().with_context(|| "Failed to get current executable path")?;
-    let dir = exe.parent().context("Failed to get executable directory")?;
+pub const DEFAULT_PROFILE: &str = "default";
 
-    Ok(dir.join("config.json"))
+fn default_profile_name() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+impl AppConfig {
+    /// All profile names, the active one included.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.push(self.active_profile.clone());
+        names.sort();
+        names
+    }
+
+    pub fn has_profile(&self, name: &str) -> bool {
+        name == self.active_profile || self.profiles.contains_key(name)
+    }
+
+    /// Profile to download and refresh the given blog with.
+    pub fn profile_for_blog(&self, blog: &str) -> &str {
+        self.blog_profiles
+            .get(blog)
+            .filter(|name| self.has_profile(name))
+            .map_or(self.active_profile.as_str(), String::as_str)
+    }
+
+    pub fn credentials(&self, profile: &str) -> Option<AuthProfile> {
+        if profile == self.active_profile {
+            return Some(AuthProfile {
+                access_token: self.access_token.clone(),
+                refresh_token: self.refresh_token.clone(),
+                device_id: self.device_id.clone(),
+                token_expires_at: self.token_expires_at,
+            });
+        }
+        self.profiles.get(profile).cloned()
+    }
+
+    pub fn set_credentials(&mut self, profile: &str, creds: AuthProfile) {
+        if profile == self.active_profile {
+            self.access_token = creds.access_token;
+            self.refresh_token = creds.refresh_token;
+            self.device_id = creds.device_id;
+            self.token_expires_at = creds.token_expires_at;
+        } else {
+            self.profiles.insert(profile.to_string(), creds);
+        }
+    }
+
+    /// Makes `name` the active profile, creating it without credentials if it doesn't exist.
+    /// The previously active credentials are kept under their profile name.
+    pub fn switch_profile(&mut self, name: &str) {
+        if name == self.active_profile {
+            return;
+        }
+        let previous = self.credentials(&self.active_profile).unwrap_or_default();
+        let next = self.profiles.remove(name).unwrap_or_default();
+        let previous_name = std::mem::replace(&mut self.active_profile, name.to_string());
+        self.profiles.insert(previous_name, previous);
+        self.set_credentials(name, next);
+    }
+
+    /// Removes an inactive profile together with the blogs mapped to it.
+    pub fn remove_profile(&mut self, name: &str) -> Result<()> {
+        if name == self.active_profile {
+            anyhow::bail!("Active profile '{name}' can't be removed, switch to another one first");
+        }
+        if self.profiles.remove(name).is_none() {
+            anyhow::bail!("Profile '{name}' not found");
+        }
+        self.blog_profiles.retain(|_, profile| profile != name);
+        Ok(())
+    }
+}
+
+const ENV_PREFIX: &str = "BOOSTY_";
+
+type FieldPath = Vec<String>;
+
+/// A field replaced by a `BOOSTY_*` variable.
+#[derive(Debug)]
+struct Override {
+    path: FieldPath,
+    /// Value loaded from the file.
+    original: Value,
+    /// Value of the variable.
+    value: Value,
+}
+
+/// Fields replaced by `BOOSTY_*` variables on the last load. Their file values are put
+/// back before saving so the overrides never end up on disk.
+static OVERRIDDEN: Lazy<Mutex<Vec<Override>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Applies `BOOSTY_<FIELD>` variables to the config; nested fields use `__`,
+/// e.g. `BOOSTY_COMMENTS__REPLY_LIMIT`. Variables that don't name a field are ignored.
+///
+/// A value is read as JSON when the field takes that type (numbers, booleans, `null`,
+/// lists) and as a plain string otherwise, so `BOOSTY_DOWNLOAD_PATH=2024` stays a path.
+fn apply_overrides(
+    cfg: &AppConfig,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(AppConfig, Vec<Override>)> {
+    let mut value = serde_json::to_value(cfg)?;
+    let mut overrides = Vec::new();
+
+    for (key, raw) in vars {
+        let Some(name) = key.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let path: FieldPath = name
+            .to_lowercase()
+            .split("__")
+            .map(str::to_string)
+            .collect();
+        let Some(original) = field_mut(&mut value, &path).cloned() else {
+            continue;
+        };
+        let parsed = serde_json::from_str(&raw)
+            .ok()
+            .filter(|parsed: &Value| !parsed.is_string() && fits(&value, &path, parsed));
+        let new_value = parsed.unwrap_or(Value::String(raw));
+        if let Some(field) = field_mut(&mut value, &path) {
+            *field = new_value.clone();
+        }
+        overrides.push(Override {
+            path,
+            original,
+            value: new_value,
+        });
+    }
+
+    let cfg = serde_json::from_value(value)
+        .with_context(|| format!("Invalid {ENV_PREFIX}* environment override"))?;
+    Ok((cfg, overrides))
+}
+
+/// Whether the config still deserializes with `new_value` at `path`.
+fn fits(value: &Value, path: &[String], new_value: &Value) -> bool {
+    let mut value = value.clone();
+    let Some(field) = field_mut(&mut value, path) else {
+        return false;
+    };
+    *field = new_value.clone();
+    serde_json::from_value::<AppConfig>(value).is_ok()
+}
+
+fn field_mut<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    path.iter()
+        .try_fold(value, |value, key| value.as_object_mut()?.get_mut(key))
+}
+
+/// `cfg` with the overridden fields set back to their file values. A field changed since
+/// the override (a refreshed token, say) keeps the new value.
+fn without_overrides(cfg: &AppConfig) -> Result<AppConfig> {
+    let overrides = OVERRIDDEN.lock().unwrap();
+    if overrides.is_empty() {
+        return Ok(cfg.clone());
+    }
+    restore_fields(cfg, &overrides)
+}
+
+fn restore_fields(cfg: &AppConfig, overrides: &[Override]) -> Result<AppConfig> {
+    let mut value = serde_json::to_value(cfg)?;
+    for item in overrides {
+        if let Some(field) = field_mut(&mut value, &item.path)
+            && *field == item.value
+        {
+            *field = item.original.clone();
+        }
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Whether the top-level field was replaced by a `BOOSTY_*` variable on the last load.
+pub(crate) fn is_overridden(field: &str) -> bool {
+    OVERRIDDEN
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|item| item.path.len() == 1 && item.path[0] == field)
 }
 
 pub async fn load_config() -> Result<AppConfig> {
+    let cfg = load_config_file().await?;
+    let (cfg, overrides) = apply_overrides(&cfg, env::vars())?;
+    *OVERRIDDEN.lock().unwrap() = overrides;
+    Ok(cfg)
+}
+
+async fn load_config_file() -> Result<AppConfig> {
     let path = config_path()?;
 
     if !fs::try_exists(&path).await? {
@@ -62,51 +354,186 @@ pub async fn load_config() -> Result<AppConfig> {
         return Ok(default);
     }
 
-    let data = match fs::read(&path).await {
-        Ok(d) => d,
-        Err(e) => {
-            cli::error_while_loading_config(&anyhow::Error::from(e));
+    let data = fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
 
-            return reset_config(&path).await;
+    let (mut cfg, upgrade) = match parse_config(&data) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let backup = backup_config(&path).await?;
+            cli::config_reset(&e, &backup);
+            let default = AppConfig::default();
+            save_config(&default).await?;
+            return Ok(default);
         }
     };
 
-    match serde_json::from_slice::<AppConfig>(&data) {
-        Ok(cfg) => Ok(cfg),
-        Err(e) => {
-            cli::error_while_loading_config(&anyhow::Error::from(e));
+    if cfg.version > CONFIG_VERSION {
+        static NEWER: std::sync::Once = std::sync::Once::new();
+        NEWER.call_once(|| {
+            log_warn!(
+                "Config was written by a newer version (schema {}, this build knows {CONFIG_VERSION}); settings this build doesn't know are kept but not used",
+                cfg.version
+            )
+        });
+    }
+
+    secrets::restore(&mut cfg).await;
+    let move_tokens = secrets::needs_migration(&cfg);
+    if let Some(upgrade) = upgrade {
+        let backup = backup_config(&path).await?;
+        save_config(&cfg).await?;
+        cli::config_upgraded(upgrade.from, &upgrade.changes, &backup);
+    } else if move_tokens {
+        save_config(&cfg).await?;
+    }
+    // Without a usable store the tokens stay in plain text and nothing moved.
+    if move_tokens && !secrets::needs_migration(&cfg) {
+        cli::tokens_moved_to_secret_store();
+    }
+    Ok(cfg)
+}
+
+/// Moves a config object one schema version forward, returning notes on what it changed.
+struct Migration {
+    to: u32,
+    apply: fn(&mut Map<String, Value>) -> Vec<String>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 1,
+        apply: migrate_to_v1,
+    },
+    Migration {
+        to: 2,
+        apply: migrate_to_v2,
+    },
+];
+
+/// Unversioned configs could keep the whole pasted `auth` value in `access_token`.
+fn migrate_to_v1(cfg: &mut Map<String, Value>) -> Vec<String> {
+    let Some(Value::String(token)) = cfg.get("access_token") else {
+        return Vec::new();
+    };
+    if !token.trim_start().starts_with('{') {
+        return Vec::new();
+    }
+    let (token, expires_at) = parse_access_token(token);
+    cfg.insert("access_token".into(), token.into());
+    cfg.insert("token_expires_at".into(), expires_at.into());
+    vec!["`access_token` held the whole `auth` value, split into token and expiry".into()]
+}
+
+/// Version 2 added the `naming`, `storage`, `throttle`, `media_client`, `proxy`, `retry`,
+/// `save_session_report`, `log_verbosity` and `token_storage` fields; they get their defaults.
+fn migrate_to_v2(_cfg: &mut Map<String, Value>) -> Vec<String> {
+    Vec::new()
+}
+
+/// What loading a config from another schema version changed in it.
+#[derive(Debug)]
+struct ConfigUpgrade {
+    from: u32,
+    changes: Vec<String>,
+}
+
+/// Parses a config file of any known schema version. Missing fields get their defaults
+/// and unknown ones are dropped; both are listed in the returned upgrade.
+///
+/// A file of a newer schema is read as is and keeps its version and the settings this
+/// build doesn't know, so saving it doesn't lose them.
+fn parse_config(data: &[u8]) -> Result<(AppConfig, Option<ConfigUpgrade>)> {
+    let mut value: Value = serde_json::from_slice(data).context("Config is not valid JSON")?;
+    let fields = value
+        .as_object_mut()
+        .context("Config is not a JSON object")?;
+    let from = fields
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| u32::try_from(v).unwrap_or(u32::MAX));
+
+    if from > CONFIG_VERSION {
+        let cfg = serde_json::from_value(value).context("Config has fields of the wrong type")?;
+        return Ok((cfg, None));
+    }
 
-            reset_config(&path).await
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.to > from) {
+        changes.extend((migration.apply)(fields));
+    }
+    fields.insert("version".into(), CONFIG_VERSION.into());
+
+    let mut cfg: AppConfig =
+        serde_json::from_value(value.clone()).context("Config has fields of the wrong type")?;
+    cfg.extra.clear();
+    field_changes(&value, &serde_json::to_value(&cfg)?, "", &mut changes);
+
+    let upgrade =
+        (from != CONFIG_VERSION || !changes.is_empty()).then_some(ConfigUpgrade { from, changes });
+    Ok((cfg, upgrade))
+}
+
+/// Lists fields the file lacked (now defaults) and fields it had that are no longer known.
+fn field_changes(file: &Value, parsed: &Value, prefix: &str, changes: &mut Vec<String>) {
+    let (Some(file), Some(parsed)) = (file.as_object(), parsed.as_object()) else {
+        return;
+    };
+    for (key, value) in parsed {
+        let path = format!("{prefix}{key}");
+        match file.get(key) {
+            Some(old) => field_changes(old, value, &format!("{path}."), changes),
+            None => changes.push(format!("`{path}` added with default value {value}")),
         }
     }
+    for key in file.keys().filter(|key| !parsed.contains_key(*key)) {
+        changes.push(format!("`{prefix}{key}` is no longer used and was dropped"));
+    }
+}
+
+/// Copies the config file next to itself with a timestamp, before it gets replaced.
+async fn backup_config(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let backup = path.with_file_name(format!("{file_name}.{stamp}.bak"));
+    fs::copy(path, &backup)
+        .await
+        .with_context(|| format!("Failed to back up config to '{}'", backup.display()))?;
+    Ok(backup)
 }
 
 pub async fn save_config(config: &AppConfig) -> Result<()> {
     let path = config_path()?;
-    let data = serde_json::to_vec_pretty(config).with_context(|| "Failed to serialize config")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create config directory '{}'", dir.display()))?;
+    }
+    let stored = secrets::store(&without_overrides(config)?).await;
+    write_config_file(&path, &stored).await
+}
 
-    fs::write(&path, data)
+/// Writes to a temporary file first, so a crash never leaves a half-written config.
+async fn write_config_file(path: &Path, cfg: &AppConfig) -> Result<()> {
+    let data = serde_json::to_vec_pretty(cfg).with_context(|| "Failed to serialize config")?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data)
+        .await
+        .with_context(|| format!("Failed to write config file '{}'", tmp.display()))?;
+    fs::rename(&tmp, path)
         .await
         .with_context(|| format!("Failed to write config file '{}'", path.display()))?;
-
     Ok(())
 }
 
-async fn reset_config(path: &Path) -> Result<AppConfig> {
-    let _ = fs::remove_file(&path).await;
-
-    let default = AppConfig::default();
-    save_config(&default).await?;
-    Ok(default)
-}
-
 pub fn get_download_path(config: &AppConfig) -> PathBuf {
     match &config.download_path {
         Some(path) => PathBuf::from(path),
-        None => {
-            let exe = env::current_exe().unwrap_or_else(|_| PathBuf::from("."));
-            exe.parent().unwrap_or_else(|| Path::new(".")).to_path_buf()
-        }
+        None => paths::default_download_dir(),
     }
 }
 
@@ -135,6 +562,10 @@ pub async fn sync_auth(
     cfg: &mut AppConfig,
     previous: Option<&AppConfig>,
 ) -> Result<()> {
+    if cfg.access_token.trim_start().starts_with('{') {
+        (cfg.access_token, cfg.token_expires_at) = parse_access_token(&cfg.access_token);
+    }
+
     let has_refresh = !cfg.refresh_token.is_empty() && !cfg.device_id.is_empty();
     let refresh_changed = previous.is_some_and(|prev| {
         prev.refresh_token != cfg.refresh_token || prev.device_id != cfg.device_id
@@ -149,6 +580,7 @@ pub async fn sync_auth(
 
         cfg.access_token = pair.access_token;
         cfg.refresh_token = pair.refresh_token;
+        cfg.token_expires_at = None;
 
         cli::access_token_set(&cfg.access_token);
         cli::refresh_token_set(&cfg.refresh_token);
@@ -159,6 +591,7 @@ pub async fn sync_auth(
     } else {
         clear_auth(client, cfg).await?;
     }
+    auth::set_current_profile(&cfg.active_profile);
 
     Ok(())
 }
@@ -170,5 +603,164 @@ pub async fn clear_auth(client: &ApiClient, cfg: &mut AppConfig) -> Result<()> {
     cfg.access_token.clear();
     cfg.refresh_token.clear();
     cfg.device_id.clear();
+    cfg.token_expires_at = None;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creds(token: &str) -> AuthProfile {
+        AuthProfile {
+            access_token: token.to_string(),
+            ..AuthProfile::default()
+        }
+    }
+
+    #[test]
+    fn switching_profiles_keeps_credentials_apart() {
+        let mut cfg = AppConfig::default();
+        cfg.set_credentials(DEFAULT_PROFILE, creds("main"));
+
+        cfg.switch_profile("work");
+        assert_eq!(cfg.access_token, "");
+        cfg.set_credentials("work", creds("work"));
+        assert_eq!(cfg.credentials(DEFAULT_PROFILE), Some(creds("main")));
+
+        cfg.switch_profile(DEFAULT_PROFILE);
+        assert_eq!(cfg.access_token, "main");
+        assert_eq!(cfg.credentials("work"), Some(creds("work")));
+        assert_eq!(cfg.profile_names(), vec!["default", "work"]);
+    }
+
+    #[test]
+    fn pasted_auth_value_gives_token_and_expiry() {
+        let (token, expires_at) = parse_access_token(
+            r#"{"accessToken":"abc","refreshToken":"def","expiresAt":1700000000000}"#,
+        );
+        assert_eq!(token, "abc");
+        assert_eq!(expires_at, Some(1_700_000_000));
+
+        assert_eq!(parse_access_token(" abc "), ("abc".to_string(), None));
+    }
+
+    #[test]
+    fn env_overrides_apply_to_nested_fields_and_stay_off_disk() {
+        let cfg = AppConfig::default();
+        let vars = [
+            ("BOOSTY_ACCESS_TOKEN", "12345"),
+            ("BOOSTY_POSTS_LIMIT", "7"),
+            ("BOOSTY_DOWNLOAD_PATH", "2024"),
+            ("BOOSTY_COMMENTS__ENABLED", "false"),
+            ("BOOSTY_CONFIG", "/etc/boosty.json"),
+            ("HOME", "/root"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let (mut overridden, overrides) = apply_overrides(&cfg, vars).unwrap();
+        assert_eq!(overridden.access_token, "12345");
+        assert_eq!(overridden.posts_limit, 7);
+        assert_eq!(overridden.download_path.as_deref(), Some("2024"));
+        assert!(!overridden.comments.enabled);
+        assert_eq!(overrides.len(), 4);
+
+        overridden.access_token = "refreshed".into();
+        let restored = restore_fields(&overridden, &overrides).unwrap();
+        assert_eq!(restored.posts_limit, 100);
+        assert!(restored.download_path.is_none());
+        assert_eq!(restored.access_token, "refreshed");
+    }
+
+    #[test]
+    fn unversioned_config_is_migrated_with_defaults() {
+        let data = br#"{
+            "posts_limit": 20,
+            "access_token": "{\"accessToken\":\"abc\",\"expiresAt\":1700000000000}",
+            "refresh_token": "",
+            "device_id": "",
+            "comments": { "enabled": false, "reply_limit": 5, "limit": null, "order": "top" },
+            "download_path": "/data",
+            "old_setting": true
+        }"#;
+
+        let (cfg, upgrade) = parse_config(data).unwrap();
+        assert_eq!(cfg.version, CONFIG_VERSION);
+        assert_eq!(cfg.posts_limit, 20);
+        assert_eq!(cfg.access_token, "abc");
+        assert_eq!(cfg.token_expires_at, Some(1_700_000_000));
+        assert!(!cfg.comments.enabled);
+        assert_eq!(cfg.comments.replies_page_delay_ms, Some(300));
+        assert_eq!(cfg.active_profile, DEFAULT_PROFILE);
+
+        let upgrade = upgrade.unwrap();
+        assert_eq!(upgrade.from, 0);
+        let has = |text: &str| upgrade.changes.iter().any(|c| c.contains(text));
+        assert!(has("`comments.replies_page_delay_ms` added"));
+        assert!(has("`old_setting` is no longer used"));
+        assert!(has("split into token and expiry"));
+    }
+
+    #[test]
+    fn newer_config_keeps_its_version() {
+        let data = br#"{ "version": 99, "posts_limit": 5, "future_setting": 1 }"#;
+        let (cfg, upgrade) = parse_config(data).unwrap();
+        assert!(upgrade.is_none());
+        assert_eq!(cfg.version, 99);
+        assert_eq!(cfg.posts_limit, 5);
+    }
+
+    #[tokio::test]
+    async fn newer_config_keeps_unknown_settings_when_saved() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("boosty_config_{}_{nanos}", std::process::id()));
+        fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("config.json");
+
+        let data = br#"{ "version": 3, "posts_limit": 5, "future_setting": { "a": 1 } }"#;
+        let (cfg, _) = parse_config(data).unwrap();
+        write_config_file(&path, &cfg).await.unwrap();
+
+        let saved = fs::read(&path).await.unwrap();
+        let (cfg, upgrade) = parse_config(&saved).unwrap();
+        assert!(upgrade.is_none());
+        assert_eq!(cfg.version, 3);
+        assert_eq!(
+            cfg.extra.get("future_setting"),
+            Some(&serde_json::json!({ "a": 1 }))
+        );
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn current_config_loads_without_changes() {
+        let data = serde_json::to_vec(&AppConfig::default()).unwrap();
+        let (_, upgrade) = parse_config(&data).unwrap();
+        assert!(upgrade.is_none());
+
+        assert!(parse_config(b"{ not json").is_err());
+        assert!(parse_config(br#"{ "posts_limit": "many" }"#).is_err());
+    }
+
+    #[test]
+    fn blogs_fall_back_to_active_profile() {
+        let mut cfg = AppConfig::default();
+        cfg.set_credentials("work", creds("work"));
+        cfg.blog_profiles.insert("author".into(), "work".into());
+        cfg.blog_profiles.insert("other".into(), "missing".into());
+
+        assert_eq!(cfg.profile_for_blog("author"), "work");
+        assert_eq!(cfg.profile_for_blog("other"), DEFAULT_PROFILE);
+        assert_eq!(cfg.profile_for_blog("unmapped"), DEFAULT_PROFILE);
+
+        cfg.remove_profile("work").unwrap();
+        assert!(cfg.blog_profiles.get("author").is_none());
+        assert!(cfg.remove_profile(DEFAULT_PROFILE).is_err());
+    }
+}
@@ -1,22 +1,145 @@
-use std::any::Any;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
 use crate::{
     cli,
-    logger::{LogLevel, Logger},
-    progress_reporter,
+    events::{Event, EventSink, ProgressMessage},
+    logger::LogLevel,
 };
 
-pub struct ConsoleLogger;
+/// Prints log lines and draws progress bars in the terminal.
+#[derive(Default)]
+pub struct ConsoleLogger {
+    bars: Mutex<Option<Bars>>,
+}
+
+struct Bars {
+    multi: MultiProgress,
+    overall: ProgressBar,
+    current: Option<ProgressBar>,
+    current_name: Option<String>,
+    pause_keys: Option<cli::PauseKeyListener>,
+}
+
+impl ConsoleLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-impl Logger for ConsoleLogger {
-    fn log(&self, level: LogLevel, message: &str) {
-        progress_reporter::suspend_for(|| match level {
+    fn print(&self, level: LogLevel, message: &str) {
+        let print = || match level {
             LogLevel::Info => cli::info(message),
             LogLevel::Warn => cli::warning(message),
             LogLevel::Error => cli::error(message),
+            LogLevel::Debug | LogLevel::Trace => cli::debug(level.as_str(), message),
+        };
+        match self.bars.lock().unwrap().as_ref() {
+            Some(bars) => bars.multi.suspend(print),
+            None => print(),
+        }
+    }
+
+    fn start(&self, files_total: u64) {
+        let multi = MultiProgress::new();
+        let overall = multi.add(ProgressBar::new(files_total));
+        overall.set_style(
+            ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} files {msg}")
+                .expect("overall progress style")
+                .progress_chars("=> "),
+        );
+        overall.set_message(cli::PAUSE_KEY_HINT);
+        let bars = Bars {
+            multi,
+            overall,
+            current: None,
+            current_name: None,
+            pause_keys: cli::PauseKeyListener::spawn(),
+        };
+        // Replaced outside the lock: dropping joins the pause key listener, which may be
+        // logging through this sink.
+        let previous = self.bars.lock().unwrap().replace(bars);
+        drop(previous.map(Bars::finish));
+    }
+
+    fn progress(&self, msg: &ProgressMessage) {
+        let mut slot = self.bars.lock().unwrap();
+        let Some(bars) = slot.as_mut() else {
+            return;
+        };
+        bars.overall.set_length(msg.files_total);
+        bars.overall.set_position(msg.files_done);
+        bars.overall.set_message(if msg.paused {
+            cli::PAUSED_HINT
+        } else {
+            cli::PAUSE_KEY_HINT
         });
+
+        if bars.current_name != msg.file_name {
+            if let Some(bar) = bars.current.take() {
+                bar.finish_and_clear();
+            }
+            bars.current_name = msg.file_name.clone();
+            if let Some(name) = &msg.file_name {
+                let bar = bars.multi.add(file_bar(msg.total));
+                bar.set_message(name.clone());
+                bars.current = Some(bar);
+            }
+        }
+        if let Some(bar) = &bars.current {
+            bar.set_position(msg.current);
+        }
     }
-    fn as_any(&self) -> &(dyn Any + 'static) {
-        self
+
+    fn finish(&self) {
+        let bars = self.bars.lock().unwrap().take();
+        drop(bars.map(Bars::finish));
+    }
+}
+
+impl Bars {
+    fn finish(self) -> Option<cli::PauseKeyListener> {
+        if let Some(bar) = &self.current {
+            bar.finish_and_clear();
+        }
+        self.overall.finish_and_clear();
+        self.pause_keys
+    }
+}
+
+impl EventSink for ConsoleLogger {
+    fn send(&self, event: Event) {
+        match event {
+            Event::Log { level, message } => self.print(level, &message),
+            Event::SessionStarted { files_total } => self.start(files_total),
+            Event::Progress(msg) => self.progress(&msg),
+            Event::SessionFinished => self.finish(),
+            Event::PostStarted { .. }
+            | Event::PostFinished { .. }
+            | Event::TokensRefreshed { .. } => {}
+        }
+    }
+}
+
+fn file_bar(total_size: u64) -> ProgressBar {
+    if total_size > 0 {
+        let pb = ProgressBar::new(total_size);
+        pb.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta}) {msg}",
+            )
+            .expect("file progress style")
+            .progress_chars("=> "),
+        );
+        pb
+    } else {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::with_template("{spinner:.green} {msg} {bytes}")
+                .expect("file progress style"),
+        );
+        pb.enable_steady_tick(Duration::from_millis(100));
+        pb
     }
 }
@@ -1,29 +1,97 @@
-use crate::{cli, file_handler, log_error, parser, post_page};
+use crate::file_handler::DownloadResult;
+use crate::naming::{MediaKind, MediaNamer};
+use crate::session_report::{FailedFile, UrlKind};
+use crate::{
+    auth, cli, file_handler, log_error, log_warn, parser, post_page, progress_reporter,
+    session_report,
+};
 use anyhow::Result;
+use boosty_api::api_client::ApiClient;
 use boosty_api::media_content::ContentItem;
 use parser::ParsedText;
 use post_page::Block;
 use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio_util::sync::CancellationToken;
 
+/// Signature of a post's audio and file links, fetched again with the post when Boosty
+/// answers 403 because it expired.
+pub struct SignedQuery<'a> {
+    client: &'a ApiClient,
+    blog: &'a str,
+    post_id: &'a str,
+    value: Mutex<String>,
+    refetched: AtomicBool,
+}
+
+impl<'a> SignedQuery<'a> {
+    pub fn new(client: &'a ApiClient, blog: &'a str, post_id: &'a str, value: &str) -> Self {
+        Self {
+            client,
+            blog,
+            post_id,
+            value: Mutex::new(value.to_string()),
+            refetched: AtomicBool::new(false),
+        }
+    }
+
+    fn current(&self) -> String {
+        self.value.lock().unwrap().clone()
+    }
+
+    /// A signature newer than `used`; the post is fetched again at most once.
+    async fn renewed(&self, used: &str) -> Option<String> {
+        let current = self.current();
+        if current != used {
+            return Some(current);
+        }
+        if self.refetched.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        let endpoint = format!("/v1/blog/{}/post/{}", self.blog, self.post_id);
+        match auth::call(self.client, &endpoint, || {
+            self.client.get_post(self.blog, self.post_id)
+        })
+        .await
+        {
+            Ok(post) if !post.signed_query.is_empty() && post.signed_query != used => {
+                *self.value.lock().unwrap() = post.signed_query.clone();
+                Some(post.signed_query)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                log_warn!(
+                    "Failed to fetch post '{}' for new file links: {e}",
+                    self.post_id
+                );
+                None
+            }
+        }
+    }
+}
+
 struct ContentCtx<'a> {
+    namer: &'a MediaNamer<'a>,
     post_title: &'a str,
     folder_path: &'a Path,
     href_prefix: &'a str,
-    signed_query: Option<&'a str>,
+    signed_query: Option<&'a SignedQuery<'a>>,
     cancel_token: &'a CancellationToken,
 }
 
 pub async fn process_content_items(
     items: Vec<ContentItem>,
-    post_title: &str,
+    namer: &MediaNamer<'_>,
     folder_path: &Path,
     href_prefix: &str,
-    signed_query: Option<&str>,
+    signed_query: Option<&SignedQuery<'_>>,
     cancel_token: &CancellationToken,
 ) -> Result<Vec<Block>> {
     let mut blocks = Vec::new();
+    let post_title = namer.post_title();
     let ctx = ContentCtx {
+        namer,
         post_title,
         folder_path,
         href_prefix,
@@ -34,7 +102,7 @@ pub async fn process_content_items(
     for item in items {
         crate::ensure_not_cancelled(ctx.cancel_token)?;
         if let Err(e) = process_one_item(item, &ctx, &mut blocks).await {
-            if crate::is_cancelled_error(&e) {
+            if crate::is_stop_error(&e) {
                 return Err(e);
             }
             log_error!("Error processing content item for post '{post_title}': {e:#}");
@@ -52,12 +120,14 @@ async fn process_one_item(
     crate::ensure_not_cancelled(ctx.cancel_token)?;
     match item {
         ContentItem::Image { url, id } => {
-            let image_name = format!("{id}.jpg");
+            let Some(image_name) = media_name(ctx, MediaKind::Image, &id, &id, Some("jpg")) else {
+                return Ok(());
+            };
             download_and_push(
                 ctx,
                 &url,
                 &image_name,
-                None,
+                (UrlKind::Image, &id),
                 |rel| Block::Image {
                     rel,
                     alt: id.clone(),
@@ -75,12 +145,15 @@ async fn process_one_item(
             });
         }
         ContentItem::OkVideo { url, title, vid } => {
-            let title_with_vid = format!("{title}({vid}).mp4");
+            let Some(video_name) = media_name(ctx, MediaKind::Video, &vid, &title, Some("mp4"))
+            else {
+                return Ok(());
+            };
             download_and_push(
                 ctx,
                 &url,
-                &title_with_vid,
-                None,
+                &video_name,
+                (UrlKind::Video, &vid),
                 |rel| Block::VideoFile { rel },
                 blocks,
             )
@@ -93,29 +166,30 @@ async fn process_one_item(
             file_type,
             ..
         } => {
-            let file_name = file_handler::media_file_name(
-                &id,
-                &title,
-                file_handler::audio_extension(file_type.as_deref()),
-            );
+            let extension = file_handler::audio_extension(file_type.as_deref());
+            let Some(file_name) = media_name(ctx, MediaKind::File, &id, &title, extension) else {
+                return Ok(());
+            };
             download_and_push(
                 ctx,
                 &url,
                 &file_name,
-                ctx.signed_query,
+                (UrlKind::Audio, &id),
                 |rel| Block::Audio { rel },
                 blocks,
             )
             .await?;
         }
         ContentItem::File { url, title, id, .. } => {
-            let file_name = file_handler::media_file_name(&id, &title, None);
+            let Some(file_name) = media_name(ctx, MediaKind::File, &id, &title, None) else {
+                return Ok(());
+            };
             let link_title = title.clone();
             download_and_push(
                 ctx,
                 &url,
                 &file_name,
-                ctx.signed_query,
+                (UrlKind::File, &id),
                 |rel| Block::FileLink {
                     rel,
                     title: link_title,
@@ -141,7 +215,7 @@ async fn process_one_item(
                 ctx,
                 &small_url,
                 &image_name,
-                None,
+                (UrlKind::Smile, &name),
                 |rel| Block::Smile { rel, alt },
                 blocks,
             )
@@ -160,7 +234,7 @@ async fn process_one_item(
                     if let Err(e) =
                         Box::pin(process_one_item(subitem, ctx, &mut group_blocks)).await
                     {
-                        if crate::is_cancelled_error(&e) {
+                        if crate::is_stop_error(&e) {
                             return Err(e);
                         }
                         log_error!(
@@ -186,23 +260,85 @@ async fn process_one_item(
     Ok(())
 }
 
+/// Name from the templates; `None` (after logging) when the file is skipped as a collision.
+fn media_name(
+    ctx: &ContentCtx<'_>,
+    kind: MediaKind,
+    id: &str,
+    original: &str,
+    extension: Option<&str>,
+) -> Option<String> {
+    let name = ctx.namer.file_name(kind, id, original, extension);
+    if name.is_none() {
+        log_warn!(
+            "File '{original}' of post '{}' has the same name as another file, skipping",
+            ctx.post_title
+        );
+        progress_reporter::finish_file();
+        session_report::file_skipped();
+    }
+    name
+}
+
 async fn download_and_push(
     ctx: &ContentCtx<'_>,
     url: &str,
     file_name: &str,
-    signed_query: Option<&str>,
+    (url_kind, item_id): (UrlKind, &str),
     make_block: impl FnOnce(String) -> Block,
     blocks: &mut Vec<Block>,
 ) -> Result<()> {
-    let (result, rel) = file_handler::download_media(
+    let signed_query = ctx.signed_query.filter(|_| url_kind.is_signed());
+    let query = signed_query.map(SignedQuery::current);
+    let failed = |reason: String| FailedFile {
+        post_id: ctx.namer.post_id().to_string(),
+        post_title: ctx.post_title.to_string(),
+        item_id: item_id.to_string(),
+        url_kind,
+        file: file_name.to_string(),
+        reason,
+    };
+    let report_error = |e: &anyhow::Error| {
+        if !crate::is_stop_error(e) {
+            session_report::file_failed(failed(format!("{e:#}")));
+        }
+    };
+    let (mut result, rel) = file_handler::download_media(
         ctx.folder_path,
         url,
         file_name,
         ctx.post_title,
-        signed_query,
+        query.as_deref(),
         ctx.cancel_token,
     )
-    .await?;
+    .await
+    .inspect_err(report_error)?;
+    if let DownloadResult::Error(e) = &result
+        && e.is_forbidden()
+        && let Some(signed_query) = signed_query
+        && let Some(renewed) = signed_query.renewed(query.as_deref().unwrap_or("")).await
+    {
+        log_warn!(
+            "File links of post '{}' expired, downloading '{file_name}' with new ones",
+            ctx.post_title
+        );
+        progress_reporter::add_files_total(1);
+        (result, _) = file_handler::download_media(
+            ctx.folder_path,
+            url,
+            file_name,
+            ctx.post_title,
+            Some(&renewed),
+            ctx.cancel_token,
+        )
+        .await
+        .inspect_err(report_error)?;
+    }
+    match &result {
+        DownloadResult::Success => session_report::file_downloaded(),
+        DownloadResult::Skipped => session_report::file_skipped(),
+        DownloadResult::Error(e) => session_report::file_failed(failed(e.to_string())),
+    }
     cli::show_download_result(result, file_name, ctx.post_title);
     blocks.push(make_block(format!("{}{rel}", ctx.href_prefix)));
     Ok(())
@@ -0,0 +1,89 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+
+use crate::config::{self, AppConfig, AuthProfile};
+use crate::events::{self, Event};
+use crate::media_client::MediaSettings;
+use crate::retry::RetryConfig;
+use crate::throttle::Throttle;
+
+/// Limits, media client and credentials the sessions of an event scope use. The CLI and GUI
+/// share the process-wide one; every [`Downloader`](crate::Downloader) has its own.
+pub(crate) struct Context {
+    pub(crate) throttle: Mutex<Throttle>,
+    pub(crate) retry: Mutex<RetryConfig>,
+    /// Client built from the settings last passed to
+    /// [`media_client::configure`](crate::media_client::configure), with those settings.
+    pub(crate) media: Mutex<Option<(MediaSettings, Client)>>,
+    /// Bumped on every successful token refresh, so concurrent callers that hit the same 401
+    /// refresh once.
+    pub(crate) refresh_generation: AtomicU64,
+    /// Held while credentials are put on the client or refreshed.
+    pub(crate) refresh_lock: tokio::sync::Mutex<()>,
+    credentials: Credentials,
+}
+
+enum Credentials {
+    /// Read from and saved to the config file.
+    ConfigFile,
+    /// Kept in this config; refreshed tokens are sent as [`Event::TokensRefreshed`].
+    Memory(Arc<Mutex<AppConfig>>),
+}
+
+static DEFAULT: Lazy<Arc<Context>> = Lazy::new(|| Context::new(Credentials::ConfigFile));
+
+impl Context {
+    fn new(credentials: Credentials) -> Arc<Self> {
+        Arc::new(Self {
+            throttle: Mutex::new(Throttle::default()),
+            retry: Mutex::new(RetryConfig::default()),
+            media: Mutex::new(None),
+            refresh_generation: AtomicU64::new(0),
+            refresh_lock: tokio::sync::Mutex::new(()),
+            credentials,
+        })
+    }
+
+    /// A context whose credentials stay in `cfg` instead of the config file.
+    pub(crate) fn in_memory(cfg: Arc<Mutex<AppConfig>>) -> Arc<Self> {
+        Self::new(Credentials::Memory(cfg))
+    }
+
+    pub(crate) fn default_context() -> Arc<Self> {
+        DEFAULT.clone()
+    }
+
+    /// The config with the latest credentials of every profile.
+    pub(crate) async fn config(&self) -> Result<AppConfig> {
+        match &self.credentials {
+            Credentials::ConfigFile => config::load_config().await,
+            Credentials::Memory(cfg) => Ok(cfg.lock().unwrap().clone()),
+        }
+    }
+
+    /// Keeps tokens refreshed for `profile` for the next sessions.
+    pub(crate) async fn save_credentials(&self, profile: &str, creds: AuthProfile) -> Result<()> {
+        match &self.credentials {
+            Credentials::ConfigFile => {
+                config::update_config(|cfg| cfg.set_credentials(profile, creds)).await?;
+            }
+            Credentials::Memory(cfg) => {
+                cfg.lock().unwrap().set_credentials(profile, creds.clone());
+                events::emit(Event::TokensRefreshed {
+                    profile: profile.to_string(),
+                    credentials: creds,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The context of the current event scope.
+pub(crate) fn current() -> Arc<Context> {
+    events::current().context.clone()
+}
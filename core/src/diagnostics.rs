@@ -0,0 +1,131 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use tokio::fs;
+
+use crate::config::{self, AppConfig};
+use crate::{file_logger, logger, paths};
+
+const REDACTED: &str = "<redacted>";
+
+/// Writes one text file for a bug report to `target_dir`: versions, paths, the config with
+/// credentials hidden and the log files, oldest first. Returns the file's path.
+pub async fn export(target_dir: &Path) -> Result<PathBuf> {
+    let cfg = config::load_config().await?;
+    let mut out = String::new();
+    let _ = writeln!(out, "boosty_downloader {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        out,
+        "System: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(out, "Exported at: {}", Local::now().to_rfc3339());
+    let _ = writeln!(out, "Log verbosity: {:?}", logger::verbosity());
+    let _ = writeln!(out, "Config file: {}", display(paths::config_path()));
+    let _ = writeln!(out, "Data directory: {}", display(paths::data_dir()));
+    let _ = writeln!(
+        out,
+        "Download path: {}",
+        config::get_download_path(&cfg).display()
+    );
+
+    let config_json =
+        serde_json::to_string_pretty(&redacted(&cfg)).context("Failed to serialize config")?;
+    section(&mut out, "config.json");
+    out.push_str(&config_json);
+    out.push('\n');
+
+    for file in file_logger::log_files()? {
+        section(&mut out, &file.display().to_string());
+        match fs::read(&file).await {
+            Ok(data) => out.push_str(&String::from_utf8_lossy(&data)),
+            Err(e) => {
+                let _ = writeln!(out, "Failed to read: {e}");
+            }
+        }
+    }
+
+    fs::create_dir_all(target_dir)
+        .await
+        .with_context(|| format!("Failed to create folder '{}'", target_dir.display()))?;
+    let name = format!(
+        "boosty_diagnostics_{}.txt",
+        Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let path = target_dir.join(name);
+    fs::write(&path, out)
+        .await
+        .with_context(|| format!("Failed to write diagnostics '{}'", path.display()))?;
+    Ok(path)
+}
+
+fn section(out: &mut String, title: &str) {
+    let _ = writeln!(out, "\n===== {title} =====");
+}
+
+fn display(path: Result<PathBuf>) -> String {
+    path.map_or_else(|e| format!("({e:#})"), |p| p.display().to_string())
+}
+
+/// `cfg` without tokens, device ids, the proxy address and credentials, and media header
+/// values.
+fn redacted(cfg: &AppConfig) -> AppConfig {
+    fn hide(value: &mut String) {
+        if !value.is_empty() {
+            *value = REDACTED.to_string();
+        }
+    }
+
+    let mut cfg = cfg.clone();
+    hide(&mut cfg.access_token);
+    hide(&mut cfg.refresh_token);
+    hide(&mut cfg.device_id);
+    for profile in cfg.profiles.values_mut() {
+        hide(&mut profile.access_token);
+        hide(&mut profile.refresh_token);
+        hide(&mut profile.device_id);
+    }
+    hide(&mut cfg.proxy.host);
+    hide(&mut cfg.proxy.username);
+    hide(&mut cfg.proxy.password);
+    cfg.media_client.extra_headers.values_mut().for_each(hide);
+    cfg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AuthProfile;
+
+    #[test]
+    fn credentials_are_hidden_and_empty_ones_kept_empty() {
+        let mut cfg = AppConfig {
+            access_token: "access".into(),
+            device_id: "device".into(),
+            ..AppConfig::default()
+        };
+        cfg.profiles.insert(
+            "work".into(),
+            AuthProfile {
+                refresh_token: "refresh".into(),
+                ..AuthProfile::default()
+            },
+        );
+        cfg.proxy.host = "proxy.example.com".into();
+        cfg.proxy.username = "user".into();
+        cfg.proxy.password = "secret".into();
+
+        let hidden = redacted(&cfg);
+        assert_eq!(hidden.access_token, REDACTED);
+        assert_eq!(hidden.refresh_token, "");
+        assert_eq!(hidden.device_id, REDACTED);
+        assert_eq!(hidden.profiles["work"].refresh_token, REDACTED);
+        assert_eq!(hidden.proxy.host, REDACTED);
+        assert_eq!(hidden.proxy.username, REDACTED);
+        assert_eq!(hidden.proxy.password, REDACTED);
+        assert_eq!(hidden.posts_limit, cfg.posts_limit);
+    }
+}
@@ -10,13 +10,20 @@ use std::path::Path;
 use tokio::fs;
 use tokio_util::sync::CancellationToken;
 
-use crate::blog_index::{self, BlogIndex, PostRecord};
+use crate::auth;
+use crate::blog_index::{self, BlogIndex, FailedItem, PostRecord};
+use crate::blog_settings::BlogSettings;
 use crate::config::{AppConfig, get_download_path};
 use crate::download_options::{DownloadOption, DownloadOptions, options_in_items, ordered_options};
+use crate::error::{CoreError, ErrorKind};
 use crate::file_handler;
 use crate::log_error;
+use crate::log_info;
+use crate::log_warn;
 use crate::menu_handler;
 use crate::parser::BoostyUrl;
+use crate::storage;
+use crate::throttle;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -62,12 +69,19 @@ pub struct BlogSnapshot {
     pub blog: String,
     pub last_checked_at: Option<i64>,
     pub posts: Vec<PostSnapshot>,
+    pub settings: BlogSettings,
+    /// Posts and files that failed in their last session, see [`retry_failures`].
+    #[serde(default)]
+    pub failures: Vec<FailedItem>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct DownloadPostsResult {
     pub downloaded: u32,
     pub skipped: u32,
+    /// Posts that could not be downloaded; their failures are kept for a retry.
+    pub failed: u32,
 }
 
 fn is_paid_post(post: &Post) -> bool {
@@ -96,12 +110,26 @@ pub async fn refresh_blog(
     cfg: &AppConfig,
     blog: &str,
     cancel_token: &CancellationToken,
+) -> Result<BlogSnapshot, CoreError> {
+    refresh(client, cfg, blog, cancel_token)
+        .await
+        .map_err(CoreError::from)
+}
+
+async fn refresh(
+    client: &ApiClient,
+    cfg: &AppConfig,
+    blog: &str,
+    cancel_token: &CancellationToken,
 ) -> Result<BlogSnapshot> {
+    throttle::configure(&cfg.throttle);
+    auth::use_profile_for_blog(client, cfg, blog).await?;
     let download_path = get_download_path(cfg);
     let mut index = blog_index::load(&download_path, blog).await?;
     if blog_index::prune_missing_folders(&download_path, &mut index).await? {
         blog_index::save(&download_path, &index).await?;
     }
+    let cfg = &index.settings.apply_to(cfg);
 
     let now = Utc::now().timestamp();
     let mut posts = Vec::new();
@@ -114,9 +142,13 @@ pub async fn refresh_blog(
         .collect();
 
     for (post_id, record) in &local_posts {
-        crate::ensure_not_cancelled(cancel_token)?;
+        crate::pause::wait_if_paused(cancel_token).await?;
         seen_ids.insert(post_id.clone());
-        let status = match client.get_post(blog, post_id).await {
+        let status = match auth::call(client, &format!("/v1/blog/{blog}/post/{post_id}"), || {
+            client.get_post(blog, post_id)
+        })
+        .await
+        {
             Ok(post) => {
                 let status = classify_existing(record, &post);
                 if let Some(stored) = index.posts.get_mut(post_id) {
@@ -133,10 +165,9 @@ pub async fn refresh_blog(
             Err(ApiError::HttpStatus { status, .. }) if status.as_u16() == 404 => {
                 PostSyncStatus::Gone
             }
-            Err(ApiError::Unauthorized) => {
-                return Err(anyhow::anyhow!(
-                    "Unauthorized when checking post '{post_id}'"
-                ));
+            Err(e @ ApiError::Unauthorized) => {
+                return Err(e)
+                    .with_context(|| format!("Unauthorized when checking post '{post_id}'"));
             }
             Err(e) => {
                 log_error!("Failed to check post '{post_id}': {e:#}");
@@ -153,10 +184,14 @@ pub async fn refresh_blog(
     }
 
     crate::ensure_not_cancelled(cancel_token)?;
-    match client.get_posts(blog, cfg.posts_limit, None, None).await {
+    match auth::call(client, &format!("/v1/blog/{blog}/post/"), || {
+        client.get_posts(blog, cfg.posts_limit, None, None)
+    })
+    .await
+    {
         Ok(feed) => {
             for post in feed {
-                if seen_ids.contains(&post.id) {
+                if seen_ids.contains(&post.id) || !index.settings.filter.matches(&post) {
                     continue;
                 }
                 seen_ids.insert(post.id.clone());
@@ -178,10 +213,9 @@ pub async fn refresh_blog(
                 });
             }
         }
-        Err(ApiError::Unauthorized) => {
-            return Err(anyhow::anyhow!(
-                "Unauthorized when fetching posts for '{blog}'"
-            ));
+        Err(e @ ApiError::Unauthorized) => {
+            return Err(e)
+                .with_context(|| format!("Unauthorized when fetching posts for '{blog}'"));
         }
         Err(e) => {
             log_error!("Failed to fetch posts for blog '{blog}': {e:#}");
@@ -196,9 +230,25 @@ pub async fn refresh_blog(
         blog: blog.to_string(),
         last_checked_at: index.last_checked_at,
         posts,
+        settings: index.settings,
+        failures: index.failures,
     })
 }
 
+/// Stores the per-blog overrides used by downloads and checks of `blog`.
+pub async fn update_blog_settings(
+    cfg: &AppConfig,
+    blog: &str,
+    settings: BlogSettings,
+) -> Result<()> {
+    blog_index::save_settings(&get_download_path(cfg), blog, settings).await
+}
+
+/// Downloads the given posts of a blog; the blog's own settings override `download_options`.
+///
+/// Posts the current profile has no access to or deleted from Boosty are counted as
+/// skipped. A post that fails is logged and counted, and the next ones go on; only
+/// cancellation and storage limits stop the run.
 pub async fn download_posts(
     client: &ApiClient,
     cfg: &AppConfig,
@@ -207,18 +257,40 @@ pub async fn download_posts(
     download_options: DownloadOptions,
     force: bool,
     cancel_token: &CancellationToken,
+) -> Result<DownloadPostsResult, CoreError> {
+    download(
+        client,
+        cfg,
+        blog,
+        post_ids,
+        download_options,
+        force,
+        cancel_token,
+    )
+    .await
+    .map_err(CoreError::from)
+}
+
+async fn download(
+    client: &ApiClient,
+    cfg: &AppConfig,
+    blog: &str,
+    post_ids: &[String],
+    download_options: DownloadOptions,
+    force: bool,
+    cancel_token: &CancellationToken,
 ) -> Result<DownloadPostsResult> {
     let download_path = get_download_path(cfg);
     let mut result = DownloadPostsResult::default();
     for post_id in post_ids {
-        crate::ensure_not_cancelled(cancel_token)?;
+        crate::pause::wait_if_paused(cancel_token).await?;
         if force
             && let Some(folder) =
                 blog_index::resolve_post_folder(&download_path, blog, post_id).await?
         {
             file_handler::clear_dir_contents(&folder).await?;
         }
-        let downloaded = menu_handler::process_boosty_url(
+        let downloaded = match menu_handler::download_url(
             client,
             cfg,
             &BoostyUrl::Post {
@@ -227,19 +299,81 @@ pub async fn download_posts(
             },
             None,
             download_options.clone(),
+            false,
             cancel_token,
         )
         .await
-        .with_context(|| format!("Failed to download post '{post_id}' of blog '{blog}'"))?;
+        {
+            Ok(report) => report.posts_downloaded,
+            Err(e) if crate::is_stop_error(&e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to download post '{post_id}' of blog '{blog}'")
+                });
+            }
+            Err(e)
+                if matches!(
+                    ErrorKind::of(&e),
+                    ErrorKind::Paywalled | ErrorKind::NotFound
+                ) =>
+            {
+                log_warn!("{e:#}");
+                // A retry can't get it either, so its failures are dropped.
+                let post = HashSet::from([post_id.clone()]);
+                if let Err(e) =
+                    blog_index::record_failures(&download_path, blog, &post, Vec::new()).await
+                {
+                    log_warn!("{e:#}");
+                }
+                0
+            }
+            Err(e) => {
+                log_error!("Failed to download post '{post_id}' of blog '{blog}': {e:#}");
+                result.failed += 1;
+                continue;
+            }
+        };
         if downloaded == 0 {
             result.skipped += 1;
         } else {
-            result.downloaded += downloaded as u32;
+            result.downloaded += downloaded;
         }
     }
     Ok(result)
 }
 
+/// Downloads again the posts that had failures in their last session. Posts are fetched
+/// anew, so audio and file links get fresh signatures, and files already on disk are
+/// kept: only the missing pieces are downloaded.
+pub async fn retry_failures(
+    client: &ApiClient,
+    cfg: &AppConfig,
+    blog: &str,
+    download_options: DownloadOptions,
+    cancel_token: &CancellationToken,
+) -> Result<DownloadPostsResult, CoreError> {
+    let download_path = get_download_path(cfg);
+    let post_ids = blog_index::failed_post_ids(&download_path, blog).await?;
+    if post_ids.is_empty() {
+        log_info!("No failed downloads to retry for '{blog}'");
+        return Ok(DownloadPostsResult::default());
+    }
+    log_info!(
+        "Retrying {} posts with failures in '{blog}'",
+        post_ids.len()
+    );
+    download(
+        client,
+        cfg,
+        blog,
+        &post_ids,
+        download_options,
+        false,
+        cancel_token,
+    )
+    .await
+    .map_err(CoreError::from)
+}
+
 pub async fn delete_post(cfg: &AppConfig, blog: &str, post_id: &str) -> Result<()> {
     let download_path = get_download_path(cfg);
     if let Some(folder) = blog_index::resolve_post_folder(&download_path, blog, post_id).await? {
@@ -248,6 +382,7 @@ pub async fn delete_post(cfg: &AppConfig, blog: &str, post_id: &str) -> Result<(
             .with_context(|| format!("Failed to delete '{}'", folder.display()))?;
     }
     blog_index::remove_post(&download_path, blog, post_id).await?;
+    storage::forget_archive_sizes();
     Ok(())
 }
 
@@ -259,6 +394,7 @@ pub async fn delete_blog(cfg: &AppConfig, blog: &str) -> Result<()> {
             .await
             .with_context(|| format!("Failed to delete '{}'", blog_dir.display()))?;
     }
+    storage::forget_archive_sizes();
     Ok(())
 }
 
@@ -312,6 +448,8 @@ fn snapshot_from_index(
         blog: index.blog.clone(),
         last_checked_at: index.last_checked_at,
         posts,
+        settings: index.settings.clone(),
+        failures: index.failures.clone(),
     }
 }
 
@@ -0,0 +1,350 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use boosty_api::api_client::ApiClient;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::{self, AppConfig};
+use crate::context::Context;
+use crate::download_options::DownloadOptions;
+use crate::downloaded::{self, BlogSnapshot, DownloadPostsResult};
+use crate::error::CoreError;
+use crate::events::{self, DefaultSink, EventSink};
+use crate::pause::PauseHandle;
+use crate::session_report::SessionReport;
+use crate::subscriptions::{self, SubscriptionsResult};
+use crate::{media_client, menu_handler, retry, throttle, url_context};
+
+/// The downloader for other Rust tools: runs downloads and manages the archive under
+/// its output root with the config it was built with, reporting to its own event sink.
+///
+/// The config file is never read or written. Tokens refreshed during a session stay in the
+/// downloader, see [`config`](Self::config), and are sent to the sink as
+/// [`Event::TokensRefreshed`](crate::Event::TokensRefreshed). The rate limits, retries, token
+/// refreshes, media client and [`pause`](Self::pause) are the downloader's own; only the log
+/// verbosity and the log file are shared by the process.
+pub struct Downloader {
+    client: ApiClient,
+    cfg: Arc<Mutex<AppConfig>>,
+    context: Arc<Context>,
+    sink: Arc<dyn EventSink>,
+    pause: PauseHandle,
+}
+
+pub struct DownloaderBuilder {
+    cfg: AppConfig,
+    client: Option<ApiClient>,
+    output_root: Option<PathBuf>,
+    sink: Option<Arc<dyn EventSink>>,
+}
+
+impl Downloader {
+    pub fn builder(cfg: AppConfig) -> DownloaderBuilder {
+        DownloaderBuilder {
+            cfg,
+            client: None,
+            output_root: None,
+            sink: None,
+        }
+    }
+
+    pub fn client(&self) -> &ApiClient {
+        &self.client
+    }
+
+    /// The config it was built with and the tokens refreshed since.
+    pub fn config(&self) -> AppConfig {
+        self.cfg.lock().unwrap().clone()
+    }
+
+    pub fn output_root(&self) -> PathBuf {
+        config::get_download_path(&self.cfg.lock().unwrap())
+    }
+
+    /// Suspends the running calls of this downloader at their next file or post, until
+    /// [`resume`](Self::resume). Calls started meanwhile wait as well.
+    pub fn pause(&self) {
+        self.pause.pause();
+    }
+
+    pub fn resume(&self) {
+        self.pause.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_paused()
+    }
+
+    /// Downloads a blog or a single post, see [`process_boosty_url`](crate::process_boosty_url).
+    pub async fn download_url(
+        &self,
+        url: &str,
+        offset_url: Option<&str>,
+        download_options: DownloadOptions,
+        resume: bool,
+        cancel_token: &CancellationToken,
+    ) -> Result<SessionReport, CoreError> {
+        let ctx = url_context::build_url_context(url, offset_url)?;
+        let cfg = self.config();
+        self.scoped(menu_handler::process_boosty_url(
+            &self.client,
+            &cfg,
+            &ctx.url,
+            ctx.offset,
+            download_options,
+            resume,
+            cancel_token,
+        ))
+        .await
+    }
+
+    /// Checks the downloaded posts of `blog` against Boosty and looks for new ones.
+    pub async fn sync_blog(
+        &self,
+        blog: &str,
+        cancel_token: &CancellationToken,
+    ) -> Result<BlogSnapshot, CoreError> {
+        let cfg = self.config();
+        self.scoped(downloaded::refresh_blog(
+            &self.client,
+            &cfg,
+            blog,
+            cancel_token,
+        ))
+        .await
+    }
+
+    /// Downloads the given posts of `blog`; with `force` their folders are emptied first.
+    pub async fn download_posts(
+        &self,
+        blog: &str,
+        post_ids: &[String],
+        download_options: DownloadOptions,
+        force: bool,
+        cancel_token: &CancellationToken,
+    ) -> Result<DownloadPostsResult, CoreError> {
+        let cfg = self.config();
+        self.scoped(downloaded::download_posts(
+            &self.client,
+            &cfg,
+            blog,
+            post_ids,
+            download_options,
+            force,
+            cancel_token,
+        ))
+        .await
+    }
+
+    pub async fn retry_failures(
+        &self,
+        blog: &str,
+        download_options: DownloadOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<DownloadPostsResult, CoreError> {
+        let cfg = self.config();
+        self.scoped(downloaded::retry_failures(
+            &self.client,
+            &cfg,
+            blog,
+            download_options,
+            cancel_token,
+        ))
+        .await
+    }
+
+    /// Downloads every blog the active profile is subscribed to.
+    pub async fn download_subscriptions(
+        &self,
+        download_options: DownloadOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<SubscriptionsResult, CoreError> {
+        let cfg = self.config();
+        self.scoped(subscriptions::download_all(
+            &self.client,
+            &cfg,
+            download_options,
+            cancel_token,
+        ))
+        .await
+        .map_err(CoreError::from)
+    }
+
+    /// The downloaded blogs and posts as the archive index records them, without asking Boosty.
+    pub async fn blogs(&self) -> Result<Vec<BlogSnapshot>, CoreError> {
+        Ok(downloaded::scan(&self.output_root()).await?)
+    }
+
+    pub async fn delete_post(&self, blog: &str, post_id: &str) -> Result<(), CoreError> {
+        Ok(downloaded::delete_post(&self.config(), blog, post_id).await?)
+    }
+
+    pub async fn delete_blog(&self, blog: &str) -> Result<(), CoreError> {
+        Ok(downloaded::delete_blog(&self.config(), blog).await?)
+    }
+
+    async fn scoped<F: Future>(&self, future: F) -> F::Output {
+        events::with_context(
+            self.sink.clone(),
+            self.context.clone(),
+            Some(self.pause.clone()),
+            future,
+        )
+        .await
+    }
+}
+
+impl DownloaderBuilder {
+    /// Without one, a client is built for the proxy of the config.
+    pub fn client(mut self, client: ApiClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Folder the blogs are downloaded to; the download path of the config by default.
+    pub fn output_root(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_root = Some(path.into());
+        self
+    }
+
+    /// Receives the logs, progress and post results of every call. Without one they go
+    /// to the sink given to [`set_default_sink`](crate::set_default_sink), if any.
+    pub fn event_sink(mut self, sink: impl EventSink + 'static) -> Self {
+        self.sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Applies the rate limits and retries of the config and builds the media client, so a
+    /// bad header, certificate file or proxy fails here.
+    /// Credentials are put on the client by the first call that needs them.
+    pub async fn build(self) -> Result<Downloader, CoreError> {
+        let mut cfg = self.cfg;
+        if let Some(root) = self.output_root {
+            cfg.download_path = Some(root.to_string_lossy().into_owned());
+        }
+        if cfg.access_token.trim_start().starts_with('{') {
+            (cfg.access_token, cfg.token_expires_at) =
+                config::parse_access_token(&cfg.access_token);
+        }
+        let client = match self.client {
+            Some(client) => client,
+            None => crate::build_api_client(&cfg.proxy)?,
+        };
+        let settings = cfg.clone();
+        let cfg = Arc::new(Mutex::new(cfg));
+        let downloader = Downloader {
+            client,
+            context: Context::in_memory(cfg.clone()),
+            cfg,
+            sink: self.sink.unwrap_or_else(|| Arc::new(DefaultSink)),
+            pause: PauseHandle::default(),
+        };
+        downloader
+            .scoped(async {
+                throttle::configure(&settings.throttle);
+                retry::configure(&settings.retry);
+                media_client::configure(&settings.media_client, &settings.proxy)
+            })
+            .await?;
+        Ok(downloader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::RetryConfig;
+
+    #[tokio::test]
+    async fn output_root_replaces_the_download_path_of_the_config() {
+        let root =
+            std::env::temp_dir().join(format!("boosty_downloader_root_{}", std::process::id()));
+        let downloader = Downloader::builder(AppConfig::default())
+            .output_root(&root)
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(downloader.output_root(), root);
+        assert!(downloader.blogs().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn settings_stay_in_the_downloader() {
+        let retry = RetryConfig {
+            max_attempts: 9,
+            ..RetryConfig::default()
+        };
+        let downloader = Downloader::builder(AppConfig {
+            retry: retry.clone(),
+            ..AppConfig::default()
+        })
+        .build()
+        .await
+        .unwrap();
+
+        assert_eq!(
+            downloader.scoped(async { crate::retry::current() }).await,
+            retry
+        );
+        assert_eq!(crate::retry::current(), RetryConfig::default());
+    }
+
+    #[tokio::test]
+    async fn a_refresh_in_one_downloader_does_not_hold_up_another() {
+        let first = Downloader::builder(AppConfig::default())
+            .build()
+            .await
+            .unwrap();
+        let second = Downloader::builder(AppConfig::default())
+            .build()
+            .await
+            .unwrap();
+        let profile = AppConfig::default().active_profile;
+        let wait = std::time::Duration::from_millis(200);
+
+        let _refreshing = first.context.refresh_lock.lock().await;
+        let switched = tokio::time::timeout(
+            wait,
+            second.scoped(crate::auth::use_profile(second.client(), &profile)),
+        )
+        .await;
+        assert!(matches!(switched, Ok(Ok(()))));
+
+        let blocked = tokio::time::timeout(
+            wait,
+            first.scoped(crate::auth::use_profile(first.client(), &profile)),
+        )
+        .await;
+        assert!(blocked.is_err());
+    }
+
+    #[tokio::test]
+    async fn pausing_a_downloader_leaves_other_sessions_running() {
+        let first = Downloader::builder(AppConfig::default())
+            .build()
+            .await
+            .unwrap();
+        let second = Downloader::builder(AppConfig::default())
+            .build()
+            .await
+            .unwrap();
+        let token = CancellationToken::new();
+        let wait = std::time::Duration::from_millis(200);
+
+        first.pause();
+        let paused =
+            tokio::time::timeout(wait, first.scoped(crate::pause::wait_if_paused(&token))).await;
+        assert!(paused.is_err());
+        let running =
+            tokio::time::timeout(wait, second.scoped(crate::pause::wait_if_paused(&token))).await;
+        assert!(matches!(running, Ok(Ok(()))));
+        assert!(!crate::pause::is_paused());
+
+        first.resume();
+        let resumed =
+            tokio::time::timeout(wait, first.scoped(crate::pause::wait_if_paused(&token))).await;
+        assert!(matches!(resumed, Ok(Ok(()))));
+    }
+}
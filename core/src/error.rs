@@ -0,0 +1,319 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use boosty_api::error::ApiError;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{self, StorageLimitError};
+
+const CANCELLED_MESSAGE: &str = "Download cancelled by user";
+
+/// The run was stopped through its cancellation token.
+#[derive(Debug, Clone, Copy)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(CANCELLED_MESSAGE)
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// The post exists, but the current profile's subscription level doesn't open it.
+#[derive(Debug, Clone)]
+pub struct Paywalled {
+    pub blog: String,
+    pub post_id: String,
+}
+
+impl fmt::Display for Paywalled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Post '{}' of blog '{}' is not available with the current subscription",
+            self.post_id, self.blog
+        )
+    }
+}
+
+impl std::error::Error for Paywalled {}
+
+/// Context of a failed file operation that keeps the path for [`CoreError::path`].
+#[derive(Debug, Clone)]
+pub(crate) struct AtPath {
+    action: &'static str,
+    path: PathBuf,
+}
+
+impl AtPath {
+    pub(crate) fn new(action: &'static str, path: &Path) -> Self {
+        Self {
+            action,
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl fmt::Display for AtPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}'", self.action, self.path.display())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Connection failures, timeouts, rate limiting and server errors.
+    Network,
+    /// Missing or expired credentials, or access denied.
+    Auth,
+    NotFound,
+    Paywalled,
+    Filesystem,
+    /// Unexpected answer from the API or a broken local file.
+    Parse,
+    Cancelled,
+    StorageLimit,
+    Other,
+}
+
+impl ErrorKind {
+    /// Class of `err`: cancellation and storage limits anywhere in the chain win,
+    /// otherwise the outermost cause that can be classified.
+    pub fn of(err: &anyhow::Error) -> Self {
+        if crate::is_cancelled_error(err) {
+            return Self::Cancelled;
+        }
+        if storage::storage_limit(err).is_some() {
+            return Self::StorageLimit;
+        }
+        err.chain().find_map(kind_of_cause).unwrap_or(Self::Other)
+    }
+}
+
+fn kind_of_cause(cause: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+    if let Some(e) = cause.downcast_ref::<CoreError>() {
+        return Some(e.kind);
+    }
+    if cause.is::<Paywalled>() {
+        return Some(ErrorKind::Paywalled);
+    }
+    if cause.is::<StorageLimitError>() {
+        return Some(ErrorKind::StorageLimit);
+    }
+    if let Some(e) = cause.downcast_ref::<ApiError>() {
+        return match e {
+            ApiError::Unauthorized => Some(ErrorKind::Auth),
+            ApiError::HttpStatus { status, .. } => kind_of_status(status.as_u16()),
+            ApiError::HttpRequest(e) => kind_of_request(e),
+            ApiError::JsonParse(_)
+            | ApiError::JsonParseDetailed { .. }
+            | ApiError::Deserialization(_) => Some(ErrorKind::Parse),
+            _ => Some(ErrorKind::Other),
+        };
+    }
+    if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+        return kind_of_request(e);
+    }
+    if cause.is::<std::io::Error>() {
+        return Some(ErrorKind::Filesystem);
+    }
+    if cause.is::<serde_json::Error>() {
+        return Some(ErrorKind::Parse);
+    }
+    None
+}
+
+fn kind_of_request(e: &reqwest::Error) -> Option<ErrorKind> {
+    if let Some(status) = e.status() {
+        return kind_of_status(status.as_u16());
+    }
+    if e.is_decode() {
+        return Some(ErrorKind::Parse);
+    }
+    Some(ErrorKind::Network)
+}
+
+fn status_of_cause(cause: &(dyn std::error::Error + 'static)) -> Option<u16> {
+    if let Some(ApiError::HttpStatus { status, .. }) = cause.downcast_ref::<ApiError>() {
+        return Some(status.as_u16());
+    }
+    cause
+        .downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        .map(|status| status.as_u16())
+}
+
+fn kind_of_status(status: u16) -> Option<ErrorKind> {
+    match status {
+        401 | 403 => Some(ErrorKind::Auth),
+        404 | 410 => Some(ErrorKind::NotFound),
+        408 | 429 | 500..=599 => Some(ErrorKind::Network),
+        _ => None,
+    }
+}
+
+/// Error of a download, sync or refresh run, with its class for callers that handle
+/// the classes differently, and the details found in its causes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// HTTP status of the request that failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blog: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<String>,
+    /// File or folder that could not be read or written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The error this one was made from; not kept across serialization.
+    #[serde(skip)]
+    source: Option<Arc<anyhow::Error>>,
+}
+
+impl PartialEq for CoreError {
+    fn eq(&self, other: &Self) -> bool {
+        (
+            self.kind,
+            &self.message,
+            self.status,
+            &self.blog,
+            &self.post_id,
+            &self.path,
+        ) == (
+            other.kind,
+            &other.message,
+            other.status,
+            &other.blog,
+            &other.post_id,
+            &other.path,
+        )
+    }
+}
+
+impl Eq for CoreError {}
+
+impl CoreError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            status: None,
+            blog: None,
+            post_id: None,
+            path: None,
+            source: None,
+        }
+    }
+
+    pub fn cancelled() -> Self {
+        Self::new(ErrorKind::Cancelled, CANCELLED_MESSAGE)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.kind == ErrorKind::Cancelled
+    }
+}
+
+impl From<anyhow::Error> for CoreError {
+    fn from(err: anyhow::Error) -> Self {
+        let kind = ErrorKind::of(&err);
+        let message = match (kind, storage::storage_limit(&err)) {
+            (ErrorKind::Cancelled, _) => CANCELLED_MESSAGE.to_string(),
+            (_, Some(limit)) => limit.to_string(),
+            _ => format!("{err:#}"),
+        };
+        let (blog, post_id) = match (
+            err.downcast_ref::<Paywalled>(),
+            storage::storage_limit(&err),
+        ) {
+            (Some(paywalled), _) => (
+                Some(paywalled.blog.clone()),
+                Some(paywalled.post_id.clone()),
+            ),
+            (None, Some(limit)) => (Some(limit.blog.clone()), None),
+            (None, None) => (None, None),
+        };
+        Self {
+            kind,
+            message,
+            status: err.chain().find_map(status_of_cause),
+            blog,
+            post_id,
+            path: err.downcast_ref::<AtPath>().map(|at| at.path.clone()),
+            source: Some(Arc::new(err)),
+        }
+    }
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        let source: &anyhow::Error = self.source.as_deref()?;
+        Some(source.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn errors_are_classified_through_their_context() {
+        let cancelled = Err::<(), _>(anyhow::Error::from(Cancelled))
+            .context("Failed to download post")
+            .unwrap_err();
+        let err = CoreError::from(cancelled);
+        assert!(err.is_cancelled());
+        assert_eq!(err.message, CANCELLED_MESSAGE);
+
+        let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let err = Err::<(), _>(io)
+            .context("Failed to write file")
+            .unwrap_err();
+        let err = CoreError::from(err);
+        assert_eq!(err.kind, ErrorKind::Filesystem);
+        assert_eq!(err.message, "Failed to write file: denied");
+        assert!(std::error::Error::source(&err).is_some());
+
+        let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let err = Err::<(), _>(io)
+            .with_context(|| AtPath::new("Failed to create file", Path::new("/data/a.jpg")))
+            .context("Failed to download post")
+            .unwrap_err();
+        let err = CoreError::from(err);
+        assert_eq!(err.kind, ErrorKind::Filesystem);
+        assert_eq!(err.path.as_deref(), Some(Path::new("/data/a.jpg")));
+
+        let paywalled = anyhow::Error::from(Paywalled {
+            blog: "author".into(),
+            post_id: "1".into(),
+        });
+        assert_eq!(ErrorKind::of(&paywalled), ErrorKind::Paywalled);
+        let err = CoreError::from(paywalled.context("Failed to fetch post"));
+        assert_eq!(err.blog.as_deref(), Some("author"));
+        assert_eq!(err.post_id.as_deref(), Some("1"));
+
+        let parse = serde_json::from_str::<u32>("x").unwrap_err();
+        assert_eq!(ErrorKind::of(&parse.into()), ErrorKind::Parse);
+        assert_eq!(ErrorKind::of(&anyhow::anyhow!("oops")), ErrorKind::Other);
+        assert_eq!(
+            ErrorKind::of(&ApiError::Unauthorized.into()),
+            ErrorKind::Auth
+        );
+
+        let wrapped = anyhow::Error::from(CoreError::new(ErrorKind::Auth, "expired"));
+        assert_eq!(ErrorKind::of(&wrapped), ErrorKind::Auth);
+    }
+}
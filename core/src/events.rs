@@ -0,0 +1,238 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::config::AuthProfile;
+use crate::context::Context;
+use crate::logger::LogLevel;
+use crate::pause::PauseHandle;
+use crate::progress_reporter::Progress;
+use crate::session_report::Recorder;
+use crate::storage::Budget;
+
+/// What a download session reports while it runs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Log {
+        level: LogLevel,
+        message: String,
+    },
+    /// A download session starts; `files_total` may grow while it runs.
+    SessionStarted {
+        files_total: u64,
+    },
+    PostStarted {
+        post_id: String,
+        title: String,
+    },
+    PostFinished {
+        post_id: String,
+        title: String,
+        outcome: PostOutcome,
+    },
+    /// Files done and the bytes of the current file, sent at most every 120 ms
+    /// and whenever a file starts or ends.
+    Progress(ProgressMessage),
+    SessionFinished,
+    /// Tokens of `profile` were refreshed. Only a [`Downloader`](crate::Downloader) sends
+    /// it, since its credentials aren't saved anywhere else; the CLI and GUI save them to
+    /// the config file.
+    TokensRefreshed {
+        profile: String,
+        credentials: AuthProfile,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PostOutcome {
+    Downloaded,
+    /// Left out because its folder name belongs to another post.
+    Skipped,
+    /// The current profile has no access to it.
+    Unavailable,
+    Failed {
+        message: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProgressMessage {
+    pub files_done: u64,
+    pub files_total: u64,
+    pub file_name: Option<String>,
+    pub current: u64,
+    pub total: u64,
+    pub paused: bool,
+}
+
+/// Receiver of the events of the sessions it is given to.
+pub trait EventSink: Send + Sync {
+    fn send(&self, event: Event);
+}
+
+impl EventSink for mpsc::UnboundedSender<Event> {
+    fn send(&self, event: Event) {
+        // The receiver went away; nobody is listening any more.
+        let _ = mpsc::UnboundedSender::send(self, event);
+    }
+}
+
+/// A sink with the state of the session running in it, so sessions in different scopes
+/// don't touch each other's progress, report, storage budget, auth profile or pause.
+pub(crate) struct EventScope {
+    /// Only the default scope changes its sink, see [`set_default_sink`].
+    sink: RwLock<Option<Arc<dyn EventSink>>>,
+    pub(crate) progress: Mutex<Option<Progress>>,
+    pub(crate) report: Mutex<Option<Recorder>>,
+    pub(crate) budget: Mutex<Option<Budget>>,
+    /// Auth profile whose credentials this scope last put on the client.
+    pub(crate) profile: Mutex<Option<String>>,
+    pub(crate) pause: PauseHandle,
+    /// Whether `pause` was given to the scope rather than made for it.
+    pub(crate) shares_pause: bool,
+    pub(crate) context: Arc<Context>,
+}
+
+impl EventScope {
+    fn new(
+        sink: Option<Arc<dyn EventSink>>,
+        context: Arc<Context>,
+        pause: Option<PauseHandle>,
+    ) -> Arc<Self> {
+        let scope = Arc::new(Self {
+            sink: RwLock::new(sink),
+            context,
+            progress: Mutex::new(None),
+            report: Mutex::new(None),
+            budget: Mutex::new(None),
+            profile: Mutex::new(None),
+            shares_pause: pause.is_some(),
+            pause: pause.unwrap_or_default(),
+        });
+        let mut scopes = SCOPES.lock().unwrap();
+        scopes.retain(|s| s.strong_count() > 0);
+        scopes.push(Arc::downgrade(&scope));
+        scope
+    }
+
+    pub(crate) fn send(&self, event: Event) {
+        if let Some(sink) = self.sink.read().unwrap().as_ref() {
+            sink.send(event);
+        }
+    }
+}
+
+tokio::task_local! {
+    static SCOPE: Arc<EventScope>;
+}
+
+/// Scope of code running outside [`with_sink`].
+static DEFAULT_SCOPE: Lazy<Arc<EventScope>> =
+    Lazy::new(|| EventScope::new(None, Context::default_context(), None));
+
+/// Every live scope, for state changes that come from outside a session, like a pause.
+static SCOPES: Lazy<Mutex<Vec<Weak<EventScope>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Receives the events of code that runs outside [`with_sink`]: startup messages and
+/// sessions of a frontend that doesn't give its own sink. Replaces the previous one.
+pub fn set_default_sink<S: EventSink + 'static>(sink: S) {
+    *DEFAULT_SCOPE.sink.write().unwrap() = Some(Arc::new(sink));
+}
+
+/// Runs `future` in a scope of its own, with its events, progress included, going to
+/// `sink`. Sessions run in different scopes report separately, even at the same time.
+pub async fn with_sink<F: Future>(sink: Arc<dyn EventSink>, future: F) -> F::Output {
+    with_context(sink, Context::default_context(), None, future).await
+}
+
+/// [`with_sink`] whose session is paused and resumed through `pause`.
+pub async fn with_sink_and_pause<F: Future>(
+    sink: Arc<dyn EventSink>,
+    pause: PauseHandle,
+    future: F,
+) -> F::Output {
+    with_context(sink, Context::default_context(), Some(pause), future).await
+}
+
+/// [`with_sink`] with the limits, media client and credentials of `context`.
+pub(crate) async fn with_context<F: Future>(
+    sink: Arc<dyn EventSink>,
+    context: Arc<Context>,
+    pause: Option<PauseHandle>,
+    future: F,
+) -> F::Output {
+    SCOPE
+        .scope(EventScope::new(Some(sink), context, pause), future)
+        .await
+}
+
+/// Passes events on to the sink given to [`set_default_sink`].
+pub(crate) struct DefaultSink;
+
+impl EventSink for DefaultSink {
+    fn send(&self, event: Event) {
+        DEFAULT_SCOPE.send(event);
+    }
+}
+
+/// The scope of the running task, or the default one.
+pub(crate) fn current() -> Arc<EventScope> {
+    SCOPE
+        .try_with(Arc::clone)
+        .unwrap_or_else(|_| DEFAULT_SCOPE.clone())
+}
+
+/// Scope of the CLI and of code that runs outside [`with_sink`].
+pub(crate) fn default_scope() -> Arc<EventScope> {
+    DEFAULT_SCOPE.clone()
+}
+
+/// The default scope included.
+pub(crate) fn live_scopes() -> Vec<Arc<EventScope>> {
+    Lazy::force(&DEFAULT_SCOPE);
+    SCOPES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
+pub(crate) fn emit(event: Event) {
+    current().send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn events_go_to_the_sink_of_their_scope() {
+        let (first_tx, mut first_rx) = mpsc::unbounded_channel();
+        let (second_tx, mut second_rx) = mpsc::unbounded_channel();
+
+        with_sink(Arc::new(first_tx), async {
+            emit(Event::SessionFinished);
+            with_sink(Arc::new(second_tx), async {
+                emit(Event::Log {
+                    level: LogLevel::Info,
+                    message: "inner".into(),
+                })
+            })
+            .await;
+        })
+        .await;
+
+        assert!(matches!(first_rx.try_recv(), Ok(Event::SessionFinished)));
+        assert!(first_rx.try_recv().is_err());
+        assert!(matches!(
+            second_rx.try_recv(),
+            Ok(Event::Log { message, .. }) if message == "inner"
+        ));
+    }
+}
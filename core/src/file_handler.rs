@@ -1,22 +1,76 @@
+use crate::error::AtPath;
+use crate::naming::{self, NamingConfig, PostFields};
 use crate::progress_reporter;
-use crate::{headers, log_error, log_info, log_warn};
+use crate::{log_debug, log_error, log_info, log_trace, log_warn, logger, media_client, retry};
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
+use reqwest::StatusCode;
+use reqwest::header::RANGE;
+use std::fmt;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio_util::sync::CancellationToken;
 
 pub enum DownloadResult {
-    Error(String),
+    Error(DownloadError),
     Success,
     Skipped,
 }
 
-const MAX_RETRIES: usize = 5;
-const MAX_TITLE_CHARS: usize = 80;
+/// Why the server did not give us the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadError {
+    /// The file link is signed, and without an access token there is no signature.
+    AuthRequired { file: String },
+    Http {
+        status: StatusCode,
+        /// `Retry-After` of a 429 or 503 answer.
+        retry_after: Option<Duration>,
+        body: String,
+    },
+}
+
+impl DownloadError {
+    /// Timeouts, rate limiting and server errors; the rest won't change on retry.
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            Self::AuthRequired { .. } => false,
+            Self::Http { status, .. } => {
+                *status == StatusCode::REQUEST_TIMEOUT
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+                    || status.is_server_error()
+            }
+        }
+    }
+
+    /// 403 on a signed link, which usually means the signature expired.
+    pub fn is_forbidden(&self) -> bool {
+        matches!(self, Self::Http { status, .. } if *status == StatusCode::FORBIDDEN)
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Http { retry_after, .. } => *retry_after,
+            Self::AuthRequired { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AuthRequired { file } => write!(
+                f,
+                "Authorization required: to download file '{file}' an access token must be provided"
+            ),
+            Self::Http { status, body, .. } => write!(f, "HTTP {status}: {body}"),
+        }
+    }
+}
+
+const PART_EXTENSION: &str = ".part";
 
 async fn ensure_blog_folder(blog_name: &str, base_path: &Path) -> Result<PathBuf> {
     let blog_path = base_path.join(blog_name);
@@ -47,7 +101,7 @@ async fn ensure_post_folder(
     if !exists {
         fs::create_dir_all(&post_path)
             .await
-            .with_context(|| format!("Failed to create post folder '{}'", post_path.display()))?;
+            .with_context(|| AtPath::new("Failed to create post folder", &post_path))?;
     }
     Ok(post_path)
 }
@@ -60,62 +114,61 @@ async fn download_file_content(
     cancel_token: &CancellationToken,
 ) -> Result<DownloadResult> {
     log_info!("Downloading file '{title}'...");
-    for attempt in 1..=MAX_RETRIES {
-        crate::ensure_not_cancelled(cancel_token)?;
-        match download_file_once(folder_path, url, title, signed_query, cancel_token).await {
-            Ok(r @ DownloadResult::Success) => {
-                progress_reporter::finish_file();
-                return Ok(r);
-            }
-            Ok(r @ DownloadResult::Skipped) => {
-                progress_reporter::finish_file();
-                return Ok(r);
-            }
-            Ok(DownloadResult::Error(ref msg)) if !is_retriable_download_error(msg) => {
-                progress_reporter::finish_file();
-                return Ok(DownloadResult::Error(msg.clone()));
-            }
-            Ok(DownloadResult::Error(_)) if attempt < MAX_RETRIES => {
-                progress_reporter::abandon_file();
-                log_warn!("Download attempt {attempt} failed (logical error), retrying...");
-            }
-            Err(e) if crate::is_cancelled_error(&e) => {
-                progress_reporter::abandon_file();
-                let safe_name = sanitize_name(title);
-                let output_path = folder_path.join(safe_name);
-                let _ = fs::remove_file(&output_path).await;
-                return Err(e);
-            }
-            Err(e) if attempt < MAX_RETRIES => {
-                progress_reporter::abandon_file();
-                log_error!("Download attempt {attempt} failed with error: {e}");
-            }
-            result => {
-                progress_reporter::finish_file();
-                return result;
-            }
-        }
-
-        let safe_name = sanitize_name(title);
-        let output_path = folder_path.join(safe_name);
-        let _ = fs::remove_file(&output_path).await;
-
+    let part_path = part_file_path(&folder_path.join(sanitize_name(title)));
+    let retry = retry::current();
+    let attempts = retry.attempts();
+    for attempt in 1..=attempts {
+        crate::pause::wait_if_paused(cancel_token).await?;
+        let retry_after =
+            match download_file_once(folder_path, url, title, signed_query, cancel_token).await {
+                Ok(r @ DownloadResult::Success) => {
+                    progress_reporter::finish_file();
+                    return Ok(r);
+                }
+                Ok(r @ DownloadResult::Skipped) => {
+                    progress_reporter::finish_file();
+                    return Ok(r);
+                }
+                Ok(DownloadResult::Error(e)) if !e.is_retriable() => {
+                    progress_reporter::finish_file();
+                    let _ = fs::remove_file(&part_path).await;
+                    return Ok(DownloadResult::Error(e));
+                }
+                Ok(DownloadResult::Error(e)) if attempt < attempts => {
+                    progress_reporter::abandon_file();
+                    log_warn!("Download attempt {attempt} failed ({e}), retrying...");
+                    e.retry_after()
+                }
+                Err(e) if crate::is_stop_error(&e) => {
+                    progress_reporter::abandon_file();
+                    let _ = fs::remove_file(&part_path).await;
+                    return Err(e);
+                }
+                Err(e) if attempt < attempts => {
+                    progress_reporter::abandon_file();
+                    log_error!("Download attempt {attempt} failed with error: {e}");
+                    None
+                }
+                result => {
+                    progress_reporter::finish_file();
+                    return result;
+                }
+            };
+
+        // The partial file is kept, the next attempt continues from its end.
         tokio::select! {
-            _ = tokio::time::sleep(Duration::from_secs(2_u64.pow(attempt as u32))) => {}
-            _ = cancel_token.cancelled() => anyhow::bail!(crate::DOWNLOAD_CANCELLED_MESSAGE),
+            _ = tokio::time::sleep(retry.delay(attempt, retry_after)) => {}
+            _ = cancel_token.cancelled() => return Err(crate::Cancelled.into()),
         }
     }
-    unreachable!("MAX_RETRIES exhausted but loop should return earlier")
+    unreachable!("retry attempts exhausted but loop should return earlier")
 }
 
-fn is_retriable_download_error(msg: &str) -> bool {
-    if msg.contains("Authorization required") {
-        return false;
-    }
-    if msg.contains("HTTP 401") || msg.contains("HTTP 403") || msg.contains("HTTP 404") {
-        return false;
-    }
-    true
+/// Path of the in-progress download; renamed to `output_path` once complete.
+pub fn part_file_path(output_path: &Path) -> PathBuf {
+    let mut name = output_path.as_os_str().to_owned();
+    name.push(PART_EXTENSION);
+    PathBuf::from(name)
 }
 
 pub async fn download_file_once(
@@ -140,90 +193,158 @@ pub async fn download_file_once(
     }
 
     let signed_query = if signed_query.is_some() && signed_query.unwrap().is_empty() {
-        return Ok(DownloadResult::Error(format!(
-            "Authorization required: to download file '{title}' an access token must be provided"
-        )));
+        return Ok(DownloadResult::Error(DownloadError::AuthRequired {
+            file: title.to_string(),
+        }));
     } else {
         signed_query.unwrap_or("")
     };
 
+    let part_path = part_file_path(&output_path);
+    let offset = fs::metadata(&part_path)
+        .await
+        .map(|meta| meta.len())
+        .unwrap_or(0);
+
     let full_url = format!("{url}{signed_query}");
-    let client = reqwest::Client::new();
-    let resp = client
-        .get(full_url)
-        .headers(headers::default_download_headers())
+    let client = media_client::media_client()?;
+    let started = Instant::now();
+    let mut request = client.get(&full_url);
+    if offset > 0 {
+        log_trace!("Requesting '{title}' from byte {offset}");
+        request = request.header(RANGE, format!("bytes={offset}-"));
+    }
+    let resp = request
         .send()
         .await
         .with_context(|| format!("HTTP GET failed for file URL '{url}'"))?;
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let error_body = resp.text().await.unwrap_or_default();
-        return Ok(DownloadResult::Error(format!(
-            "HTTP {status}: {error_body}"
-        )));
+
+    let status = resp.status();
+    log_debug!(
+        "GET {} -> {status} in {} ms",
+        logger::redact_url(&full_url),
+        started.elapsed().as_millis()
+    );
+    if offset > 0 && status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file already holds the whole body.
+        progress_reporter::start_file(title, offset);
+        progress_reporter::inc(offset);
+        return finish_part_file(&part_path, &output_path).await;
+    }
+    if !status.is_success() {
+        let retry_after = if status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::SERVICE_UNAVAILABLE
+        {
+            retry::retry_after(resp.headers())
+        } else {
+            None
+        };
+        let body = resp.text().await.unwrap_or_default();
+        return Ok(DownloadResult::Error(DownloadError::Http {
+            status,
+            retry_after,
+            body,
+        }));
     }
 
-    let total_size = resp.content_length().unwrap_or(0);
-    progress_reporter::start_file(title, total_size)?;
+    let resumed_from = if status == StatusCode::PARTIAL_CONTENT {
+        offset
+    } else {
+        0
+    };
+    let mut file = if resumed_from > 0 {
+        log_info!("Resuming file '{title}' from byte {resumed_from}");
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&part_path)
+            .await
+            .with_context(|| AtPath::new("Failed to open file", &part_path))?
+    } else {
+        fs::File::create(&part_path)
+            .await
+            .with_context(|| AtPath::new("Failed to create file", &part_path))?
+    };
+
+    let total_size = resp
+        .content_length()
+        .map(|len| len + resumed_from)
+        .unwrap_or(0);
+    progress_reporter::start_file(title, total_size);
+    progress_reporter::inc(resumed_from);
 
-    let mut file = fs::File::create(&output_path)
-        .await
-        .with_context(|| format!("Failed to create file '{}'", output_path.display()))?;
     let mut stream = resp.bytes_stream();
+    let mut written = 0u64;
 
-    while let Some(chunk) = stream.next().await {
+    loop {
+        crate::pause::wait_if_paused(cancel_token).await?;
+        let Some(chunk) = stream.next().await else {
+            break;
+        };
         crate::ensure_not_cancelled(cancel_token)?;
         let chunk = chunk.with_context(|| format!("Error while reading chunk from '{url}'"))?;
-        file.write_all(&chunk).await?;
+        crate::storage::consume(chunk.len() as u64)?;
+        file.write_all(&chunk)
+            .await
+            .with_context(|| AtPath::new("Failed to write file", &part_path))?;
+        written += chunk.len() as u64;
         progress_reporter::inc(chunk.len() as u64);
+        crate::session_report::add_bytes(chunk.len() as u64);
+        crate::throttle::download_chunk(chunk.len() as u64).await;
     }
-
-    Ok(DownloadResult::Success)
+    file.flush()
+        .await
+        .with_context(|| AtPath::new("Failed to write file", &part_path))?;
+    drop(file);
+    log_debug!(
+        "Received {written} bytes of '{title}' in {} ms",
+        started.elapsed().as_millis()
+    );
+
+    finish_part_file(&part_path, &output_path).await
 }
 
-pub fn post_folder_name(title: &str, created_at: i64, post_id: &str) -> String {
-    let datetime: DateTime<Utc> = DateTime::from_timestamp(created_at, 0)
-        .or_else(|| DateTime::from_timestamp(0, 0))
-        .expect("unix epoch is a valid timestamp");
-    let date_str = datetime.format("%Y.%m.%d").to_string();
-    let safe_id = sanitize_name(post_id);
-    let mut safe_title = sanitize_name(title);
-
-    if safe_title.chars().count() > MAX_TITLE_CHARS {
-        safe_title = safe_title.chars().take(MAX_TITLE_CHARS).collect();
-        while safe_title.ends_with('.') || safe_title.ends_with(' ') {
-            safe_title.pop();
-        }
-        if safe_title.is_empty() {
-            safe_title = "_".to_string();
-        }
-    }
-
-    format!("{date_str} {safe_title} [{safe_id}]")
+async fn finish_part_file(part_path: &Path, output_path: &Path) -> Result<DownloadResult> {
+    fs::rename(part_path, output_path)
+        .await
+        .with_context(|| AtPath::new("Failed to move downloaded file to", output_path))?;
+    Ok(DownloadResult::Success)
 }
 
+/// Folder for the post: the one it was downloaded to before, or a new one named by the
+/// post folder template. `None` when the name belongs to another post and the collision
+/// policy is to skip.
 pub async fn prepare_folder_path(
-    blog_name: &str,
-    post_title: &str,
-    created_at: i64,
-    post_id: &str,
+    post: &PostFields,
+    naming: &NamingConfig,
     base_path: &Path,
-) -> Result<PathBuf> {
+) -> Result<Option<PathBuf>> {
     if let Some(existing) =
-        crate::blog_index::resolve_post_folder(base_path, blog_name, post_id).await?
+        crate::blog_index::resolve_post_folder(base_path, &post.blog, &post.id).await?
     {
-        return Ok(existing);
+        return Ok(Some(existing));
     }
 
-    let folder_name = post_folder_name(post_title, created_at, post_id);
+    let wanted = naming.post_folder_name(post);
+    let taken = crate::blog_index::folders_of_other_posts(base_path, &post.blog, &post.id).await?;
+    let Some(folder_name) = naming::resolve_folder_collision(&wanted, &taken, naming.collision)
+    else {
+        log_warn!(
+            "Folder '{wanted}' already belongs to another post, skipping '{}'",
+            post.title
+        );
+        return Ok(None);
+    };
 
-    let post_folder_path: PathBuf = ensure_post_folder(blog_name, &folder_name, base_path)
+    let post_folder_path: PathBuf = ensure_post_folder(&post.blog, &folder_name, base_path)
         .await
         .with_context(|| {
-            format!("Failed to create folder for post '{post_title}' in blog '{blog_name}'")
+            format!(
+                "Failed to create folder for post '{}' in blog '{}'",
+                post.title, post.blog
+            )
         })?;
 
-    Ok(post_folder_path)
+    Ok(Some(post_folder_path))
 }
 
 pub async fn clear_dir_contents(dir: &Path) -> Result<()> {
@@ -246,6 +367,7 @@ pub async fn clear_dir_contents(dir: &Path) -> Result<()> {
                 .with_context(|| format!("Failed to remove '{}'", path.display()))?;
         }
     }
+    crate::storage::forget_archive_sizes();
     Ok(())
 }
 
@@ -308,21 +430,6 @@ pub async fn download_media(
     Ok((result, rel))
 }
 
-pub fn media_file_name(id: &str, title: &str, extension: Option<&str>) -> String {
-    let base = format!("{id}_{title}");
-    let mut name = sanitize_name(&base);
-    if let Some(ext) = extension {
-        let ext = ext.trim_start_matches('.');
-        if !ext.is_empty() {
-            let suffix = format!(".{ext}");
-            if !name.to_lowercase().ends_with(&suffix.to_lowercase()) {
-                name.push_str(&suffix);
-            }
-        }
-    }
-    name
-}
-
 pub fn audio_extension(file_type: Option<&str>) -> Option<&'static str> {
     match file_type.map(|s| s.to_ascii_lowercase()) {
         Some(ft) if ft == "mp3" || ft.contains("mpeg") => Some("mp3"),
@@ -356,29 +463,27 @@ mod tests {
     use super::*;
 
     #[test]
-    fn post_folder_name_includes_id() {
-        let a = post_folder_name("Same title", 1_700_000_000, "id-aaa");
-        let b = post_folder_name("Same title", 1_700_000_000, "id-bbb");
-        assert_ne!(a, b);
-        assert!(a.contains("[id-aaa]"));
-        assert!(b.contains("[id-bbb]"));
-        assert!(a.starts_with("2023.11.14 "));
-    }
-
-    #[test]
-    fn post_folder_name_truncates_title_keeps_id() {
-        let long_title = "a".repeat(200);
-        let name = post_folder_name(&long_title, 1_700_000_000, "keep-this-id");
-        assert!(name.contains("[keep-this-id]"));
-        assert!(name.len() < long_title.len() + 40);
+    fn part_file_sits_next_to_output() {
+        let output = Path::new("/tmp/post/abc_track.mp3");
+        assert_eq!(
+            part_file_path(output),
+            PathBuf::from("/tmp/post/abc_track.mp3.part")
+        );
     }
 
     #[test]
-    fn media_file_name_prefixes_id() {
-        assert_eq!(media_file_name("abc", "track.mp3", None), "abc_track.mp3");
-        assert_eq!(
-            media_file_name("abc", "track", Some("mp3")),
-            "abc_track.mp3"
-        );
+    fn only_transient_http_errors_are_retried() {
+        let http = |status| DownloadError::Http {
+            status,
+            retry_after: None,
+            body: String::new(),
+        };
+        assert!(http(StatusCode::TOO_MANY_REQUESTS).is_retriable());
+        assert!(http(StatusCode::SERVICE_UNAVAILABLE).is_retriable());
+        assert!(http(StatusCode::REQUEST_TIMEOUT).is_retriable());
+        assert!(!http(StatusCode::NOT_FOUND).is_retriable());
+        assert!(!http(StatusCode::FORBIDDEN).is_retriable());
+        assert!(http(StatusCode::FORBIDDEN).is_forbidden());
+        assert!(!DownloadError::AuthRequired { file: "a".into() }.is_retriable());
     }
 }
@@ -0,0 +1,143 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use once_cell::sync::Lazy;
+
+use crate::logger::LogLevel;
+use crate::paths;
+
+pub const LOG_FILE_NAME: &str = "boosty_downloader.log";
+/// The log file is rotated once it would grow past this size.
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept next to the current one; `.1` is the newest.
+const KEPT_FILES: u32 = 3;
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+}
+
+static LOG_FILE: Lazy<Mutex<Option<LogFile>>> = Lazy::new(|| Mutex::new(None));
+
+/// Starts copying every log line to [`LOG_FILE_NAME`] in the logs folder of the data
+/// directory, next to the logger set by the frontend. Returns the file's path.
+pub fn init() -> Result<PathBuf> {
+    let path = log_path()?;
+    let log = LogFile::open(&path, MAX_FILE_BYTES)?;
+    *LOG_FILE.lock().unwrap() = Some(log);
+    Ok(path)
+}
+
+pub fn log_path() -> Result<PathBuf> {
+    Ok(paths::log_dir()?.join(LOG_FILE_NAME))
+}
+
+/// The current log file and the rotated ones that exist, oldest first.
+pub fn log_files() -> Result<Vec<PathBuf>> {
+    let path = log_path()?;
+    let mut files: Vec<PathBuf> = (1..=KEPT_FILES)
+        .rev()
+        .map(|n| rotated_path(&path, n))
+        .collect();
+    files.push(path);
+    files.retain(|file| file.is_file());
+    Ok(files)
+}
+
+pub(crate) fn write(level: LogLevel, message: &str) {
+    if let Some(log) = LOG_FILE.lock().unwrap().as_mut() {
+        let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+        log.append(&format!("{time} {:<5} {message}\n", level.as_str()));
+    }
+}
+
+impl LogFile {
+    fn open(path: &Path, max_bytes: u64) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create log folder '{}'", dir.display()))?;
+        }
+        let file = open_append(path)?;
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+        })
+    }
+
+    /// A line that can't be written is dropped; logging never fails a download.
+    fn append(&mut self, line: &str) {
+        let len = line.len() as u64;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            // Without rotation the current file keeps growing.
+            let _ = self.rotate();
+        }
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.size += len;
+        }
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        for n in (1..KEPT_FILES).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.is_file() {
+                fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file '{}'", path.display()))
+}
+
+fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn full_file_is_rotated_and_old_ones_dropped() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("boosty_log_{}_{nanos}", std::process::id()));
+        let path = dir.join(LOG_FILE_NAME);
+        let mut log = LogFile::open(&path, 10).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n", "fifth\n"] {
+            log.append(line);
+        }
+
+        let read = |n| fs::read_to_string(rotated_path(&path, n)).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fifth\n");
+        assert_eq!(read(1), "fourth\n");
+        assert_eq!(read(3), "second\n");
+        assert!(!rotated_path(&path, 4).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
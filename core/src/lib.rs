@@ -1,27 +1,57 @@
+pub(crate) mod account;
+pub(crate) mod archive_move;
+pub(crate) mod auth;
 pub(crate) mod blog_index;
+pub(crate) mod blog_settings;
 pub(crate) mod checks;
 pub(crate) mod cli;
 pub(crate) mod comment_handler;
 pub(crate) mod config;
 pub(crate) mod console_logger;
 pub(crate) mod content_items_handler;
+pub(crate) mod context;
+pub(crate) mod diagnostics;
 pub(crate) mod download_options;
 pub(crate) mod downloaded;
+pub(crate) mod downloader;
+pub(crate) mod error;
+pub(crate) mod events;
 pub(crate) mod file_handler;
+pub(crate) mod file_logger;
 pub(crate) mod headers;
 pub(crate) mod logger;
+pub(crate) mod media_client;
 pub(crate) mod menu_handler;
+pub(crate) mod naming;
 pub(crate) mod parser;
+pub(crate) mod paths;
+pub(crate) mod pause;
 pub(crate) mod post_handler;
 pub(crate) mod post_page;
 pub(crate) mod progress_reporter;
+pub(crate) mod proxy;
+pub(crate) mod retry;
+pub(crate) mod secrets;
+pub(crate) mod session_report;
+pub(crate) mod session_state;
+pub(crate) mod storage;
+pub(crate) mod subscriptions;
+pub(crate) mod throttle;
 pub(crate) mod url_context;
 
+pub use account::{AccountInfo, AccountUser, Subscription, TokenStatus, check_auth};
+pub use archive_move::{
+    MoveArchiveResult, MoveJournal, discard_pending_move, move_archive, pending_move,
+};
+pub use blog_index::FailedItem;
+pub use blog_settings::{BlogSettings, OutputFormat, PostFilter};
 pub use cli::print_error;
 pub use config::{
-    AppConfig, CommentsConfig, clear_auth, get_download_path, load_config, save_config, sync_auth,
+    AppConfig, AuthProfile, CommentsConfig, clear_auth, get_download_path, load_config,
+    save_config, sync_auth,
 };
 pub use console_logger::ConsoleLogger;
+pub use diagnostics::export as export_diagnostics;
 pub use download_options::{
     DownloadOption, DownloadOptions, default_download_options, filter_content_items,
 };
@@ -29,10 +59,40 @@ pub use downloaded::{
     BlogSnapshot, DownloadPostsResult, PostSnapshot, PostSyncStatus,
     delete_blog as delete_downloaded_blog, delete_post as delete_downloaded_post,
     download_posts as download_downloaded_posts, refresh_blog as refresh_downloaded_blog,
-    scan as scan_downloaded,
+    retry_failures as retry_failed_downloads, scan as scan_downloaded, update_blog_settings,
+};
+pub use downloader::{Downloader, DownloaderBuilder};
+pub use error::{Cancelled, CoreError, ErrorKind, Paywalled};
+pub use events::{
+    Event, EventSink, PostOutcome, ProgressMessage, set_default_sink, with_sink as with_event_sink,
+    with_sink_and_pause as with_event_sink_and_pause,
 };
-pub use logger::{LogLevel, LogMessage, Logger, ProgressMessage, get_logger, set_logger};
+pub use file_handler::{DownloadError, DownloadResult};
+pub use file_logger::{init as init_file_log, log_path as log_file_path};
+pub use logger::{
+    LogLevel, LogMessage, LogVerbosity, redact_url, set_verbosity as set_log_verbosity, write_log,
+};
+pub use media_client::{MediaClientConfig, build_media_client, media_client};
 pub use menu_handler::{handle_menu, process_boosty_url};
+pub use naming::{
+    CollisionPolicy, FolderMove, NamingConfig, RenameReport,
+    rename_downloaded as rename_downloaded_folders,
+};
+pub use paths::{app_file_path, config_arg, config_path, set_config_path};
+pub use pause::{PauseHandle, is_paused, pause as pause_download, resume as resume_download};
+pub use proxy::{ProxyConfig, ProxyScheme};
+pub use retry::RetryConfig;
+pub use secrets::{TokenStorage, ask_passphrase as ask_secrets_passphrase, tokens_in_plain_text};
+pub use session_report::{FailedFile, FailedPost, ReportedPost, SessionReport, UrlKind};
+pub use session_state::{
+    SessionState, load_for_url as load_interrupted_session, remove as discard_interrupted_session,
+};
+pub use storage::{LimitKind, StorageConfig, StorageLimitError, storage_limit};
+pub use subscriptions::{
+    SubscribedBlog, SubscriptionList, SubscriptionsResult, download_all as download_subscriptions,
+    load_list as load_subscription_list,
+};
+pub use throttle::ThrottleConfig;
 pub use url_context::build_url_context;
 
 use anyhow::Result;
@@ -43,34 +103,59 @@ use tokio_util::sync::CancellationToken;
 
 const API_URL: &str = "https://api.boosty.to";
 const TIMEOUT_SECONDS: u64 = 10;
-pub const DOWNLOAD_CANCELLED_MESSAGE: &str = "Download cancelled by user";
 
 pub fn ensure_not_cancelled(token: &CancellationToken) -> Result<()> {
     if token.is_cancelled() {
-        anyhow::bail!(DOWNLOAD_CANCELLED_MESSAGE);
+        return Err(error::Cancelled.into());
     }
     Ok(())
 }
 
 pub fn is_cancelled_error(err: &anyhow::Error) -> bool {
-    err.chain()
-        .any(|cause| cause.to_string() == DOWNLOAD_CANCELLED_MESSAGE)
+    err.chain().any(|cause| {
+        cause.is::<error::Cancelled>()
+            || cause
+                .downcast_ref::<error::CoreError>()
+                .is_some_and(error::CoreError::is_cancelled)
+    })
+}
+
+/// Errors that end the whole session instead of one file or post: cancellation or a
+/// storage limit.
+pub fn is_stop_error(err: &anyhow::Error) -> bool {
+    is_cancelled_error(err) || storage::storage_limit(err).is_some()
 }
 
+/// API client for the proxy in the saved config; also points media downloads at it and
+/// applies the saved log verbosity.
 pub async fn make_client() -> Result<ApiClient> {
-    let client = Client::builder()
+    let cfg = config::load_config().await?;
+    logger::set_verbosity(cfg.log_verbosity);
+    media_client::configure(&cfg.media_client, &cfg.proxy)?;
+    build_api_client(&cfg.proxy)
+}
+
+pub fn build_api_client(proxy: &ProxyConfig) -> Result<ApiClient> {
+    Ok(ApiClient::new(api_http_client(proxy)?, API_URL))
+}
+
+/// HTTP client with the settings of the API client, also for API requests it has no
+/// method for.
+pub(crate) fn api_http_client(proxy: &ProxyConfig) -> Result<Client> {
+    let mut builder = Client::builder()
         .http1_only()
         .connect_timeout(Duration::from_secs(TIMEOUT_SECONDS))
         .pool_idle_timeout(None)
-        .tcp_keepalive(Some(Duration::from_secs(TIMEOUT_SECONDS * 3)))
-        .build()?;
-
-    Ok(ApiClient::new(client, API_URL))
+        .tcp_keepalive(Some(Duration::from_secs(TIMEOUT_SECONDS * 3)));
+    if let Some(proxy) = proxy.to_proxy()? {
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
 }
 
 pub async fn init_client(client: &ApiClient) -> Result<()> {
-    checks::check_api(client).await?;
     let mut cfg = config::load_config().await?;
+    checks::check_api(client, &cfg.proxy).await?;
     config::sync_auth(client, &mut cfg, None).await?;
     config::save_config(&cfg).await?;
     Ok(())
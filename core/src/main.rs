@@ -1,11 +1,18 @@
 use anyhow::Result;
 use boosty_downloader_core::{
-    ConsoleLogger, handle_menu, init_client, make_client, print_error, set_logger,
+    ConsoleLogger, ask_secrets_passphrase, config_arg, handle_menu, init_client, init_file_log,
+    log_warn, make_client, print_error, set_config_path, set_default_sink,
 };
 
 #[tokio::main]
 async fn main() {
-    set_logger(ConsoleLogger);
+    set_default_sink(ConsoleLogger::new());
+    if let Some(path) = config_arg(std::env::args().skip(1)) {
+        set_config_path(path);
+    }
+    if let Err(e) = init_file_log() {
+        log_warn!("Logs are not written to a file: {e:#}");
+    }
 
     if let Err(e) = run().await {
         print_error(&e);
@@ -14,6 +21,7 @@ async fn main() {
 }
 
 async fn run() -> Result<()> {
+    ask_secrets_passphrase().await;
     let client = make_client().await?;
     init_client(&client).await?;
 
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Certificate, Client};
+use serde::{Deserialize, Serialize};
+
+use crate::proxy::ProxyConfig;
+use crate::{context, headers};
+
+/// Settings of the HTTP client that downloads media files (not the API client).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MediaClientConfig {
+    pub connect_timeout_secs: u64,
+    /// Longest wait for the next piece of a response before the download attempt fails.
+    pub read_timeout_secs: u64,
+    pub user_agent: String,
+    /// Headers sent with every media request, on top of the defaults.
+    pub extra_headers: BTreeMap<String, String>,
+    /// Allow HTTP/2 when the server offers it; HTTP/1.1 only when off.
+    pub http2: bool,
+    /// PEM files with additional root certificates, e.g. of a corporate proxy.
+    pub ca_certificates: Vec<String>,
+}
+
+impl Default for MediaClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            read_timeout_secs: 60,
+            user_agent: headers::DEFAULT_USER_AGENT.to_string(),
+            extra_headers: BTreeMap::new(),
+            http2: true,
+            ca_certificates: Vec::new(),
+        }
+    }
+}
+
+pub(crate) type MediaSettings = (MediaClientConfig, ProxyConfig);
+
+/// Builds a media client from `cfg`. Fails on a bad header, certificate file or proxy.
+pub fn build_media_client(cfg: &MediaClientConfig, proxy: &ProxyConfig) -> Result<Client> {
+    let mut default_headers = headers::default_download_headers();
+    for (name, value) in &cfg.extra_headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("Invalid header name '{name}'"))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("Invalid value of header '{name}'"))?;
+        default_headers.insert(name, value);
+    }
+
+    let mut builder = Client::builder()
+        .user_agent(&cfg.user_agent)
+        .default_headers(default_headers)
+        .connect_timeout(Duration::from_secs(cfg.connect_timeout_secs))
+        .read_timeout(Duration::from_secs(cfg.read_timeout_secs))
+        .tcp_keepalive(Some(Duration::from_secs(cfg.connect_timeout_secs * 3)));
+    if !cfg.http2 {
+        builder = builder.http1_only();
+    }
+    if let Some(proxy) = proxy.to_proxy()? {
+        builder = builder.proxy(proxy);
+    }
+    for path in &cfg.ca_certificates {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Failed to read CA certificate '{path}'"))?;
+        let cert = Certificate::from_pem(&pem)
+            .with_context(|| format!("Invalid CA certificate '{path}'"))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    builder
+        .build()
+        .context("Failed to build the media HTTP client")
+}
+
+/// Makes [`media_client`] return a client built from `cfg` and `proxy` in the current
+/// context; the current one is kept (with its open connections) when the settings did
+/// not change.
+pub fn configure(cfg: &MediaClientConfig, proxy: &ProxyConfig) -> Result<()> {
+    let context = context::current();
+    let mut slot = context.media.lock().unwrap();
+    if slot
+        .as_ref()
+        .is_some_and(|((current, current_proxy), _)| current == cfg && current_proxy == proxy)
+    {
+        return Ok(());
+    }
+    let client = build_media_client(cfg, proxy)?;
+    *slot = Some(((cfg.clone(), proxy.clone()), client));
+    Ok(())
+}
+
+/// The client for media downloads of the current context, built from the default config
+/// until [`configure`] is called.
+pub fn media_client() -> Result<Client> {
+    let context = context::current();
+    let mut slot = context.media.lock().unwrap();
+    if let Some((_, client)) = slot.as_ref() {
+        return Ok(client.clone());
+    }
+    let settings = (MediaClientConfig::default(), ProxyConfig::default());
+    let client = build_media_client(&settings.0, &settings.1)?;
+    *slot = Some((settings, client.clone()));
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_settings_are_reported() {
+        let proxy = ProxyConfig::default();
+        assert!(build_media_client(&MediaClientConfig::default(), &proxy).is_ok());
+
+        let mut cfg = MediaClientConfig::default();
+        cfg.extra_headers.insert("bad header".into(), "x".into());
+        let err = build_media_client(&cfg, &proxy).unwrap_err();
+        assert!(err.to_string().contains("bad header"));
+
+        let cfg = MediaClientConfig {
+            ca_certificates: vec!["/nonexistent/ca.pem".into()],
+            ..MediaClientConfig::default()
+        };
+        assert!(build_media_client(&cfg, &proxy).is_err());
+    }
+}
@@ -1,33 +1,52 @@
 use crate::DownloadOptions;
-use crate::cli;
+use crate::account;
+use crate::archive_move;
+use crate::auth;
+use crate::blog_index;
+use crate::blog_settings::BlogSettings;
+use crate::cli::{self, MenuItem};
 use crate::comment_handler;
 use crate::config;
 use crate::config::AppConfig;
+use crate::diagnostics;
 use crate::downloaded;
+use crate::error::{CoreError, Paywalled};
 use crate::file_handler;
+use crate::file_logger;
 use crate::log_error;
 use crate::log_info;
+use crate::log_warn;
+use crate::logger;
+use crate::media_client;
+use crate::naming::{self, NamingConfig};
 use crate::parser::BoostyUrl;
 use crate::post_handler;
 use crate::progress_reporter;
+use crate::retry;
+use crate::session_report::{self, ReportGuard, SessionReport};
+use crate::session_state::{self, Checkpoint, CommentTarget, SessionState};
+use crate::storage;
+use crate::subscriptions;
+use crate::throttle;
 use crate::url_context;
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result};
 use boosty_api::api_client::ApiClient;
+use boosty_api::model::Post;
 use boosty_api::traits::HasTitle;
 use boosty_api::traits::IsAvailable;
 use std::path::Path;
 use tokio_util::sync::CancellationToken;
 
+const USE_ACTIVE_PROFILE: &str = "(active profile)";
+
 pub async fn handle_menu(client: &ApiClient) -> Result<bool> {
     let selected_menu = cli::read_input_menu();
 
     match selected_menu {
-        0 => {
+        MenuItem::DownloadUrl => {
             let cfg = config::load_config().await?;
 
-            if let Some((input, offset_input)) = cli::read_download_url_and_offset()
-                && let Some(download_options) = cli::read_download_options()
-            {
+            if let Some((input, offset_input)) = cli::read_download_url_and_offset() {
                 let offset_opt = if offset_input.is_empty() {
                     None
                 } else {
@@ -35,22 +54,41 @@ pub async fn handle_menu(client: &ApiClient) -> Result<bool> {
                 };
 
                 let ctx = url_context::build_url_context(&input, offset_opt)?;
+                let blog = match &ctx.url {
+                    BoostyUrl::Blog(blog) | BoostyUrl::Post { blog, .. } => blog,
+                };
+                let Some(download_options) = read_blog_download_options(&cfg, blog).await? else {
+                    return Ok(true);
+                };
+
+                let resume = match &ctx.url {
+                    BoostyUrl::Blog(blog) => {
+                        let download_path = config::get_download_path(&cfg);
+                        match session_state::load(&download_path, blog).await? {
+                            Some(state) => cli::confirm_resume_session(&state),
+                            None => false,
+                        }
+                    }
+                    BoostyUrl::Post { .. } => false,
+                };
 
-                if let Err(e) = process_boosty_url(
+                match process_boosty_url(
                     client,
                     &cfg,
                     &ctx.url,
                     ctx.offset,
                     download_options,
+                    resume,
                     &CancellationToken::new(),
                 )
                 .await
                 {
-                    log_error!("{:#}", e);
+                    Ok(report) => cli::print_session_report(&report),
+                    Err(e) => log_error!("{:#}", e),
                 };
             }
         }
-        1 => {
+        MenuItem::DownloadBatch => {
             let cfg = config::load_config().await?;
 
             if let Some(file_path_str) = cli::read_batch_file_path()
@@ -61,24 +99,44 @@ pub async fn handle_menu(client: &ApiClient) -> Result<bool> {
                 log_error!("Batch process failed: {:#}", e);
             }
         }
-        2 => {
+        MenuItem::DownloadSubscriptions => {
+            let cfg = config::load_config().await?;
+
+            if let Some(download_options) = cli::read_download_options() {
+                match subscriptions::download_all(
+                    client,
+                    &cfg,
+                    download_options,
+                    &CancellationToken::new(),
+                )
+                .await
+                {
+                    Ok(result) => cli::print_subscriptions_result(&result),
+                    Err(e) => log_error!("Subscriptions download failed: {:#}", e),
+                }
+            }
+        }
+        MenuItem::ManageDownloaded => {
             if let Err(e) = handle_downloaded_menu(client).await {
                 log_error!("{:#}", e);
             }
         }
-        3 => {
+        MenuItem::AccessToken => {
             if let Some(entered_token) = cli::read_access_token() {
+                let (entered_token, expires_at) = config::parse_access_token(&entered_token);
                 client.set_bearer_token(&entered_token).await?;
-                config::update_config(|cfg| {
+                let cfg = config::update_config(|cfg| {
                     cfg.access_token = entered_token;
                     cfg.refresh_token = String::new();
                     cfg.device_id = String::new();
+                    cfg.token_expires_at = expires_at;
                 })
                 .await
                 .with_context(|| "Failed to update config")?;
+                auth::set_current_profile(&cfg.active_profile);
             }
         }
-        4 => {
+        MenuItem::RefreshToken => {
             if let Some((entered_token, entered_device_id)) = cli::read_refresh_and_client_id() {
                 client
                     .set_refresh_token_and_device_id(&entered_token, &entered_device_id)
@@ -87,24 +145,32 @@ pub async fn handle_menu(client: &ApiClient) -> Result<bool> {
                 cli::access_token_set(&pair.access_token);
                 cli::refresh_token_set(&pair.refresh_token);
                 cli::client_id_set(&entered_device_id);
-                config::update_config(|cfg| {
+                let cfg = config::update_config(|cfg| {
                     cfg.access_token = pair.access_token;
                     cfg.refresh_token = pair.refresh_token;
                     cfg.device_id = entered_device_id;
+                    cfg.token_expires_at = None;
                 })
                 .await
                 .with_context(|| "Failed to update config")?;
+                auth::set_current_profile(&cfg.active_profile);
             }
         }
-        5 => {
+        MenuItem::ClearTokens => {
             let mut cfg = config::load_config().await?;
             config::clear_auth(client, &mut cfg).await?;
             config::save_config(&cfg)
                 .await
                 .with_context(|| "Failed to clear tokens")?;
+            auth::set_current_profile(&cfg.active_profile);
             cli::tokens_and_client_id_cleared();
         }
-        6 => {
+        MenuItem::AuthProfiles => {
+            if let Err(e) = handle_profiles_menu(client).await {
+                log_error!("{:#}", e);
+            }
+        }
+        MenuItem::PostsLimit => {
             let cfg = config::load_config().await?;
 
             if let Some(limit) = cli::read_posts_limit(cfg.posts_limit) {
@@ -113,16 +179,27 @@ pub async fn handle_menu(client: &ApiClient) -> Result<bool> {
                     .with_context(|| "Failed to update posts limit")?;
             }
         }
-        7 => {
-            let cfg = config::load_config().await?;
-
-            if let Some(new_path_opt) = cli::read_download_path(cfg.download_path.as_deref()) {
-                config::update_config(|cfg| cfg.download_path = new_path_opt)
-                    .await
-                    .with_context(|| "Failed to update download path")?;
+        MenuItem::DownloadPath => {
+            if let Err(e) = handle_download_path().await {
+                log_error!("{:#}", e);
+            }
+        }
+        MenuItem::Naming => {
+            if let Err(e) = handle_naming_menu().await {
+                log_error!("{:#}", e);
+            }
+        }
+        MenuItem::StorageLimits => {
+            if let Err(e) = handle_storage_menu().await {
+                log_error!("{:#}", e);
+            }
+        }
+        MenuItem::SpeedLimits => {
+            if let Err(e) = handle_throttle_menu().await {
+                log_error!("{:#}", e);
             }
         }
-        8 => {
+        MenuItem::ToggleComments => {
             let cfg = config::load_config().await?;
 
             if let Some(enable_comments) = cli::read_comments_status(cfg.comments.enabled) {
@@ -140,30 +217,147 @@ pub async fn handle_menu(client: &ApiClient) -> Result<bool> {
                 cli::comments_toggled(status);
             }
         }
-        9 => cli::show_api_client_headers(&client.headers_as_map()),
-        10 => cli::show_config(&config::load_config().await?),
-        11 => {
+        MenuItem::ToggleAllReplies => {
+            let cfg = config::load_config().await?;
+
+            if let Some(fetch_all) = cli::read_all_replies_status(cfg.comments.fetch_all_replies) {
+                config::update_config(|cfg| {
+                    cfg.comments.fetch_all_replies = fetch_all;
+                })
+                .await
+                .with_context(|| "Failed to update comment replies setting")?;
+
+                let status = if fetch_all { "enabled" } else { "disabled" };
+                cli::all_replies_toggled(status);
+            }
+        }
+        MenuItem::ToggleSessionReports => {
+            let cfg = config::load_config().await?;
+
+            if let Some(save) = cli::read_session_report_status(cfg.save_session_report) {
+                config::update_config(|cfg| {
+                    cfg.save_session_report = save;
+                })
+                .await
+                .with_context(|| "Failed to update session report setting")?;
+
+                let status = if save { "enabled" } else { "disabled" };
+                cli::session_report_toggled(status);
+            }
+        }
+        MenuItem::LogVerbosity => {
+            let cfg = config::load_config().await?;
+
+            if let Some(verbosity) = cli::read_log_verbosity(cfg.log_verbosity) {
+                config::update_config(|cfg| {
+                    cfg.log_verbosity = verbosity;
+                })
+                .await
+                .with_context(|| "Failed to update log verbosity")?;
+
+                logger::set_verbosity(verbosity);
+                cli::log_verbosity_changed(verbosity, file_logger::log_path().ok().as_deref());
+            }
+        }
+        MenuItem::ExportDiagnostics => {
+            let default_dir = config::get_download_path(&config::load_config().await?);
+            if let Some(dir) = cli::read_diagnostics_dir(&default_dir) {
+                match diagnostics::export(Path::new(&dir)).await {
+                    Ok(path) => cli::diagnostics_exported(&path),
+                    Err(e) => log_error!("Failed to export diagnostics: {e:#}"),
+                }
+            }
+        }
+        MenuItem::ShowHeaders => cli::show_api_client_headers(&client.headers_as_map()),
+        MenuItem::CheckAuth => {
+            let cfg = config::load_config().await?;
+            match account::check_auth(client, &cfg).await {
+                Ok(info) => cli::print_account_info(&info),
+                Err(e) => log_error!("Auth check failed: {:#}", e),
+            }
+        }
+        MenuItem::ShowConfig => cli::show_config(&config::load_config().await?),
+        MenuItem::Exit => {
             cli::exit_message();
             return Ok(false);
         }
-        _ => {}
     }
     Ok(true)
 }
 
+/// Downloads a blog or a single post and reports what the session did.
+///
+/// With `resume`, a blog download interrupted earlier continues from its saved state;
+/// otherwise a new session replaces that state.
+///
+/// # Errors
+///
+/// Returns a [`CoreError`] classified by cause; a single post the current profile has
+/// no access to is [`ErrorKind::Paywalled`](crate::ErrorKind::Paywalled).
 pub async fn process_boosty_url(
     client: &ApiClient,
     cfg: &AppConfig,
     url: &BoostyUrl,
     offset_url: Option<BoostyUrl>,
     download_options: DownloadOptions,
+    resume: bool,
     cancel_token: &CancellationToken,
-) -> Result<usize> {
+) -> Result<SessionReport, CoreError> {
+    download_url(
+        client,
+        cfg,
+        url,
+        offset_url,
+        download_options,
+        resume,
+        cancel_token,
+    )
+    .await
+    .map_err(CoreError::from)
+}
+
+/// [`process_boosty_url`] keeping the whole error chain, for runs over several URLs
+/// that decide by the cause whether to go on.
+pub(crate) async fn download_url(
+    client: &ApiClient,
+    cfg: &AppConfig,
+    url: &BoostyUrl,
+    offset_url: Option<BoostyUrl>,
+    download_options: DownloadOptions,
+    resume: bool,
+    cancel_token: &CancellationToken,
+) -> Result<SessionReport> {
     crate::ensure_not_cancelled(cancel_token)?;
-    let offset: Option<String> = match offset_url {
-        Some(BoostyUrl::Post { blog, post_id }) => {
+    throttle::configure(&cfg.throttle);
+    media_client::configure(&cfg.media_client, &cfg.proxy)?;
+    retry::configure(&cfg.retry);
+    let download_path = &config::get_download_path(cfg);
+
+    let blog = match &url {
+        BoostyUrl::Blog(blog) => blog,
+        BoostyUrl::Post { blog, .. } => blog,
+    };
+    auth::use_profile_for_blog(client, cfg, blog).await?;
+    let report = ReportGuard::new(blog);
+
+    let settings = blog_index::load_settings(download_path, blog).await?;
+    let cfg = &settings.apply_to(cfg);
+    let download_options = settings.download_options().unwrap_or(download_options);
+
+    let resumed = match &url {
+        BoostyUrl::Blog(blog) if resume => session_state::load(download_path, blog).await?,
+        _ => None,
+    };
+
+    let offset: Option<String> = match (offset_url, &resumed) {
+        (_, Some(state)) => state.feed_offset.clone(),
+        (Some(BoostyUrl::Post { blog, post_id }), None) => {
             crate::ensure_not_cancelled(cancel_token)?;
-            let offset_post = client.get_post(&blog, &post_id).await?;
+            let offset_post =
+                auth::call(client, &format!("/v1/blog/{blog}/post/{post_id}"), || {
+                    client.get_post(&blog, &post_id)
+                })
+                .await?;
             Some(format!("{}:{}", offset_post.sort_order, offset_post.int_id))
         }
         _ => None,
@@ -172,110 +366,212 @@ pub async fn process_boosty_url(
     let result = match &url {
         BoostyUrl::Blog(blog) => {
             crate::ensure_not_cancelled(cancel_token)?;
-            let multiple = client
-                .get_posts(blog, cfg.posts_limit, None, offset)
-                .await
-                .map_err(|e| anyhow!("Failed to fetch posts for blog '{blog}', {}", e))?;
+            let multiple = match &resumed {
+                Some(state) => {
+                    cli::session_resumed(state);
+                    fetch_session_posts(client, blog, state, cancel_token).await?
+                }
+                None => {
+                    let mut posts = auth::call(client, &format!("/v1/blog/{blog}/post/"), || {
+                        client.get_posts(blog, cfg.posts_limit, None, offset.clone())
+                    })
+                    .await
+                    .with_context(|| format!("Failed to fetch posts for blog '{blog}'"))?;
+                    posts.retain(|post| settings.filter.matches(post));
+                    posts
+                }
+            };
             post_handler::PostsResult::Multiple(multiple)
         }
         BoostyUrl::Post { blog, post_id } => {
             crate::ensure_not_cancelled(cancel_token)?;
-            let single = client.get_post(blog, post_id).await.map_err(|e| {
-                anyhow!("Failed to fetch post '{post_id}' for blog '{blog}', {}", e)
-            })?;
+            let single = auth::call(client, &format!("/v1/blog/{blog}/post/{post_id}"), || {
+                client.get_post(blog, post_id)
+            })
+            .await
+            .with_context(|| format!("Failed to fetch post '{post_id}' for blog '{blog}'"))?;
+            if single.not_available() {
+                return Err(Paywalled {
+                    blog: blog.clone(),
+                    post_id: post_id.clone(),
+                }
+                .into());
+            }
             post_handler::PostsResult::Single(Box::from(single))
         }
     };
 
     // Collect metadata for comments before posts are consumed by process_posts.
-    let comment_targets: Vec<(String, String, String)> = if cfg.comments.enabled {
+    let mut comment_targets: Vec<CommentTarget> = if cfg.comments.enabled {
         match &result {
             post_handler::PostsResult::Single(post) => {
                 if post.not_available() {
                     Vec::new()
                 } else {
-                    vec![(
-                        post.user.blog_url.clone(),
-                        post.id.clone(),
-                        post.safe_title(),
-                    )]
+                    vec![comment_target(post)]
                 }
             }
             post_handler::PostsResult::Multiple(posts) => posts
                 .iter()
                 .filter(|p| !p.not_available())
-                .map(|p| (p.user.blog_url.clone(), p.id.clone(), p.safe_title()))
+                .map(comment_target)
                 .collect(),
         }
     } else {
         Vec::new()
     };
 
-    let download_path = &config::get_download_path(cfg);
-    let post_files = post_handler::count_downloadable_files(&result, &download_options);
-    let _progress = progress_reporter::SessionGuard::new(post_files);
+    let mut checkpoint = match (&url, resumed) {
+        (_, Some(state)) => {
+            comment_targets.retain(|t| state.has_pending_comments(&t.post_id));
+            Some(Checkpoint::start(download_path, state).await?)
+        }
+        (BoostyUrl::Blog(blog), None) => {
+            let post_ids = match &result {
+                post_handler::PostsResult::Multiple(posts) => {
+                    posts.iter().map(|p| p.id.clone()).collect()
+                }
+                post_handler::PostsResult::Single(post) => vec![post.id.clone()],
+            };
+            let state = SessionState::new(blog, offset, post_ids, comment_targets.clone());
+            Some(Checkpoint::start(download_path, state).await?)
+        }
+        (BoostyUrl::Post { .. }, None) => None,
+    };
 
-    let mut pages = post_handler::process_posts(
-        result,
+    let posts: Vec<&Post> = match &result {
+        post_handler::PostsResult::Multiple(posts) => posts.iter().collect(),
+        post_handler::PostsResult::Single(post) => vec![post.as_ref()],
+    };
+    let _budget = storage::start_session(
+        cfg,
         download_path,
-        download_options.clone(),
+        blog,
+        &posts,
+        &download_options,
         cancel_token,
     )
-    .await
-    .with_context(|| {
-        format!(
-            "Error while processing post content: {}",
-            match &url {
-                BoostyUrl::Blog(blog) => blog,
-                BoostyUrl::Post { blog, .. } => blog,
-            }
+    .await?;
+
+    let post_files = post_handler::count_downloadable_files(&result, &download_options);
+    let _progress = progress_reporter::SessionGuard::new(post_files);
+
+    // Failures are kept below even when the session stops early.
+    let outcome = async {
+        let mut pages = post_handler::process_posts(
+            client,
+            result,
+            download_path,
+            download_options.clone(),
+            &settings.output_formats(),
+            &cfg.naming,
+            checkpoint.as_mut(),
+            cancel_token,
         )
-    })?;
+        .await
+        .with_context(|| format!("Error while processing post content: {blog}"))?;
 
-    if !comment_targets.is_empty() {
-        let mut comments_results = Vec::new();
+        if !comment_targets.is_empty() {
+            let mut comments_results = Vec::new();
 
-        for (blog_url, post_id, safe_post_title) in comment_targets {
-            crate::ensure_not_cancelled(cancel_token)?;
-            match client
-                .get_all_comments(
-                    &blog_url,
-                    &post_id,
-                    cfg.comments.limit,
-                    cfg.comments.reply_limit,
-                    cfg.comments.order.as_deref(),
+            for target in comment_targets {
+                crate::pause::wait_if_paused(cancel_token).await?;
+                match comment_handler::fetch_comments(
+                    client,
+                    &cfg.comments,
+                    &target.blog,
+                    &target.post_id,
+                    cancel_token,
                 )
                 .await
-            {
-                Ok(comments) => {
-                    comments_results.push(comment_handler::CommentsResult {
-                        comments,
-                        post_id,
-                        safe_post_title,
-                    });
+                {
+                    Ok((comments, truncated)) => {
+                        comments_results.push(comment_handler::CommentsResult {
+                            comments,
+                            truncated,
+                            post_id: target.post_id,
+                            safe_post_title: target.title,
+                        });
+                    }
+                    Err(e) => {
+                        if crate::is_stop_error(&e) {
+                            return Err(e);
+                        }
+                        log_error!("{e:#}");
+                    }
                 }
-                Err(e) => {
-                    log_error!("Failed to fetch comments for post '{post_id}': {e:#}");
+            }
+
+            if let Err(e) = comment_handler::process_comments(
+                comments_results,
+                &mut pages,
+                download_options,
+                &cfg.naming,
+                checkpoint.as_mut(),
+                cancel_token,
+            )
+            .await
+            {
+                if crate::is_stop_error(&e) {
+                    return Err(e);
                 }
+                log_error!("Error while processing comments: {e:#}");
             }
         }
 
-        if let Err(e) = comment_handler::process_comments(
-            comments_results,
-            &mut pages,
-            download_options,
-            cancel_token,
-        )
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.finish().await?;
+        }
+        Ok(())
+    }
+    .await;
+
+    let finished_posts = report.finished_posts();
+    let report = report.finish();
+    if let Err(e) =
+        blog_index::record_failures(download_path, blog, &finished_posts, report.failed_items())
+            .await
+    {
+        log_warn!("{e:#}");
+    }
+    outcome?;
+    if cfg.save_session_report {
+        match session_report::save(download_path, &report).await {
+            Ok(path) => log_info!("Session report saved to '{}'", path.display()),
+            Err(e) => log_warn!("{e:#}"),
+        }
+    }
+    Ok(report)
+}
+
+fn comment_target(post: &Post) -> CommentTarget {
+    CommentTarget {
+        blog: post.user.blog_url.clone(),
+        post_id: post.id.clone(),
+        title: post.safe_title(),
+    }
+}
+
+/// Refetches the posts an interrupted session still has to go through, for fresh signed URLs.
+async fn fetch_session_posts(
+    client: &ApiClient,
+    blog: &str,
+    state: &SessionState,
+    cancel_token: &CancellationToken,
+) -> Result<Vec<Post>> {
+    let mut posts = Vec::new();
+    for post_id in state.posts_to_fetch() {
+        crate::pause::wait_if_paused(cancel_token).await?;
+        match auth::call(client, &format!("/v1/blog/{blog}/post/{post_id}"), || {
+            client.get_post(blog, &post_id)
+        })
         .await
         {
-            if crate::is_cancelled_error(&e) {
-                return Err(e);
-            }
-            log_error!("Error while processing comments: {e:#}");
+            Ok(post) => posts.push(post),
+            Err(e) => log_error!("Failed to fetch post '{post_id}' for blog '{blog}': {e:#}"),
         }
     }
-
-    Ok(pages.len())
+    Ok(posts)
 }
 
 async fn process_batch_file(
@@ -294,17 +590,24 @@ async fn process_batch_file(
 
         match url_context::build_url_context(&link, None) {
             Ok(ctx) => {
-                if let Err(e) = process_boosty_url(
+                match download_url(
                     client,
                     cfg,
                     &ctx.url,
                     ctx.offset,
                     download_options.clone(),
+                    false,
                     &CancellationToken::new(),
                 )
                 .await
                 {
-                    log_error!("Error processing link '{}': {e}", link);
+                    Ok(report) => cli::print_session_report(&report),
+                    Err(e) => {
+                        log_error!("Error processing link '{}': {e:#}", link);
+                        if storage::ends_run(&e) {
+                            break;
+                        }
+                    }
                 }
             }
             Err(e) => {
@@ -317,6 +620,274 @@ async fn process_batch_file(
     Ok(())
 }
 
+async fn handle_profiles_menu(client: &ApiClient) -> Result<()> {
+    let mut cfg = config::load_config().await?;
+
+    match cli::read_profiles_action(&cfg.active_profile) {
+        0 => {
+            if let Some(name) = cli::select_profile("Switch to profile", &cfg.profile_names()) {
+                cfg.switch_profile(&name);
+                config::sync_auth(client, &mut cfg, None).await?;
+                config::save_config(&cfg)
+                    .await
+                    .with_context(|| "Failed to update config")?;
+                cli::profile_switched(&name);
+            }
+        }
+        1 => {
+            if let Some(name) = cli::read_profile_name() {
+                if cfg.has_profile(&name) {
+                    anyhow::bail!("Profile '{name}' already exists");
+                }
+                cfg.switch_profile(&name);
+                config::sync_auth(client, &mut cfg, None).await?;
+                config::save_config(&cfg)
+                    .await
+                    .with_context(|| "Failed to update config")?;
+                cli::profile_created(&name);
+            }
+        }
+        2 => {
+            let inactive: Vec<String> = cfg.profiles.keys().cloned().collect();
+            if inactive.is_empty() {
+                cli::info("No inactive profiles to delete.");
+            } else if let Some(name) = cli::select_profile("Delete profile", &inactive)
+                && cli::confirm_delete(&format!("profile '{name}'"))
+            {
+                cfg.remove_profile(&name)?;
+                config::save_config(&cfg)
+                    .await
+                    .with_context(|| "Failed to update config")?;
+            }
+        }
+        3 => {
+            if let Some(blog) = cli::read_blog_name() {
+                let mut options = cfg.profile_names();
+                options.push(USE_ACTIVE_PROFILE.to_string());
+                if let Some(profile) = cli::select_profile("Profile for this blog", &options) {
+                    let profile = (profile != USE_ACTIVE_PROFILE).then_some(profile);
+                    match &profile {
+                        Some(profile) => cfg.blog_profiles.insert(blog.clone(), profile.clone()),
+                        None => cfg.blog_profiles.remove(&blog),
+                    };
+                    config::save_config(&cfg)
+                        .await
+                        .with_context(|| "Failed to update config")?;
+                    cli::blog_profile_assigned(&blog, profile.as_deref());
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Content types for a blog: its own setting if it has one, otherwise asked.
+async fn read_blog_download_options(
+    cfg: &AppConfig,
+    blog: &str,
+) -> Result<Option<DownloadOptions>> {
+    let download_path = config::get_download_path(cfg);
+    match blog_index::load_settings(&download_path, blog)
+        .await?
+        .download_options()
+    {
+        Some(options) => {
+            cli::using_blog_download_options(blog, &options);
+            Ok(Some(options))
+        }
+        None => Ok(cli::read_download_options()),
+    }
+}
+
+async fn handle_blog_settings(cfg: &AppConfig, blog: &str) -> Result<()> {
+    let download_path = config::get_download_path(cfg);
+    let mut settings = blog_index::load_settings(&download_path, blog).await?;
+
+    loop {
+        cli::print_blog_settings(blog, &settings);
+        let changed = match cli::read_blog_settings_action() {
+            0 => cli::read_blog_download_options(settings.download_options.is_some())
+                .map(|options| settings.set_download_options(options.as_ref())),
+            1 => cli::read_override_status("Download comments", settings.comments_enabled)
+                .map(|enabled| settings.comments_enabled = enabled),
+            2 => cli::read_optional_limit("Posts limit", settings.posts_limit)
+                .map(|limit| settings.posts_limit = limit),
+            3 => cli::read_optional_limit("Comments limit", settings.comments_limit)
+                .map(|limit| settings.comments_limit = limit),
+            4 => cli::read_optional_limit("Reply limit", settings.reply_limit)
+                .map(|limit| settings.reply_limit = limit),
+            5 => cli::read_word_list(
+                "Only posts with titles containing",
+                &settings.filter.title_includes,
+            )
+            .map(|words| settings.filter.title_includes = words),
+            6 => cli::read_word_list(
+                "Skip posts with titles containing",
+                &settings.filter.title_excludes,
+            )
+            .map(|words| settings.filter.title_excludes = words),
+            7 => cli::read_word_list("Only posts tagged", &settings.filter.tags)
+                .map(|tags| settings.filter.tags = tags),
+            8 => cli::read_skip_paid(settings.filter.skip_paid)
+                .map(|skip| settings.filter.skip_paid = skip),
+            9 => cli::read_output_formats(&settings.output_formats())
+                .map(|formats| settings.output_formats = formats),
+            10 => cli::read_optional_limit("Storage quota, MB", settings.quota_mb)
+                .map(|quota| settings.quota_mb = quota),
+            11 => {
+                settings = BlogSettings::default();
+                Some(())
+            }
+            _ => return Ok(()),
+        };
+        if changed.is_some() {
+            blog_index::save_settings(&download_path, blog, settings.clone()).await?;
+        }
+    }
+}
+
+/// Changes the download path, offering to move the downloaded blogs along with it.
+async fn handle_download_path() -> Result<()> {
+    if let Some(journal) = archive_move::pending_move().await? {
+        if cli::confirm_resume_move(&journal) {
+            let cfg = config::load_config().await?;
+            let result =
+                archive_move::move_archive(&cfg, &journal.to, &CancellationToken::new()).await?;
+            cli::print_move_result(&result);
+            return Ok(());
+        }
+        archive_move::discard_pending_move().await?;
+    }
+
+    let cfg = config::load_config().await?;
+    let Some(new_path_opt) = cli::read_download_path(cfg.download_path.as_deref()) else {
+        return Ok(());
+    };
+    let current = config::get_download_path(&cfg);
+    if let Some(new_path) = &new_path_opt
+        && Path::new(new_path) != current
+        && !blog_index::list_blog_names(&current).await?.is_empty()
+        && cli::confirm_move_archive(&current, new_path)
+    {
+        let result =
+            archive_move::move_archive(&cfg, Path::new(new_path), &CancellationToken::new())
+                .await?;
+        cli::print_move_result(&result);
+        return Ok(());
+    }
+
+    config::update_config(|cfg| cfg.download_path = new_path_opt)
+        .await
+        .with_context(|| "Failed to update download path")?;
+    Ok(())
+}
+
+async fn handle_naming_menu() -> Result<()> {
+    loop {
+        let cfg = config::load_config().await?;
+        let mut naming = cfg.naming.clone();
+        cli::print_naming(&naming);
+        let changed =
+            match cli::read_naming_action() {
+                0 => cli::read_template("Post folder", &naming.post_folder)
+                    .map(|t| naming.post_folder = t),
+                1 => cli::read_template("Image file", &naming.image_file)
+                    .map(|t| naming.image_file = t),
+                2 => cli::read_template("Video file", &naming.video_file)
+                    .map(|t| naming.video_file = t),
+                3 => cli::read_template("Audio and other files", &naming.media_file)
+                    .map(|t| naming.media_file = t),
+                4 => cli::read_collision_policy(naming.collision).map(|c| naming.collision = c),
+                5 => {
+                    naming = NamingConfig::default();
+                    Some(())
+                }
+                6 => {
+                    let planned = naming::rename_downloaded(&cfg, true).await?;
+                    cli::print_rename_report(&planned, true);
+                    if !planned.moves.is_empty() && cli::confirm_rename(planned.moves.len()) {
+                        let report = naming::rename_downloaded(&cfg, false).await?;
+                        cli::print_rename_report(&report, false);
+                    }
+                    None
+                }
+                _ => return Ok(()),
+            };
+        if changed.is_some() {
+            if let Err(e) = naming.validate() {
+                log_error!("{:#}", e);
+                continue;
+            }
+            config::update_config(|cfg| cfg.naming = naming)
+                .await
+                .with_context(|| "Failed to update file naming")?;
+        }
+    }
+}
+
+async fn handle_storage_menu() -> Result<()> {
+    loop {
+        let mut storage = config::load_config().await?.storage;
+        cli::print_storage(&storage);
+        let changed = match cli::read_storage_action() {
+            0 => cli::read_preflight_check(storage.preflight_check)
+                .map(|check| storage.preflight_check = check),
+            1 => cli::read_reserve_mb(storage.reserve_mb).map(|mb| storage.reserve_mb = mb),
+            2 => cli::read_cap("Quota of each blog, MB", storage.blog_quota_mb)
+                .map(|quota| storage.blog_quota_mb = quota),
+            3 => cli::read_cap(
+                "Quota of the whole download folder, MB",
+                storage.total_quota_mb,
+            )
+            .map(|quota| storage.total_quota_mb = quota),
+            _ => return Ok(()),
+        };
+        if changed.is_some() {
+            config::update_config(|cfg| cfg.storage = storage)
+                .await
+                .with_context(|| "Failed to update storage limits")?;
+        }
+    }
+}
+
+async fn handle_throttle_menu() -> Result<()> {
+    loop {
+        let cfg = config::load_config().await?;
+        let (mut throttle, mut retry) = (cfg.throttle, cfg.retry);
+        cli::print_throttle(&throttle);
+        cli::print_retry(&retry);
+        let changed = match cli::read_throttle_action() {
+            0 => cli::read_cap(
+                "Download speed cap, KB/s",
+                throttle.download_bytes_per_sec.map(|b| b / 1024),
+            )
+            .map(|kb| throttle.download_bytes_per_sec = kb.map(|kb| kb.saturating_mul(1024))),
+            1 => cli::read_cap(
+                "API requests per minute",
+                throttle.api_requests_per_minute.map(u64::from),
+            )
+            .map(|rpm| {
+                throttle.api_requests_per_minute =
+                    rpm.map(|rpm| u32::try_from(rpm).unwrap_or(u32::MAX))
+            }),
+            2 => cli::read_jitter_ms(throttle.api_jitter_ms).map(|ms| throttle.api_jitter_ms = ms),
+            3 => cli::read_retry_attempts(retry.attempts()).map(|n| retry.max_attempts = n),
+            4 => cli::read_max_retry_delay(retry.max_delay_secs)
+                .map(|secs| retry.max_delay_secs = secs),
+            _ => return Ok(()),
+        };
+        if changed.is_some() {
+            config::update_config(|cfg| {
+                cfg.throttle = throttle;
+                cfg.retry = retry;
+            })
+            .await
+            .with_context(|| "Failed to update speed limits")?;
+        }
+    }
+}
+
 async fn handle_downloaded_menu(client: &ApiClient) -> Result<()> {
     let cfg = config::load_config().await?;
     let download_path = config::get_download_path(&cfg);
@@ -371,7 +942,7 @@ async fn handle_downloaded_blog(client: &ApiClient, cfg: &AppConfig, blog: &str)
                     cli::info("No new accessible posts. Check the blog first.");
                     continue;
                 }
-                if let Some(options) = cli::read_download_options() {
+                if let Some(options) = read_blog_download_options(cfg, blog).await? {
                     match downloaded::download_posts(
                         client,
                         cfg,
@@ -407,6 +978,31 @@ async fn handle_downloaded_blog(client: &ApiClient, cfg: &AppConfig, blog: &str)
                 download_selected_post(client, cfg, blog, snapshot.as_ref(), true).await?;
             }
             5 => {
+                let Some(options) = read_blog_download_options(cfg, blog).await? else {
+                    continue;
+                };
+                match downloaded::retry_failures(
+                    client,
+                    cfg,
+                    blog,
+                    options,
+                    &CancellationToken::new(),
+                )
+                .await
+                {
+                    Ok(result) => {
+                        cli::print_download_posts_result(&result);
+                        if let Ok(updated) =
+                            downloaded::refresh_blog(client, cfg, blog, &CancellationToken::new())
+                                .await
+                        {
+                            snapshot = Some(updated);
+                        }
+                    }
+                    Err(e) => log_error!("{:#}", e),
+                }
+            }
+            6 => {
                 let Some(current) = snapshot.as_ref() else {
                     cli::info("Nothing to delete.");
                     continue;
@@ -422,13 +1018,14 @@ async fn handle_downloaded_blog(client: &ApiClient, cfg: &AppConfig, blog: &str)
                     cli::info("Post deleted.");
                 }
             }
-            6 => {
+            7 => {
                 if cli::confirm_delete(&format!("blog '{blog}'")) {
                     downloaded::delete_blog(cfg, blog).await?;
                     cli::info("Blog deleted.");
                     return Ok(());
                 }
             }
+            8 => handle_blog_settings(cfg, blog).await?,
             _ => return Ok(()),
         }
     }
@@ -469,7 +1066,7 @@ async fn download_selected_post(
         cli::info("Post is up to date. Use redownload to replace files.");
         return Ok(());
     }
-    let Some(options) = cli::read_download_options() else {
+    let Some(options) = read_blog_download_options(cfg, blog).await? else {
         return Ok(());
     };
     match downloaded::download_posts(
@@ -0,0 +1,675 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Context, Result};
+use boosty_api::model::Post;
+use boosty_api::traits::HasTitle;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::blog_index::{self, PostRecord};
+use crate::config::{AppConfig, get_download_path};
+use crate::file_handler::sanitize_name;
+use crate::post_page::PostPage;
+
+const MAX_TITLE_CHARS: usize = 80;
+
+pub const DEFAULT_POST_FOLDER: &str = "{date} {title} [{id}]";
+pub const DEFAULT_IMAGE_FILE: &str = "{id}";
+pub const DEFAULT_VIDEO_FILE: &str = "{filename}({id})";
+pub const DEFAULT_MEDIA_FILE: &str = "{id}_{filename}";
+
+const POST_PLACEHOLDERS: &[&str] = &[
+    "blog", "author", "date", "year", "month", "day", "time", "title", "id", "tag",
+];
+const MEDIA_PLACEHOLDERS: &[&str] = &["post_id", "index", "filename"];
+
+/// What happens when a template gives the same name to two different posts or files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    /// Add ` (2)`, ` (3)`, ... to the later name.
+    #[default]
+    Suffix,
+    /// Keep the first one and skip the later post or file.
+    Skip,
+}
+
+/// Templates for folder and file names.
+///
+/// Placeholders: `{blog}`, `{author}`, `{date}` (`2024.01.31`), `{year}`, `{month}`, `{day}`,
+/// `{time}` (`18-05`), `{title}`, `{id}`, `{tag}` (first tag). File templates also know
+/// `{post_id}`, `{index}` (position within the post) and `{filename}` (original name);
+/// `{id}` there is the id of the file. Post folders may use `/` for subfolders of the blog folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingConfig {
+    pub post_folder: String,
+    pub image_file: String,
+    pub video_file: String,
+    /// Audio and attached files.
+    pub media_file: String,
+    pub collision: CollisionPolicy,
+}
+
+impl Default for NamingConfig {
+    fn default() -> Self {
+        Self {
+            post_folder: DEFAULT_POST_FOLDER.to_string(),
+            image_file: DEFAULT_IMAGE_FILE.to_string(),
+            video_file: DEFAULT_VIDEO_FILE.to_string(),
+            media_file: DEFAULT_MEDIA_FILE.to_string(),
+            collision: CollisionPolicy::default(),
+        }
+    }
+}
+
+impl NamingConfig {
+    pub fn validate(&self) -> Result<()> {
+        check_template("post folder", &self.post_folder, POST_PLACEHOLDERS, &[])?;
+        for (label, template) in [
+            ("image file", &self.image_file),
+            ("video file", &self.video_file),
+            ("media file", &self.media_file),
+        ] {
+            check_template(label, template, POST_PLACEHOLDERS, MEDIA_PLACEHOLDERS)?;
+            if template.contains('/') || template.contains('\\') {
+                anyhow::bail!("The {label} template can't contain folders");
+            }
+        }
+        Ok(())
+    }
+
+    /// Post folder relative to the blog folder, `/`-separated.
+    pub fn post_folder_name(&self, post: &PostFields) -> String {
+        let rendered = render(&self.post_folder, |name| post.value(name));
+        let segments: Vec<String> = rendered
+            .split(['/', '\\'])
+            .map(str::trim)
+            .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+            .map(sanitize_name)
+            .collect();
+        if segments.is_empty() {
+            sanitize_name(&post.id)
+        } else {
+            segments.join("/")
+        }
+    }
+}
+
+fn check_template(label: &str, template: &str, known: &[&str], extra: &[&str]) -> Result<()> {
+    if template.trim().is_empty() {
+        anyhow::bail!("The {label} template is empty");
+    }
+    let mut unknown = Vec::new();
+    render(template, |name| {
+        if !known.contains(&name) && !extra.contains(&name) {
+            unknown.push(name.to_string());
+        }
+        Some(String::new())
+    });
+    if !unknown.is_empty() {
+        anyhow::bail!(
+            "Unknown placeholder in the {label} template: {{{}}}",
+            unknown.join("}, {")
+        );
+    }
+    Ok(())
+}
+
+/// Replaces `{name}` with `value(name)`; unknown names are left as they are.
+fn render(template: &str, mut value: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let token = &rest[start..start + len + 1];
+        match value(&token[1..token.len() - 1]) {
+            Some(v) => out.push_str(&v),
+            None => out.push_str(token),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Post data the templates are filled from.
+#[derive(Debug, Clone, Default)]
+pub struct PostFields {
+    pub blog: String,
+    pub author: String,
+    pub title: String,
+    pub id: String,
+    pub created_at: i64,
+    pub tag: String,
+}
+
+impl PostFields {
+    pub fn of_post(post: &Post) -> Self {
+        Self {
+            blog: post.user.blog_url.clone(),
+            author: post.user.name.clone(),
+            title: post.safe_title(),
+            id: post.id.clone(),
+            created_at: post.created_at,
+            tag: post
+                .tags
+                .first()
+                .map(|t| t.title.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn of_page(page: &PostPage) -> Self {
+        Self {
+            blog: page.blog.clone(),
+            author: page.author.clone(),
+            title: page.title.clone(),
+            id: page.post_id.clone(),
+            created_at: page.created_at,
+            tag: page.tags.first().cloned().unwrap_or_default(),
+        }
+    }
+
+    pub fn of_record(blog: &str, post_id: &str, record: &PostRecord) -> Self {
+        Self {
+            blog: blog.to_string(),
+            author: record.author.clone(),
+            title: record.title.clone(),
+            id: post_id.to_string(),
+            created_at: record.created_at,
+            tag: record.tags.first().cloned().unwrap_or_default(),
+        }
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        let date = || {
+            DateTime::<Utc>::from_timestamp(self.created_at, 0)
+                .or_else(|| DateTime::from_timestamp(0, 0))
+                .expect("unix epoch is a valid timestamp")
+        };
+        Some(match name {
+            "blog" => field(&self.blog),
+            "author" => field(&self.author),
+            "date" => date().format("%Y.%m.%d").to_string(),
+            "year" => date().format("%Y").to_string(),
+            "month" => date().format("%m").to_string(),
+            "day" => date().format("%d").to_string(),
+            "time" => date().format("%H-%M").to_string(),
+            "title" => title_field(&self.title),
+            "id" => field(&self.id),
+            "tag" => field(&self.tag),
+            _ => return None,
+        })
+    }
+}
+
+fn field(value: &str) -> String {
+    if value.is_empty() {
+        String::new()
+    } else {
+        sanitize_name(value)
+    }
+}
+
+fn title_field(title: &str) -> String {
+    let mut safe_title = sanitize_name(title);
+    if safe_title.chars().count() > MAX_TITLE_CHARS {
+        safe_title = safe_title.chars().take(MAX_TITLE_CHARS).collect();
+        while safe_title.ends_with('.') || safe_title.ends_with(' ') {
+            safe_title.pop();
+        }
+        if safe_title.is_empty() {
+            safe_title = "_".to_string();
+        }
+    }
+    safe_title
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Video,
+    /// Audio and attached files.
+    File,
+}
+
+/// Hands out file names for the media of one post (or of its comments), keeping
+/// `{index}` running and resolving names that collide between different items.
+pub struct MediaNamer<'a> {
+    naming: &'a NamingConfig,
+    post: PostFields,
+    index: AtomicUsize,
+    /// Lowercased name -> id of the item it was given to.
+    used: Mutex<HashMap<String, String>>,
+}
+
+impl<'a> MediaNamer<'a> {
+    pub fn new(naming: &'a NamingConfig, post: PostFields) -> Self {
+        Self {
+            naming,
+            post,
+            index: AtomicUsize::new(0),
+            used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn post_title(&self) -> &str {
+        &self.post.title
+    }
+
+    pub fn post_id(&self) -> &str {
+        &self.post.id
+    }
+
+    /// Name for the next media item; `None` when it collides and the policy is to skip.
+    /// The same item seen twice gets the same name.
+    pub fn file_name(
+        &self,
+        kind: MediaKind,
+        id: &str,
+        original: &str,
+        extension: Option<&str>,
+    ) -> Option<String> {
+        let index = self.index.fetch_add(1, Ordering::Relaxed) + 1;
+        let template = match kind {
+            MediaKind::Image => &self.naming.image_file,
+            MediaKind::Video => &self.naming.video_file,
+            MediaKind::File => &self.naming.media_file,
+        };
+        let base = render(template, |name| match name {
+            "id" => Some(id.to_string()),
+            "post_id" => Some(self.post.id.clone()),
+            "index" => Some(index.to_string()),
+            "filename" => Some(original.to_string()),
+            other => self.post.value(other),
+        });
+        let name = with_extension(sanitize_name(&base), extension);
+
+        let mut used = self.used.lock().unwrap();
+        match used.get(&name.to_lowercase()) {
+            None => {
+                used.insert(name.to_lowercase(), id.to_string());
+                Some(name)
+            }
+            Some(owner) if owner == id => Some(name),
+            Some(_) if self.naming.collision == CollisionPolicy::Skip => None,
+            Some(_) => (2..)
+                .map(|n| with_suffix(&name, n))
+                .find(|candidate| match used.get(&candidate.to_lowercase()) {
+                    None => true,
+                    Some(owner) => owner == id,
+                })
+                .inspect(|candidate| {
+                    used.insert(candidate.to_lowercase(), id.to_string());
+                }),
+        }
+    }
+}
+
+pub fn with_extension(mut name: String, extension: Option<&str>) -> String {
+    if let Some(ext) = extension {
+        let ext = ext.trim_start_matches('.');
+        if !ext.is_empty() {
+            let suffix = format!(".{ext}");
+            if !name.to_lowercase().ends_with(&suffix.to_lowercase()) {
+                name.push_str(&suffix);
+            }
+        }
+    }
+    name
+}
+
+/// `name (n)`, keeping the extension of files at the end.
+fn with_suffix(name: &str, n: usize) -> String {
+    match name.rfind('.').filter(|&dot| dot > 0) {
+        Some(dot) => format!("{} ({n}){}", &name[..dot], &name[dot..]),
+        None => format!("{name} ({n})"),
+    }
+}
+
+/// Picks a free folder for a new post; `None` when the template's folder belongs to
+/// another post and the policy is to skip.
+///
+/// A folder inside another post's folder, or holding one, counts as taken too, since the
+/// two posts' files would mix. A folder inside a taken one gets the suffix at the level of
+/// that one, as a suffix at the end would leave it inside.
+pub fn resolve_folder_collision(
+    wanted: &str,
+    taken: &HashSet<String>,
+    policy: CollisionPolicy,
+) -> Option<String> {
+    if !overlaps(wanted, taken) {
+        return Some(wanted.to_string());
+    }
+    match policy {
+        CollisionPolicy::Skip => None,
+        CollisionPolicy::Suffix => {
+            let (head, tail) = wanted
+                .match_indices('/')
+                .map(|(i, _)| wanted.split_at(i))
+                .find(|(head, _)| taken.contains(*head))
+                .unwrap_or((wanted, ""));
+            (2..)
+                .map(|n| format!("{head} ({n}){tail}"))
+                .find(|candidate| !overlaps(candidate, taken))
+        }
+    }
+}
+
+/// Whether `folder` is taken, inside a taken folder or holds one.
+fn overlaps(folder: &str, taken: &HashSet<String>) -> bool {
+    let inside = |path: &str, dir: &str| {
+        path.strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+    };
+    taken
+        .iter()
+        .any(|other| other == folder || inside(folder, other) || inside(other, folder))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderMove {
+    pub blog: String,
+    pub post_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenameReport {
+    /// Moves made (or, for a dry run, planned).
+    pub moves: Vec<FolderMove>,
+    /// Posts left as they are because the new name is taken and the policy is to skip.
+    pub skipped: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Renames the folders of downloaded posts to the post folder template and updates
+/// every blog index. With `dry_run` only reports what would be moved.
+pub async fn rename_downloaded(cfg: &AppConfig, dry_run: bool) -> Result<RenameReport> {
+    cfg.naming.validate()?;
+    rename_in(&get_download_path(cfg), &cfg.naming, dry_run).await
+}
+
+async fn rename_in(
+    download_path: &Path,
+    naming: &NamingConfig,
+    dry_run: bool,
+) -> Result<RenameReport> {
+    let mut report = RenameReport::default();
+
+    for blog in blog_index::list_blog_names(download_path).await? {
+        let mut index = blog_index::load(download_path, &blog).await?;
+        let blog_dir = download_path.join(&blog);
+        let mut taken: HashSet<String> = index.posts.values().map(|r| r.folder.clone()).collect();
+
+        let mut ids: Vec<String> = index.posts.keys().cloned().collect();
+        ids.sort_by_key(|id| index.posts[id].created_at);
+        let mut changed = false;
+
+        for post_id in ids {
+            let record = &index.posts[&post_id];
+            let wanted = naming.post_folder_name(&PostFields::of_record(&blog, &post_id, record));
+            if wanted == record.folder {
+                continue;
+            }
+            let from = record.folder.clone();
+            let mut occupied = taken.clone();
+            if fs::try_exists(blog_dir.join(&wanted))
+                .await
+                .unwrap_or(false)
+            {
+                occupied.insert(wanted.clone());
+            }
+            let Some(to) = resolve_folder_collision(&wanted, &occupied, naming.collision) else {
+                report.skipped.push(format!("{blog}/{from}"));
+                continue;
+            };
+
+            if !dry_run && let Err(e) = move_folder(&blog_dir, &from, &to).await {
+                report.failed.push(format!("{blog}/{from}: {e:#}"));
+                continue;
+            }
+            taken.remove(&from);
+            taken.insert(to.clone());
+            if !dry_run && let Some(record) = index.posts.get_mut(&post_id) {
+                record.folder = to.clone();
+                changed = true;
+            }
+            report.moves.push(FolderMove {
+                blog: blog.clone(),
+                post_id,
+                from,
+                to,
+            });
+        }
+
+        if changed {
+            blog_index::save(download_path, &index).await?;
+        }
+    }
+    Ok(report)
+}
+
+async fn move_folder(blog_dir: &Path, from: &str, to: &str) -> Result<()> {
+    let source = blog_dir.join(from);
+    let target = blog_dir.join(to);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+    }
+    fs::rename(&source, &target)
+        .await
+        .with_context(|| format!("Failed to move '{}'", source.display()))?;
+
+    // Drop the subfolders of the old layout that are now empty.
+    let mut dir = source.parent();
+    while let Some(current) = dir {
+        if current == blog_dir || fs::remove_dir(current).await.is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> PostFields {
+        PostFields {
+            blog: "author".into(),
+            author: "Ann".into(),
+            title: "Hello: world".into(),
+            id: "id-aaa".into(),
+            created_at: 1_700_000_000,
+            tag: "music".into(),
+        }
+    }
+
+    #[test]
+    fn default_post_folder_keeps_the_old_layout() {
+        let naming = NamingConfig::default();
+        assert_eq!(
+            naming.post_folder_name(&fields()),
+            "2023.11.14 Hello_ world [id-aaa]"
+        );
+
+        let long = PostFields {
+            title: "a".repeat(200),
+            ..fields()
+        };
+        let name = naming.post_folder_name(&long);
+        assert!(name.ends_with("[id-aaa]"));
+        assert!(name.len() < 120);
+    }
+
+    #[test]
+    fn post_folder_template_can_nest_but_not_escape() {
+        let naming = NamingConfig {
+            post_folder: "{year}/{month}/../{tag}/{title}".into(),
+            ..NamingConfig::default()
+        };
+        assert_eq!(
+            naming.post_folder_name(&fields()),
+            "2023/11/music/Hello_ world"
+        );
+
+        let empty_tag = PostFields {
+            tag: String::new(),
+            ..fields()
+        };
+        assert_eq!(naming.post_folder_name(&empty_tag), "2023/11/Hello_ world");
+    }
+
+    #[test]
+    fn nested_post_folders_do_not_overlap() {
+        let taken: HashSet<String> = ["2023/11".to_string()].into();
+        assert_eq!(
+            resolve_folder_collision("2023/11/Hello", &taken, CollisionPolicy::Suffix),
+            Some("2023/11 (2)/Hello".into())
+        );
+        assert_eq!(
+            resolve_folder_collision("2023/11/Hello", &taken, CollisionPolicy::Skip),
+            None
+        );
+
+        let taken: HashSet<String> = ["2023/11/Hello".to_string(), "2023/11 (2)".into()].into();
+        assert_eq!(
+            resolve_folder_collision("2023/11", &taken, CollisionPolicy::Suffix),
+            Some("2023/11 (3)".into())
+        );
+        assert_eq!(
+            resolve_folder_collision("2023/11/Hello world", &taken, CollisionPolicy::Skip),
+            Some("2023/11/Hello world".into())
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unknown_placeholders() {
+        let mut naming = NamingConfig::default();
+        assert!(naming.validate().is_ok());
+        naming.post_folder = "{date} {index}".into();
+        assert!(naming.validate().is_err());
+        naming.post_folder = DEFAULT_POST_FOLDER.into();
+        naming.image_file = "img/{id}".into();
+        assert!(naming.validate().is_err());
+    }
+
+    #[test]
+    fn media_names_follow_templates_and_resolve_collisions() {
+        let naming = NamingConfig {
+            image_file: "{index}".into(),
+            media_file: "{filename}".into(),
+            ..NamingConfig::default()
+        };
+        let namer = MediaNamer::new(&naming, fields());
+        assert_eq!(
+            namer.file_name(MediaKind::Image, "i1", "i1", Some("jpg")),
+            Some("1.jpg".into())
+        );
+        assert_eq!(
+            namer.file_name(MediaKind::File, "f1", "track.mp3", Some("mp3")),
+            Some("track.mp3".into())
+        );
+        assert_eq!(
+            namer.file_name(MediaKind::File, "f2", "track.mp3", Some("mp3")),
+            Some("track (2).mp3".into())
+        );
+        assert_eq!(
+            namer.file_name(MediaKind::File, "f1", "track.mp3", Some("mp3")),
+            Some("track.mp3".into())
+        );
+
+        let skip = NamingConfig {
+            collision: CollisionPolicy::Skip,
+            ..naming
+        };
+        let namer = MediaNamer::new(&skip, fields());
+        assert!(namer.file_name(MediaKind::File, "a", "x", None).is_some());
+        assert!(namer.file_name(MediaKind::File, "b", "x", None).is_none());
+    }
+
+    #[test]
+    fn default_media_names_match_the_old_ones() {
+        let naming = NamingConfig::default();
+        let namer = MediaNamer::new(&naming, fields());
+        assert_eq!(
+            namer.file_name(MediaKind::Image, "img", "img", Some("jpg")),
+            Some("img.jpg".into())
+        );
+        assert_eq!(
+            namer.file_name(MediaKind::File, "abc", "track", Some("mp3")),
+            Some("abc_track.mp3".into())
+        );
+        assert_eq!(
+            namer.file_name(MediaKind::Video, "v1", "clip", Some("mp4")),
+            Some("clip(v1).mp4".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn rename_moves_folders_and_updates_index() {
+        let base = std::env::temp_dir().join(format!(
+            "boosty_naming_{}_{}",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let old = "2023.11.14 Hello_ world [id-aaa]";
+        fs::create_dir_all(base.join("author").join(old))
+            .await
+            .unwrap();
+        fs::write(base.join("author").join(old).join("1.jpg"), b"x")
+            .await
+            .unwrap();
+        blog_index::upsert_post(
+            &base,
+            "author",
+            "id-aaa",
+            PostRecord {
+                title: "Hello: world".into(),
+                folder: old.into(),
+                created_at: 1_700_000_000,
+                updated_at: 1_700_000_000,
+                downloaded_options: Vec::new(),
+                last_checked_at: None,
+                is_paid: false,
+                author: "Ann".into(),
+                tags: vec!["music".into()],
+            },
+        )
+        .await
+        .unwrap();
+
+        let naming = NamingConfig {
+            post_folder: "{year}/{tag}/{title}".into(),
+            ..NamingConfig::default()
+        };
+        let planned = rename_in(&base, &naming, true).await.unwrap();
+        assert_eq!(planned.moves.len(), 1);
+        assert!(fs::try_exists(base.join("author").join(old)).await.unwrap());
+
+        let report = rename_in(&base, &naming, false).await.unwrap();
+        assert_eq!(report.moves[0].to, "2023/music/Hello_ world");
+        assert!(report.failed.is_empty());
+        let moved = base.join("author/2023/music/Hello_ world/1.jpg");
+        assert!(fs::try_exists(&moved).await.unwrap());
+        let index = blog_index::load(&base, "author").await.unwrap();
+        assert_eq!(index.posts["id-aaa"].folder, "2023/music/Hello_ world");
+
+        let again = rename_in(&base, &naming, false).await.unwrap();
+        assert!(again.moves.is_empty());
+
+        let _ = fs::remove_dir_all(&base).await;
+    }
+}
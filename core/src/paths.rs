@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+
+const APP_DIR_NAME: &str = "boosty_downloader";
+const CONFIG_FILE_NAME: &str = "config.json";
+pub const CONFIG_ENV: &str = "BOOSTY_CONFIG";
+const CONFIG_FLAG: &str = "--config";
+
+static CONFIG_OVERRIDE: OnceCell<PathBuf> = OnceCell::new();
+
+/// Uses `path` as the config file for the rest of the run (the `--config` flag).
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_OVERRIDE.set(path);
+}
+
+/// Value of `--config <path>` or `--config=<path>` among command line arguments.
+pub fn config_arg(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+fn exe_dir() -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to get current executable path")?;
+    let dir = exe.parent().context("Failed to get executable directory")?;
+    Ok(dir.to_path_buf())
+}
+
+fn explicit_config_path() -> Option<PathBuf> {
+    CONFIG_OVERRIDE.get().cloned().or_else(|| {
+        std::env::var_os(CONFIG_ENV)
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// `config.json` next to the executable, as older versions kept it.
+fn legacy_config_path() -> Option<PathBuf> {
+    let path = exe_dir().ok()?.join(CONFIG_FILE_NAME);
+    path.is_file().then_some(path)
+}
+
+/// Whether the app keeps all of its files next to the executable, as older versions did.
+fn is_legacy_layout() -> bool {
+    explicit_config_path().is_none() && legacy_config_path().is_some()
+}
+
+/// Where the config lives: `--config`, then `BOOSTY_CONFIG`, then a config next to the
+/// executable left by older versions, then the platform config dir (`$XDG_CONFIG_HOME` on Linux).
+pub fn config_path() -> Result<PathBuf> {
+    if let Some(path) = explicit_config_path() {
+        return Ok(path);
+    }
+    if let Some(path) = legacy_config_path() {
+        return Ok(path);
+    }
+    let dir = dirs::config_dir().context("Failed to find the user config directory")?;
+    Ok(dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+/// Directory for the queue, subscription list and other app files.
+pub fn data_dir() -> Result<PathBuf> {
+    if is_legacy_layout() {
+        return exe_dir();
+    }
+    let dir = dirs::data_dir().context("Failed to find the user data directory")?;
+    Ok(dir.join(APP_DIR_NAME))
+}
+
+/// Directory of the log files.
+pub fn log_dir() -> Result<PathBuf> {
+    Ok(data_dir()?.join("logs"))
+}
+
+/// Path of an application file (queue, subscriptions, ...) in the data directory.
+pub fn app_file_path(file_name: &str) -> Result<PathBuf> {
+    let dir = data_dir()?;
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create data directory '{}'", dir.display()))?;
+    Ok(dir.join(file_name))
+}
+
+/// Where content goes when `download_path` is not set.
+pub fn default_download_dir() -> PathBuf {
+    if is_legacy_layout()
+        && let Ok(dir) = exe_dir()
+    {
+        return dir;
+    }
+    dirs::download_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .or_else(|| data_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn config_flag_is_read_in_both_forms() {
+        assert_eq!(
+            config_arg(args(&["app", "--config", "/etc/boosty.json"])),
+            Some(PathBuf::from("/etc/boosty.json"))
+        );
+        assert_eq!(
+            config_arg(args(&["app", "--config=cfg.json"])),
+            Some(PathBuf::from("cfg.json"))
+        );
+        assert_eq!(config_arg(args(&["app", "--config"])), None);
+        assert_eq!(config_arg(args(&["app"])), None);
+    }
+}
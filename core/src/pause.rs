@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::events::{self, EventScope};
+use crate::progress_reporter;
+
+/// Pause of the sessions run with it: every session of a [`Downloader`](crate::Downloader),
+/// or those run through [`with_event_sink_and_pause`](crate::with_event_sink_and_pause).
+/// Clones pause and resume the same sessions.
+#[derive(Debug, Clone)]
+pub struct PauseHandle(Arc<watch::Sender<bool>>);
+
+impl Default for PauseHandle {
+    fn default() -> Self {
+        Self(Arc::new(watch::channel(false).0))
+    }
+}
+
+impl PauseHandle {
+    pub fn pause(&self) {
+        if !self.0.send_replace(true) {
+            self.changed();
+        }
+    }
+
+    pub fn resume(&self) {
+        if self.0.send_replace(false) {
+            self.changed();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<bool> {
+        self.0.subscribe()
+    }
+
+    fn changed(&self) {
+        for scope in events::live_scopes() {
+            if Arc::ptr_eq(&scope.pause.0, &self.0) {
+                progress_reporter::pause_changed(&scope);
+            }
+        }
+    }
+}
+
+/// Pauses the sessions run outside an event sink of their own, the CLI's; other frontends
+/// pause theirs through a [`PauseHandle`].
+pub fn pause() {
+    events::default_scope().pause.pause();
+}
+
+pub fn resume() {
+    events::default_scope().pause.resume();
+}
+
+/// Flips the pause state and returns the new one.
+pub fn toggle() -> bool {
+    if is_paused() {
+        resume();
+        false
+    } else {
+        pause();
+        true
+    }
+}
+
+/// Whether the sessions paused by [`pause`] are paused.
+pub fn is_paused() -> bool {
+    events::default_scope().pause.is_paused()
+}
+
+/// Clears the pause of `scope` so its next session doesn't start suspended. A pause the
+/// scope was given stays with whoever gave it.
+pub(crate) fn reset(scope: &EventScope) {
+    if !scope.shares_pause {
+        scope.pause.0.send_replace(false);
+    }
+}
+
+/// Suspends the caller while the session of its scope is paused.
+///
+/// # Errors
+///
+/// Returns the cancellation error if the session is cancelled before or during the pause.
+pub async fn wait_if_paused(cancel_token: &CancellationToken) -> Result<()> {
+    let mut rx = events::current().pause.subscribe();
+    while *rx.borrow_and_update() {
+        tokio::select! {
+            changed = rx.changed() => changed?,
+            _ = cancel_token.cancelled() => return Err(crate::Cancelled.into()),
+        }
+    }
+    crate::ensure_not_cancelled(cancel_token)
+}
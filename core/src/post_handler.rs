@@ -1,8 +1,18 @@
 use crate::{
-    DownloadOptions, cli, content_items_handler, download_options, file_handler, log_error,
-    post_page, progress_reporter,
+    DownloadOptions,
+    blog_index::PostRecord,
+    blog_settings::OutputFormat,
+    cli,
+    content_items_handler::{self, SignedQuery},
+    download_options,
+    events::{self, Event, PostOutcome},
+    file_handler, log_error,
+    naming::{MediaNamer, NamingConfig, PostFields},
+    post_page, progress_reporter, session_report,
+    session_state::Checkpoint,
 };
 use anyhow::Result;
+use boosty_api::api_client::ApiClient;
 use boosty_api::model::Post;
 use boosty_api::traits::{HasContent, HasTitle, IsAvailable};
 use post_page::PostPage;
@@ -38,21 +48,41 @@ fn count_post_files(post: &Post, download_options: &DownloadOptions) -> u64 {
 }
 
 pub async fn process_posts(
+    client: &ApiClient,
     result: PostsResult,
     download_path: &Path,
     download_options: DownloadOptions,
+    output_formats: &[OutputFormat],
+    naming: &NamingConfig,
+    mut checkpoint: Option<&mut Checkpoint>,
     cancel_token: &CancellationToken,
 ) -> Result<Vec<PostPage>> {
     let mut pages = Vec::new();
     match result {
         PostsResult::Multiple(posts) => {
             for post in posts {
-                crate::ensure_not_cancelled(cancel_token)?;
-                match process(&post, download_path, download_options.clone(), cancel_token).await {
-                    Ok(Some(page)) => pages.push(page),
-                    Ok(None) => {}
+                crate::pause::wait_if_paused(cancel_token).await?;
+                post_started(&post);
+                let outcome = process(
+                    client,
+                    &post,
+                    download_path,
+                    download_options.clone(),
+                    output_formats,
+                    naming,
+                    cancel_token,
+                )
+                .await;
+                report_outcome(&post, &outcome);
+                match outcome {
+                    Ok(page) => {
+                        pages.extend(page);
+                        if let Some(checkpoint) = checkpoint.as_deref_mut() {
+                            checkpoint.post_done(&post.id).await?;
+                        }
+                    }
                     Err(e) => {
-                        if crate::is_cancelled_error(&e) {
+                        if crate::is_stop_error(&e) {
                             return Err(e);
                         }
                         log_error!("Error processing post '{}': {:#}", post.safe_title(), e);
@@ -62,11 +92,23 @@ pub async fn process_posts(
         }
         PostsResult::Single(post) => {
             crate::ensure_not_cancelled(cancel_token)?;
-            match process(&post, download_path, download_options, cancel_token).await {
+            post_started(&post);
+            let outcome = process(
+                client,
+                &post,
+                download_path,
+                download_options,
+                output_formats,
+                naming,
+                cancel_token,
+            )
+            .await;
+            report_outcome(&post, &outcome);
+            match outcome {
                 Ok(Some(page)) => pages.push(page),
                 Ok(None) => {}
                 Err(e) => {
-                    if crate::is_cancelled_error(&e) {
+                    if crate::is_stop_error(&e) {
                         return Err(e);
                     }
                     log_error!("Error processing post '{}': {:#}", post.safe_title(), e);
@@ -78,9 +120,12 @@ pub async fn process_posts(
 }
 
 async fn process(
+    client: &ApiClient,
     post: &Post,
     download_path: &Path,
     download_options: DownloadOptions,
+    output_formats: &[OutputFormat],
+    naming: &NamingConfig,
     cancel_token: &CancellationToken,
 ) -> Result<Option<PostPage>> {
     crate::ensure_not_cancelled(cancel_token)?;
@@ -88,66 +133,108 @@ async fn process(
         return Ok(None);
     }
 
-    let post_title = post.safe_title();
-    let blog_name = &post.user.blog_url;
-
-    let post_folder_path = file_handler::prepare_folder_path(
-        blog_name,
-        &post_title,
-        post.created_at,
-        &post.id,
-        download_path,
-    )
-    .await?;
+    let fields = PostFields::of_post(post);
+    let Some(post_folder_path) =
+        file_handler::prepare_folder_path(&fields, naming, download_path).await?
+    else {
+        return Ok(None);
+    };
+    let blog_dir = download_path.join(&fields.blog);
+    let folder_name = post_folder_path
+        .strip_prefix(&blog_dir)
+        .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
 
     let items = post.extract_content();
     let filtered_items = download_options::filter_content_items(items, &download_options);
 
+    let namer = MediaNamer::new(naming, fields.clone());
+    let signed_query = SignedQuery::new(client, &fields.blog, &post.id, &post.signed_query);
     let body = content_items_handler::process_content_items(
         filtered_items,
-        &post_title,
+        &namer,
         &post_folder_path,
         "",
-        Some(&post.signed_query),
+        Some(&signed_query),
         cancel_token,
     )
     .await?;
 
-    let folder_name = post_folder_path
-        .file_name()
-        .map(|s| s.to_string_lossy().into_owned())
-        .unwrap_or_default();
-
+    let tags: Vec<String> = post.tags.iter().map(|t| t.title.clone()).collect();
     let page = PostPage {
         folder: post_folder_path,
         post_id: post.id.clone(),
-        title: post_title.clone(),
+        title: fields.title.clone(),
         created_at: post.created_at,
-        author: post.user.name.clone(),
-        blog: post.user.blog_url.clone(),
-        tags: post.tags.iter().map(|t| t.title.clone()).collect(),
+        author: fields.author.clone(),
+        blog: fields.blog.clone(),
+        tags: tags.clone(),
         body,
         comments: Vec::new(),
+        comments_truncated: false,
+        formats: output_formats.to_vec(),
     };
 
     post_page::write_post_page(&page).await?;
 
     crate::blog_index::upsert_post(
         download_path,
-        blog_name,
+        &fields.blog,
         &post.id,
-        &post_title,
-        &folder_name,
-        post.created_at,
-        post.updated_at,
-        &download_options,
-        post.price > 0.0,
+        PostRecord {
+            title: fields.title,
+            folder: folder_name,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            downloaded_options: download_options.iter().cloned().collect(),
+            last_checked_at: None,
+            is_paid: post.price > 0.0,
+            author: fields.author,
+            tags,
+        },
     )
     .await?;
 
     Ok(Some(page))
 }
 
+fn post_started(post: &Post) {
+    events::emit(Event::PostStarted {
+        post_id: post.id.clone(),
+        title: post.safe_title(),
+    });
+}
+
+fn report_outcome(post: &Post, outcome: &Result<Option<PostPage>>) {
+    let finished = match outcome {
+        Ok(Some(_)) => {
+            session_report::post_downloaded();
+            PostOutcome::Downloaded
+        }
+        Ok(None) if post.not_available() => {
+            session_report::post_unavailable(&post.id, &post.safe_title());
+            PostOutcome::Unavailable
+        }
+        Ok(None) => {
+            session_report::post_skipped();
+            PostOutcome::Skipped
+        }
+        Err(e) if !crate::is_stop_error(e) => {
+            session_report::post_failed(&post.id, &post.safe_title(), e);
+            PostOutcome::Failed {
+                message: format!("{e:#}"),
+            }
+        }
+        Err(_) => return,
+    };
+    session_report::post_finished(&post.id);
+    events::emit(Event::PostFinished {
+        post_id: post.id.clone(),
+        title: post.safe_title(),
+        outcome: finished,
+    });
+}
+
 fn check_available_post(post: &Post) -> bool {
     if post.not_available() {
         cli::post_not_available_or_without_content(&post.safe_title());
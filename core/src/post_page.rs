@@ -3,6 +3,8 @@ use chrono::DateTime;
 use std::path::PathBuf;
 use tokio::fs;
 
+use crate::blog_settings::OutputFormat;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextStyle {
     Unstyled,
@@ -56,6 +58,8 @@ pub struct CommentView {
     pub created_at: i64,
     pub level: u8,
     pub blocks: Vec<Block>,
+    /// Not every reply of this comment made it into the page.
+    pub truncated: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -69,6 +73,9 @@ pub struct PostPage {
     pub tags: Vec<String>,
     pub body: Vec<Block>,
     pub comments: Vec<CommentView>,
+    /// Not every top-level comment made it into the page.
+    pub comments_truncated: bool,
+    pub formats: Vec<OutputFormat>,
 }
 
 const TEMPLATE: &str = include_str!("../../templates/template.html");
@@ -92,7 +99,7 @@ pub fn render_post_html(page: &PostPage) -> String {
     let title = escape_html(&page.title);
     let header = render_header(page);
     let mut content = render_blocks(&page.body);
-    content.push_str(&render_comments(&page.comments));
+    content.push_str(&render_comments(&page.comments, page.comments_truncated));
 
     TEMPLATE
         .replace("{{TITLE}}", &title)
@@ -101,14 +108,80 @@ pub fn render_post_html(page: &PostPage) -> String {
 }
 
 pub async fn write_post_page(page: &PostPage) -> Result<()> {
-    let html = render_post_html(page);
-    let path = page.folder.join("index.html");
-    fs::write(&path, html)
-        .await
-        .with_context(|| format!("Failed to write HTML file '{}'", path.display()))?;
+    for format in &page.formats {
+        let (file_name, contents) = match format {
+            OutputFormat::Html => ("index.html", render_post_html(page)),
+            OutputFormat::Text => ("post.txt", render_post_text(page)),
+        };
+        let path = page.folder.join(file_name);
+        fs::write(&path, contents)
+            .await
+            .with_context(|| format!("Failed to write post file '{}'", path.display()))?;
+    }
     Ok(())
 }
 
+pub fn render_post_text(page: &PostPage) -> String {
+    let date = format_datetime(page.created_at, "%Y.%m.%d %H:%M");
+    let mut text = format!("{}\n{} · {date} · {}\n", page.title, page.author, page.blog);
+    if !page.tags.is_empty() {
+        text.push_str(&format!("#{}\n", page.tags.join(" #")));
+    }
+    text.push('\n');
+    text.push_str(render_blocks_text(&page.body).trim_end());
+    text.push('\n');
+
+    if !page.comments.is_empty() {
+        text.push_str("\n---\nКомментарии\n");
+        for comment in &page.comments {
+            let indent = "  ".repeat(comment.level as usize);
+            let date = format_datetime(comment.created_at, "%Y.%m.%d %H:%M");
+            text.push_str(&format!("\n{indent}{} · {date}\n", comment.author));
+            for line in render_blocks_text(&comment.blocks).trim_end().lines() {
+                text.push_str(&format!("{indent}{line}\n"));
+            }
+            if comment.truncated {
+                text.push_str(&format!("{indent}(показаны не все ответы)\n"));
+            }
+        }
+        if page.comments_truncated {
+            text.push_str("\n(показаны не все комментарии)\n");
+        }
+    }
+    text
+}
+
+fn render_blocks_text(blocks: &[Block]) -> String {
+    let mut text = String::new();
+    for block in blocks {
+        match block {
+            Block::Text { text: t, .. } => text.push_str(t),
+            Block::ParagraphBreak => text.push('\n'),
+            Block::Link { text: t, url } => text.push_str(&format!("{t} ({url})")),
+            Block::Smile { alt, .. } => text.push_str(alt),
+            Block::Image { rel, .. } => text.push_str(&format!("\n[image: {rel}]\n")),
+            Block::VideoFile { rel } => text.push_str(&format!("\n[video: {rel}]\n")),
+            Block::Audio { rel } => text.push_str(&format!("\n[audio: {rel}]\n")),
+            Block::Embed { watch_url, .. } => text.push_str(&format!("\n[video: {watch_url}]\n")),
+            Block::FileLink { rel, title } => {
+                text.push_str(&format!("\n[file: {title} ({rel})]\n"))
+            }
+            Block::List { ordered, items } => {
+                text.push('\n');
+                for (i, item) in items.iter().enumerate() {
+                    let marker = if *ordered {
+                        format!("{}.", i + 1)
+                    } else {
+                        "-".to_string()
+                    };
+                    text.push_str(&format!("{marker} {}\n", render_blocks_text(item).trim()));
+                }
+            }
+        }
+    }
+    text
+}
+
 fn render_header(page: &PostPage) -> String {
     let title = escape_html(&page.title);
     let author = escape_html(&page.author);
@@ -131,7 +204,7 @@ fn render_header(page: &PostPage) -> String {
     html
 }
 
-fn render_comments(comments: &[CommentView]) -> String {
+fn render_comments(comments: &[CommentView], truncated: bool) -> String {
     if comments.is_empty() {
         return String::new();
     }
@@ -141,11 +214,19 @@ fn render_comments(comments: &[CommentView]) -> String {
         let author = escape_html(&comment.author);
         let date = format_datetime(comment.created_at, "%Y.%m.%d %H:%M");
         let level = comment.level;
+        let truncated = if comment.truncated {
+            "\n    <p class=\"comment-truncated\">Показаны не все ответы</p>"
+        } else {
+            ""
+        };
         html.push_str(&format!(
-            "  <article class=\"comment\" style=\"--level:{level}\">\n    <header class=\"comment-meta\"><span class=\"author\">{author}</span><span class=\"sep\">·</span><time>{date}</time></header>\n    <div class=\"comment-body\">{}</div>\n  </article>\n",
+            "  <article class=\"comment\" style=\"--level:{level}\">\n    <header class=\"comment-meta\"><span class=\"author\">{author}</span><span class=\"sep\">·</span><time>{date}</time></header>\n    <div class=\"comment-body\">{}</div>{truncated}\n  </article>\n",
             render_blocks(&comment.blocks)
         ));
     }
+    if truncated {
+        html.push_str("  <p class=\"comment-truncated\">Показаны не все комментарии</p>\n");
+    }
     html.push_str("</section>\n");
     html
 }
@@ -288,6 +369,8 @@ mod tests {
             tags: vec!["tag".into()],
             body,
             comments,
+            comments_truncated: false,
+            formats: vec![OutputFormat::Html],
         }
     }
 
@@ -334,12 +417,70 @@ mod tests {
                     text: "reply".into(),
                     style: TextStyle::Unstyled,
                 }],
+                truncated: false,
             }],
         ));
         assert!(html.contains("Комментарии"));
         assert!(html.contains("style=\"--level:2\""));
         assert!(html.contains("Bob"));
         assert!(html.contains("<p>reply</p>"));
+        assert!(!html.contains("comment-truncated"));
+    }
+
+    #[test]
+    fn render_marks_truncated_thread() {
+        let mut page = sample_page(
+            vec![],
+            vec![CommentView {
+                author: "Bob".into(),
+                created_at: 1_700_000_000,
+                level: 0,
+                blocks: vec![],
+                truncated: true,
+            }],
+        );
+        let html = render_post_html(&page);
+        assert!(html.contains("<p class=\"comment-truncated\">Показаны не все ответы</p>"));
+        assert!(!html.contains("Показаны не все комментарии"));
+
+        page.comments_truncated = true;
+        assert!(render_post_html(&page).contains("Показаны не все комментарии"));
+    }
+
+    #[test]
+    fn text_output_keeps_links_media_and_comments() {
+        let text = render_post_text(&sample_page(
+            vec![
+                Block::Text {
+                    text: "Hi".into(),
+                    style: TextStyle::Bold,
+                },
+                Block::ParagraphBreak,
+                Block::Link {
+                    text: "site".into(),
+                    url: "https://example.com".into(),
+                },
+                Block::Audio {
+                    rel: "track.mp3".into(),
+                },
+            ],
+            vec![CommentView {
+                author: "Bob".into(),
+                created_at: 1_700_000_000,
+                level: 1,
+                blocks: vec![Block::Text {
+                    text: "reply".into(),
+                    style: TextStyle::Unstyled,
+                }],
+                truncated: false,
+            }],
+        ));
+        assert!(text.starts_with("Hello <world>\nAnn · "));
+        assert!(text.contains("#tag\n"));
+        assert!(text.contains("Hi\nsite (https://example.com)"));
+        assert!(text.contains("[audio: track.mp3]"));
+        assert!(text.contains("\n  Bob · "));
+        assert!(text.contains("\n  reply\n"));
     }
 
     #[test]
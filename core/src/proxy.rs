@@ -0,0 +1,119 @@
+use anyhow::{Context, Result, bail};
+use reqwest::{NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+
+/// Proxy for both API and media traffic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub enabled: bool,
+    pub scheme: ProxyScheme,
+    pub host: String,
+    pub port: u16,
+    /// Sent as proxy credentials when not empty.
+    pub username: String,
+    pub password: String,
+    /// Hosts, domains (`.example.com`) and IP ranges reached directly.
+    pub no_proxy: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyScheme {
+    #[default]
+    Http,
+    Https,
+    /// Host names are resolved by the proxy.
+    Socks5,
+}
+
+impl ProxyScheme {
+    fn as_url_scheme(self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+            Self::Socks5 => "socks5h",
+        }
+    }
+}
+
+impl ProxyConfig {
+    /// Address of the proxy without the password, for messages.
+    pub fn display_url(&self) -> String {
+        let user = if self.username.is_empty() {
+            String::new()
+        } else {
+            format!("{}@", self.username)
+        };
+        format!(
+            "{}://{user}{}:{}",
+            self.scheme.as_url_scheme(),
+            self.host,
+            self.port
+        )
+    }
+
+    /// The proxy to give to a client builder, `None` when disabled.
+    pub fn to_proxy(&self) -> Result<Option<Proxy>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        if self.host.trim().is_empty() {
+            bail!("Proxy host is empty");
+        }
+        if self.port == 0 {
+            bail!("Proxy port is not set");
+        }
+        let url = format!(
+            "{}://{}:{}",
+            self.scheme.as_url_scheme(),
+            self.host.trim(),
+            self.port
+        );
+        let mut proxy =
+            Proxy::all(&url).with_context(|| format!("Invalid proxy address '{url}'"))?;
+        if !self.username.is_empty() {
+            proxy = proxy.basic_auth(&self.username, &self.password);
+        }
+        if !self.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(",")));
+        }
+        Ok(Some(proxy))
+    }
+}
+
+/// Whether the error comes from failing to connect, which behind a proxy usually means
+/// the proxy is down, the address is wrong or it refused the credentials.
+pub(crate) fn is_connect_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxy_is_built_only_when_enabled_and_complete() {
+        let mut cfg = ProxyConfig {
+            scheme: ProxyScheme::Socks5,
+            host: "127.0.0.1".into(),
+            port: 1080,
+            username: "user".into(),
+            password: "secret".into(),
+            no_proxy: vec!["localhost".into()],
+            ..ProxyConfig::default()
+        };
+        assert!(cfg.to_proxy().unwrap().is_none());
+
+        cfg.enabled = true;
+        assert!(cfg.to_proxy().unwrap().is_some());
+        assert_eq!(cfg.display_url(), "socks5h://user@127.0.0.1:1080");
+
+        cfg.port = 0;
+        assert!(cfg.to_proxy().is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::{Deserialize, Serialize};
+
+use crate::context;
+
+/// How failed file downloads are retried.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Attempts per file, the first one included.
+    pub max_attempts: u32,
+    /// Wait before the second attempt; doubles with each next one.
+    pub base_delay_ms: u64,
+    /// Longest wait between attempts, `Retry-After` of the server included.
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_ms: 2000,
+            max_delay_secs: 60,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn attempts(&self) -> u32 {
+        self.max_attempts.max(1)
+    }
+
+    /// Wait after failed attempt number `attempt` (from 1): what the server asked for, or
+    /// an exponential backoff with jitter, both capped at `max_delay_secs`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let cap = Duration::from_secs(self.max_delay_secs);
+        if let Some(wait) = retry_after {
+            return wait.min(cap);
+        }
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let backoff = Duration::from_millis(self.base_delay_ms)
+            .saturating_mul(factor)
+            .min(cap);
+        // Half fixed, half random, so parallel downloads don't retry in lockstep.
+        let half = backoff / 2;
+        let jitter_ms = half.as_millis() as u64;
+        let jitter = match jitter_ms {
+            0 => Duration::ZERO,
+            max => Duration::from_millis(fastrand::u64(0..=max)),
+        };
+        half + jitter
+    }
+}
+
+/// Applies `cfg` to the downloads of the current context started from now on.
+pub fn configure(cfg: &RetryConfig) {
+    *context::current().retry.lock().unwrap() = cfg.clone();
+}
+
+pub(crate) fn current() -> RetryConfig {
+    context::current().retry.lock().unwrap().clone()
+}
+
+/// `Retry-After` as seconds or an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    (at - Utc::now()).to_std().ok().or(Some(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn delay_grows_with_jitter_and_stays_under_the_cap() {
+        let cfg = RetryConfig {
+            max_attempts: 5,
+            base_delay_ms: 1000,
+            max_delay_secs: 5,
+        };
+        for _ in 0..20 {
+            let first = cfg.delay(1, None);
+            assert!(first >= Duration::from_millis(500) && first <= Duration::from_secs(1));
+            let third = cfg.delay(3, None);
+            assert!(third >= Duration::from_secs(2) && third <= Duration::from_secs(4));
+            assert!(cfg.delay(30, None) <= Duration::from_secs(5));
+        }
+        assert_eq!(
+            cfg.delay(1, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            cfg.delay(1, Some(Duration::from_secs(600))),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_dates() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
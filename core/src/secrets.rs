@@ -0,0 +1,378 @@
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result, anyhow};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::config::AppConfig;
+use crate::paths;
+use crate::{cli, log_warn};
+
+const KEYRING_SERVICE: &str = "boosty_downloader";
+const SECRETS_FILE_NAME: &str = "secrets.enc";
+pub const PASSPHRASE_ENV: &str = "BOOSTY_SECRETS_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Where the tokens of a saved config live.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStorage {
+    /// In `config.json` itself: configs from older versions, or no secret store available.
+    #[default]
+    Plain,
+    Keyring,
+    EncryptedFile,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SecretTokens {
+    access_token: String,
+    refresh_token: String,
+}
+
+type TokenBundle = BTreeMap<String, SecretTokens>;
+
+/// Set when the tokens could not be read this run, so an empty bundle must not overwrite them.
+static LOCKED: AtomicBool = AtomicBool::new(false);
+/// Set once neither the keyring nor the encrypted file could be written, so later saves
+/// don't try them and warn again.
+static NO_STORE: AtomicBool = AtomicBool::new(false);
+static PASSPHRASE: Lazy<std::sync::Mutex<Option<String>>> =
+    Lazy::new(|| std::sync::Mutex::new(None));
+
+/// Moves the tokens out of `cfg` into the keyring (or the encrypted file) and returns
+/// the config to write to disk. Falls back to plain text if neither store is usable.
+pub(crate) async fn store(cfg: &AppConfig) -> AppConfig {
+    let mut stripped = cfg.clone();
+    let mut bundle = take_tokens(&mut stripped);
+
+    if LOCKED.load(Ordering::Acquire) {
+        if bundle.values().all(is_empty) {
+            return stripped;
+        }
+        // The tokens of the other profiles are only in the store; keep them.
+        match read(cfg.token_storage).await {
+            Ok(existing) => {
+                bundle = merge(existing, bundle);
+                LOCKED.store(false, Ordering::Release);
+            }
+            Err(e) => {
+                log_warn!(
+                    "Saved tokens could not be read, new tokens are kept for this run only: {e:#}"
+                );
+                return stripped;
+            }
+        }
+    }
+
+    if !NO_STORE.load(Ordering::Acquire) {
+        match write_keyring(&bundle).await {
+            Ok(()) => {
+                stripped.token_storage = TokenStorage::Keyring;
+                return stripped;
+            }
+            Err(e) => log_warn!("OS keyring is not available: {e:#}"),
+        }
+
+        if let Some(passphrase) = passphrase() {
+            match write_encrypted(&bundle, &passphrase).await {
+                Ok(()) => {
+                    stripped.token_storage = TokenStorage::EncryptedFile;
+                    return stripped;
+                }
+                Err(e) => log_warn!("Failed to write encrypted tokens: {e:#}"),
+            }
+        }
+
+        NO_STORE.store(true, Ordering::Release);
+        log_warn!(
+            "Tokens are saved to the config in plain text; set {PASSPHRASE_ENV} to encrypt them"
+        );
+    }
+    let mut plain = cfg.clone();
+    plain.token_storage = TokenStorage::Plain;
+    plain
+}
+
+/// Fills the tokens of a loaded config from the store it names.
+pub(crate) async fn restore(cfg: &mut AppConfig) {
+    if cfg.token_storage == TokenStorage::Plain {
+        return;
+    }
+    match read(cfg.token_storage).await {
+        Ok(bundle) => {
+            LOCKED.store(false, Ordering::Release);
+            put_tokens(cfg, bundle);
+        }
+        Err(e) => {
+            LOCKED.store(true, Ordering::Release);
+            log_warn!("Failed to read saved tokens, continuing without them: {e:#}");
+        }
+    }
+}
+
+async fn read(storage: TokenStorage) -> Result<TokenBundle> {
+    match storage {
+        TokenStorage::Plain => Ok(TokenBundle::new()),
+        TokenStorage::Keyring => read_keyring().await,
+        TokenStorage::EncryptedFile => match passphrase() {
+            Some(passphrase) => read_encrypted(&passphrase).await,
+            None => Err(anyhow!("no passphrase, set {PASSPHRASE_ENV}")),
+        },
+    }
+}
+
+/// `existing` with the profiles that have tokens in `bundle` replaced.
+fn merge(mut existing: TokenBundle, bundle: TokenBundle) -> TokenBundle {
+    existing.extend(bundle.into_iter().filter(|(_, tokens)| !is_empty(tokens)));
+    existing
+}
+
+/// Whether a config still keeps tokens in plain text and should be moved to a secret store.
+pub(crate) fn needs_migration(cfg: &AppConfig) -> bool {
+    !NO_STORE.load(Ordering::Acquire)
+        && cfg.token_storage == TokenStorage::Plain
+        && !take_tokens(&mut cfg.clone()).values().all(is_empty)
+}
+
+fn is_empty(tokens: &SecretTokens) -> bool {
+    tokens.access_token.is_empty() && tokens.refresh_token.is_empty()
+}
+
+fn take_tokens(cfg: &mut AppConfig) -> TokenBundle {
+    let mut bundle = TokenBundle::new();
+    bundle.insert(
+        cfg.active_profile.clone(),
+        SecretTokens {
+            access_token: std::mem::take(&mut cfg.access_token),
+            refresh_token: std::mem::take(&mut cfg.refresh_token),
+        },
+    );
+    for (name, profile) in cfg.profiles.iter_mut() {
+        bundle.insert(
+            name.clone(),
+            SecretTokens {
+                access_token: std::mem::take(&mut profile.access_token),
+                refresh_token: std::mem::take(&mut profile.refresh_token),
+            },
+        );
+    }
+    bundle
+}
+
+fn put_tokens(cfg: &mut AppConfig, mut bundle: TokenBundle) {
+    if let Some(tokens) = bundle.remove(&cfg.active_profile) {
+        cfg.access_token = tokens.access_token;
+        cfg.refresh_token = tokens.refresh_token;
+    }
+    for (name, profile) in cfg.profiles.iter_mut() {
+        if let Some(tokens) = bundle.remove(name) {
+            profile.access_token = tokens.access_token;
+            profile.refresh_token = tokens.refresh_token;
+        }
+    }
+}
+
+/// Whether tokens went to the config in plain text this run, because neither the keyring
+/// nor the encrypted file could be written.
+pub fn tokens_in_plain_text() -> bool {
+    NO_STORE.load(Ordering::Acquire)
+}
+
+/// Asks for the passphrase of the encrypted file once, at startup, so no prompt comes up
+/// in the middle of a download: when the file exists, or when the keyring can't hold the
+/// tokens. Only in a terminal and without [`PASSPHRASE_ENV`].
+pub async fn ask_passphrase() {
+    if passphrase().is_some() || !std::io::stdin().is_terminal() {
+        return;
+    }
+    let file_exists = match secrets_path() {
+        Ok(path) => fs::try_exists(path).await.unwrap_or(false),
+        Err(_) => false,
+    };
+    if !file_exists && keyring_usable().await {
+        return;
+    }
+    *PASSPHRASE.lock().unwrap() = cli::read_secrets_passphrase(!file_exists);
+}
+
+/// Passphrase of the encrypted file: from the environment or given to [`ask_passphrase`].
+fn passphrase() -> Option<String> {
+    let mut cached = PASSPHRASE.lock().unwrap();
+    if cached.is_none() {
+        *cached = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty());
+    }
+    cached.clone()
+}
+
+/// Keyring entry per config file, so separate installs don't share tokens.
+fn keyring_entry() -> Result<keyring::Entry> {
+    let user = format!("tokens:{}", paths::config_path()?.display());
+    Ok(keyring::Entry::new(KEYRING_SERVICE, &user)?)
+}
+
+async fn write_keyring(bundle: &TokenBundle) -> Result<()> {
+    let data = serde_json::to_string(bundle)?;
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        keyring_entry()?.set_password(&data)?;
+        Ok(())
+    })
+    .await?
+}
+
+/// Whether the keyring answers at all; an entry that isn't there yet is fine.
+async fn keyring_usable() -> bool {
+    tokio::task::spawn_blocking(|| {
+        keyring_entry()
+            .is_ok_and(|entry| matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry)))
+    })
+    .await
+    .unwrap_or(false)
+}
+
+async fn read_keyring() -> Result<TokenBundle> {
+    let data =
+        tokio::task::spawn_blocking(|| -> Result<String> { Ok(keyring_entry()?.get_password()?) })
+            .await??;
+    serde_json::from_str(&data).context("Failed to parse tokens from keyring")
+}
+
+fn secrets_path() -> Result<PathBuf> {
+    paths::app_file_path(SECRETS_FILE_NAME)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive key: {e}"))?;
+    Ok(key)
+}
+
+/// File layout: salt | nonce | ciphertext of the JSON bundle.
+fn encrypt(bundle: &TokenBundle, passphrase: &str) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plain = serde_json::to_vec(bundle)?;
+    let sealed = cipher
+        .encrypt(&nonce, plain.as_slice())
+        .map_err(|_| anyhow!("Failed to encrypt tokens"))?;
+
+    let mut data = Vec::with_capacity(SALT_LEN + NONCE_LEN + sealed.len());
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&sealed);
+    Ok(data)
+}
+
+fn decrypt(data: &[u8], passphrase: &str) -> Result<TokenBundle> {
+    if data.len() < SALT_LEN + NONCE_LEN {
+        anyhow::bail!("Encrypted tokens file is truncated");
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    let plain = cipher
+        .decrypt(Nonce::from_slice(nonce), sealed)
+        .map_err(|_| anyhow!("Wrong passphrase or damaged tokens file"))?;
+    serde_json::from_slice(&plain).context("Failed to parse decrypted tokens")
+}
+
+async fn write_encrypted(bundle: &TokenBundle, passphrase: &str) -> Result<()> {
+    let path = secrets_path()?;
+    let data = encrypt(bundle, passphrase)?;
+    fs::write(&path, data)
+        .await
+        .with_context(|| format!("Failed to write '{}'", path.display()))
+}
+
+async fn read_encrypted(passphrase: &str) -> Result<TokenBundle> {
+    let path = secrets_path()?;
+    let data = fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read '{}'", path.display()))?;
+    decrypt(&data, passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AuthProfile;
+
+    #[test]
+    fn tokens_round_trip_through_bundle() {
+        let mut cfg = AppConfig {
+            access_token: "main".into(),
+            ..AppConfig::default()
+        };
+        cfg.profiles.insert(
+            "work".into(),
+            AuthProfile {
+                access_token: "work".into(),
+                refresh_token: "work-refresh".into(),
+                device_id: "device".into(),
+                ..AuthProfile::default()
+            },
+        );
+        assert!(needs_migration(&cfg));
+
+        let bundle = take_tokens(&mut cfg);
+        assert!(cfg.access_token.is_empty());
+        assert!(cfg.profiles["work"].refresh_token.is_empty());
+        assert_eq!(cfg.profiles["work"].device_id, "device");
+
+        put_tokens(&mut cfg, bundle);
+        assert_eq!(cfg.access_token, "main");
+        assert_eq!(cfg.profiles["work"].refresh_token, "work-refresh");
+    }
+
+    #[test]
+    fn merge_keeps_profiles_without_new_tokens() {
+        let tokens = |access: &str| SecretTokens {
+            access_token: access.into(),
+            refresh_token: String::new(),
+        };
+        let existing = TokenBundle::from([
+            ("default".to_string(), tokens("old")),
+            ("work".to_string(), tokens("work")),
+        ]);
+        let bundle = TokenBundle::from([
+            ("default".to_string(), tokens("new")),
+            ("work".to_string(), tokens("")),
+        ]);
+
+        let merged = merge(existing, bundle);
+        assert_eq!(merged["default"].access_token, "new");
+        assert_eq!(merged["work"].access_token, "work");
+    }
+
+    #[test]
+    fn encrypted_bundle_needs_the_right_passphrase() {
+        let mut bundle = TokenBundle::new();
+        bundle.insert(
+            "default".into(),
+            SecretTokens {
+                access_token: "secret".into(),
+                refresh_token: String::new(),
+            },
+        );
+        let data = encrypt(&bundle, "correct horse").unwrap();
+
+        assert!(!data.windows(6).any(|w| w == b"secret"));
+        assert!(decrypt(&data, "wrong").is_err());
+        let restored = decrypt(&data, "correct horse").unwrap();
+        assert_eq!(restored["default"].access_token, "secret");
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::blog_index::FailedItem;
+use crate::error::ErrorKind;
+use crate::events::{self, EventScope};
+
+/// Written to the blog folder after a session when `save_session_report` is on.
+pub const REPORT_FILE: &str = "session_report.json";
+
+/// What a download session of one blog or post did.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionReport {
+    pub blog: String,
+    /// Unix seconds.
+    pub started_at: i64,
+    pub duration_ms: u64,
+    pub posts_downloaded: u32,
+    /// Posts left out because their folder name belongs to another post.
+    pub posts_skipped: u32,
+    /// Posts the current profile has no access to.
+    pub posts_unavailable: Vec<ReportedPost>,
+    pub posts_failed: Vec<FailedPost>,
+    pub files_downloaded: u32,
+    /// Files already on disk or left out as name collisions.
+    pub files_skipped: u32,
+    pub files_failed: Vec<FailedFile>,
+    /// Bytes written in this session, resumed parts of files not included.
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportedPost {
+    pub post_id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedPost {
+    pub post_id: String,
+    pub title: String,
+    pub kind: ErrorKind,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedFile {
+    pub post_id: String,
+    pub post_title: String,
+    /// Id of the content item, or the name of a smile.
+    pub item_id: String,
+    pub url_kind: UrlKind,
+    pub file: String,
+    pub reason: String,
+}
+
+/// Kind of media link; audio and file links are signed and expire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlKind {
+    Image,
+    Video,
+    Audio,
+    File,
+    Smile,
+}
+
+impl UrlKind {
+    pub fn is_signed(self) -> bool {
+        matches!(self, Self::Audio | Self::File)
+    }
+}
+
+impl SessionReport {
+    pub fn has_failures(&self) -> bool {
+        !self.posts_failed.is_empty() || !self.files_failed.is_empty()
+    }
+
+    /// Failures to keep in the blog index for a later retry.
+    pub(crate) fn failed_items(&self) -> Vec<FailedItem> {
+        let posts = self.posts_failed.iter().map(|post| FailedItem {
+            post_id: post.post_id.clone(),
+            title: post.title.clone(),
+            item_id: None,
+            url_kind: None,
+            error: post.reason.clone(),
+        });
+        let files = self.files_failed.iter().map(|file| FailedItem {
+            post_id: file.post_id.clone(),
+            title: file.post_title.clone(),
+            item_id: Some(file.item_id.clone()),
+            url_kind: Some(file.url_kind),
+            error: file.reason.clone(),
+        });
+        posts.chain(files).collect()
+    }
+}
+
+/// The report of the session running in an event scope.
+pub(crate) struct Recorder {
+    report: SessionReport,
+    started: Instant,
+    /// Posts gone through to the end, so their failures in the blog index can be replaced.
+    finished_posts: HashSet<String>,
+}
+
+/// Collects the report of the session running in the current event scope until dropped
+/// or finished.
+pub struct ReportGuard {
+    scope: Arc<EventScope>,
+}
+
+impl ReportGuard {
+    pub fn new(blog: &str) -> Self {
+        let scope = events::current();
+        *scope.report.lock().unwrap() = Some(Recorder {
+            report: SessionReport {
+                blog: blog.to_string(),
+                started_at: Utc::now().timestamp(),
+                ..SessionReport::default()
+            },
+            started: Instant::now(),
+            finished_posts: HashSet::new(),
+        });
+        Self { scope }
+    }
+
+    /// Posts the session went through to the end so far, whatever their outcome.
+    pub(crate) fn finished_posts(&self) -> HashSet<String> {
+        self.scope
+            .report
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|r| r.finished_posts.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn finish(self) -> SessionReport {
+        let recorder = self.scope.report.lock().unwrap().take();
+        recorder
+            .map(|r| SessionReport {
+                duration_ms: r.started.elapsed().as_millis() as u64,
+                ..r.report
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for ReportGuard {
+    fn drop(&mut self) {
+        *self.scope.report.lock().unwrap() = None;
+    }
+}
+
+fn record(f: impl FnOnce(&mut SessionReport)) {
+    if let Some(recorder) = events::current().report.lock().unwrap().as_mut() {
+        f(&mut recorder.report);
+    }
+}
+
+pub(crate) fn post_finished(post_id: &str) {
+    if let Some(recorder) = events::current().report.lock().unwrap().as_mut() {
+        recorder.finished_posts.insert(post_id.to_string());
+    }
+}
+
+pub(crate) fn post_downloaded() {
+    record(|r| r.posts_downloaded += 1);
+}
+
+pub(crate) fn post_skipped() {
+    record(|r| r.posts_skipped += 1);
+}
+
+pub(crate) fn post_unavailable(post_id: &str, title: &str) {
+    record(|r| {
+        r.posts_unavailable.push(ReportedPost {
+            post_id: post_id.to_string(),
+            title: title.to_string(),
+        })
+    });
+}
+
+pub(crate) fn post_failed(post_id: &str, title: &str, err: &anyhow::Error) {
+    record(|r| {
+        r.posts_failed.push(FailedPost {
+            post_id: post_id.to_string(),
+            title: title.to_string(),
+            kind: ErrorKind::of(err),
+            reason: format!("{err:#}"),
+        })
+    });
+}
+
+pub(crate) fn file_downloaded() {
+    record(|r| r.files_downloaded += 1);
+}
+
+pub(crate) fn file_skipped() {
+    record(|r| r.files_skipped += 1);
+}
+
+pub(crate) fn file_failed(failed: FailedFile) {
+    record(|r| r.files_failed.push(failed));
+}
+
+pub(crate) fn add_bytes(bytes: u64) {
+    record(|r| r.bytes += bytes);
+}
+
+/// Writes `report` to the blog folder, replacing the one of the previous session.
+pub async fn save(download_path: &Path, report: &SessionReport) -> Result<PathBuf> {
+    let blog_dir = download_path.join(&report.blog);
+    fs::create_dir_all(&blog_dir)
+        .await
+        .with_context(|| format!("Failed to create folder '{}'", blog_dir.display()))?;
+    let path = blog_dir.join(REPORT_FILE);
+    let data = serde_json::to_vec_pretty(report).context("Failed to serialize session report")?;
+    fs::write(&path, data)
+        .await
+        .with_context(|| format!("Failed to write session report '{}'", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `f` in an event scope of its own, so tests don't share the session state.
+    async fn in_scope<F: Future>(f: F) -> F::Output {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        events::with_sink(Arc::new(tx), f).await
+    }
+
+    #[tokio::test]
+    async fn report_collects_only_while_the_guard_lives() {
+        in_scope(async {
+            post_downloaded();
+            let guard = ReportGuard::new("author");
+            post_downloaded();
+            post_unavailable("2", "Paid");
+            file_downloaded();
+            file_failed(FailedFile {
+                post_id: "1".into(),
+                post_title: "Free".into(),
+                item_id: "a".into(),
+                url_kind: UrlKind::Audio,
+                file: "a.mp3".into(),
+                reason: "HTTP 404 Not Found: ".into(),
+            });
+            add_bytes(10);
+            post_failed("3", "Broken", &anyhow::Error::from(crate::Cancelled));
+            let report = guard.finish();
+
+            assert_eq!(report.blog, "author");
+            assert_eq!(report.posts_downloaded, 1);
+            assert_eq!(report.posts_unavailable.len(), 1);
+            assert_eq!(report.files_downloaded, 1);
+            assert_eq!(report.bytes, 10);
+            assert_eq!(report.posts_failed[0].kind, ErrorKind::Cancelled);
+            assert!(report.has_failures());
+            let items = report.failed_items();
+            assert_eq!(items.len(), 2);
+            assert_eq!(items[1].url_kind, Some(UrlKind::Audio));
+
+            post_skipped();
+            assert!(events::current().report.lock().unwrap().is_none());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn sessions_in_different_scopes_report_separately() {
+        in_scope(async {
+            let outer = ReportGuard::new("outer");
+            let inner = in_scope(async {
+                let guard = ReportGuard::new("inner");
+                post_downloaded();
+                guard.finish()
+            })
+            .await;
+            post_skipped();
+            let outer = outer.finish();
+
+            assert_eq!((inner.blog.as_str(), inner.posts_downloaded), ("inner", 1));
+            assert_eq!(inner.posts_skipped, 0);
+            assert_eq!((outer.blog.as_str(), outer.posts_downloaded), ("outer", 0));
+            assert_eq!(outer.posts_skipped, 1);
+        })
+        .await;
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use crate::parser::{self, BoostyUrl};
+
+pub const SESSION_FILE_NAME: &str = ".boosty_session.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentTarget {
+    pub blog: String,
+    pub post_id: String,
+    pub title: String,
+}
+
+/// Plan of an interrupted blog download, kept in the blog folder until the session finishes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub blog: String,
+    #[serde(default)]
+    pub feed_offset: Option<String>,
+    pub post_ids: Vec<String>,
+    #[serde(default)]
+    pub completed_posts: Vec<String>,
+    #[serde(default)]
+    pub pending_comments: Vec<CommentTarget>,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+impl SessionState {
+    pub fn new(
+        blog: &str,
+        feed_offset: Option<String>,
+        post_ids: Vec<String>,
+        pending_comments: Vec<CommentTarget>,
+    ) -> Self {
+        let now = Utc::now().timestamp();
+        Self {
+            blog: blog.to_string(),
+            feed_offset,
+            post_ids,
+            completed_posts: Vec::new(),
+            pending_comments,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn is_post_completed(&self, post_id: &str) -> bool {
+        self.completed_posts.iter().any(|id| id == post_id)
+    }
+
+    pub fn has_pending_comments(&self, post_id: &str) -> bool {
+        self.pending_comments.iter().any(|t| t.post_id == post_id)
+    }
+
+    /// Posts that still have to be fetched: unfinished ones and finished ones whose
+    /// comments are pending (their page is rebuilt to attach the comments).
+    pub fn posts_to_fetch(&self) -> Vec<String> {
+        let pending: HashSet<&str> = self
+            .pending_comments
+            .iter()
+            .map(|t| t.post_id.as_str())
+            .collect();
+        self.post_ids
+            .iter()
+            .filter(|id| !self.is_post_completed(id) || pending.contains(id.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+pub fn session_path(download_path: &Path, blog: &str) -> PathBuf {
+    download_path.join(blog).join(SESSION_FILE_NAME)
+}
+
+pub async fn load(download_path: &Path, blog: &str) -> Result<Option<SessionState>> {
+    let path = session_path(download_path, blog);
+    if !fs::try_exists(&path).await.unwrap_or(false) {
+        return Ok(None);
+    }
+    let data = fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read session state '{}'", path.display()))?;
+    let state = serde_json::from_slice(&data)
+        .with_context(|| format!("Failed to parse session state '{}'", path.display()))?;
+    Ok(Some(state))
+}
+
+/// Interrupted session of the blog a download URL points at. Post URLs never have one.
+pub async fn load_for_url(download_path: &Path, url: &str) -> Result<Option<SessionState>> {
+    match parser::parse_boosty_url(url)? {
+        BoostyUrl::Blog(blog) => load(download_path, &blog).await,
+        BoostyUrl::Post { .. } => Ok(None),
+    }
+}
+
+pub async fn save(download_path: &Path, state: &SessionState) -> Result<()> {
+    let blog_dir = download_path.join(&state.blog);
+    fs::create_dir_all(&blog_dir)
+        .await
+        .with_context(|| format!("Failed to create blog folder '{}'", blog_dir.display()))?;
+    let path = session_path(download_path, &state.blog);
+    let data = serde_json::to_vec_pretty(state).context("Failed to serialize session state")?;
+    fs::write(&path, data)
+        .await
+        .with_context(|| format!("Failed to write session state '{}'", path.display()))?;
+    Ok(())
+}
+
+pub async fn remove(download_path: &Path, blog: &str) -> Result<()> {
+    let path = session_path(download_path, blog);
+    if fs::try_exists(&path).await.unwrap_or(false) {
+        fs::remove_file(&path)
+            .await
+            .with_context(|| format!("Failed to remove session state '{}'", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Session state bound to its file; every step is written to disk right away.
+pub struct Checkpoint {
+    download_path: PathBuf,
+    state: SessionState,
+}
+
+impl Checkpoint {
+    pub async fn start(download_path: &Path, state: SessionState) -> Result<Self> {
+        save(download_path, &state).await?;
+        Ok(Self {
+            download_path: download_path.to_path_buf(),
+            state,
+        })
+    }
+
+    pub async fn post_done(&mut self, post_id: &str) -> Result<()> {
+        if self.state.is_post_completed(post_id) {
+            return Ok(());
+        }
+        self.state.completed_posts.push(post_id.to_string());
+        self.persist().await
+    }
+
+    pub async fn comments_done(&mut self, post_id: &str) -> Result<()> {
+        self.state.pending_comments.retain(|t| t.post_id != post_id);
+        self.persist().await
+    }
+
+    /// Drops the state file once the whole plan went through.
+    pub async fn finish(self) -> Result<()> {
+        remove(&self.download_path, &self.state.blog).await
+    }
+
+    async fn persist(&mut self) -> Result<()> {
+        self.state.updated_at = Utc::now().timestamp();
+        save(&self.download_path, &self.state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_base() -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let p = std::env::temp_dir().join(format!(
+            "boosty_session_state_{}_{nanos}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&p).unwrap();
+        p
+    }
+
+    fn target(post_id: &str) -> CommentTarget {
+        CommentTarget {
+            blog: "author".into(),
+            post_id: post_id.into(),
+            title: post_id.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn checkpoint_tracks_progress_and_cleans_up() {
+        let base = temp_base();
+        let state = SessionState::new(
+            "author",
+            None,
+            vec!["a".into(), "b".into(), "c".into()],
+            vec![target("a"), target("b")],
+        );
+        let mut checkpoint = Checkpoint::start(&base, state).await.unwrap();
+        checkpoint.post_done("a").await.unwrap();
+        checkpoint.post_done("b").await.unwrap();
+        checkpoint.comments_done("b").await.unwrap();
+
+        let loaded = load(&base, "author").await.unwrap().unwrap();
+        assert_eq!(loaded.completed_posts, vec!["a", "b"]);
+        assert_eq!(loaded.posts_to_fetch(), vec!["a", "c"]);
+
+        checkpoint.finish().await.unwrap();
+        assert!(load(&base, "author").await.unwrap().is_none());
+
+        let _ = fs::remove_dir_all(&base).await;
+    }
+}
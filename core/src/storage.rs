@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use boosty_api::media_content::ContentItem;
+use boosty_api::model::Post;
+use boosty_api::traits::{HasContent, IsAvailable};
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use reqwest::header::CONTENT_LENGTH;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::AppConfig;
+use crate::events::{self, EventScope};
+use crate::{
+    DownloadOptions, blog_index, download_options, log_info, log_trace, log_warn, logger,
+    media_client, throttle,
+};
+
+const MB: u64 = 1024 * 1024;
+const HEAD_CONCURRENCY: usize = 8;
+/// Files whose size is asked for; the rest are assumed to have the average size.
+const MAX_HEAD_REQUESTS: usize = 200;
+
+/// Size of each download folder with a total quota: measured by the first session, then
+/// grown by what sessions write, until [`forget_archive_sizes`].
+static ARCHIVE_SIZES: Lazy<Mutex<HashMap<PathBuf, u64>>> = Lazy::new(Default::default);
+
+/// Disk space checks and quotas.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Estimate the size of posts not downloaded yet (HEAD requests) and compare it
+    /// with the free space and quotas before downloading. Off by default, since it asks
+    /// for every new file once more.
+    pub preflight_check: bool,
+    /// Free space to leave on the disk, in MB.
+    pub reserve_mb: u64,
+    /// Largest size of one blog folder, in MB.
+    pub blog_quota_mb: Option<u64>,
+    /// Largest size of the whole download folder, in MB.
+    pub total_quota_mb: Option<u64>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            preflight_check: false,
+            reserve_mb: 512,
+            blog_quota_mb: None,
+            total_quota_mb: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    DiskSpace,
+    BlogQuota,
+    TotalQuota,
+}
+
+/// A download would not fit; stops the session instead of failing file by file.
+#[derive(Debug, Clone)]
+pub struct StorageLimitError {
+    pub kind: LimitKind,
+    pub blog: String,
+    /// Bytes still allowed under the limit.
+    pub available: u64,
+    /// Bytes the session needs (estimate), or the size of the chunk that didn't fit.
+    pub needed: u64,
+}
+
+impl fmt::Display for StorageLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (needed, available) = (format_size(self.needed), format_size(self.available));
+        match self.kind {
+            LimitKind::DiskSpace => write!(
+                f,
+                "Not enough disk space for '{}': {needed} needed, {available} free above the reserve",
+                self.blog
+            ),
+            LimitKind::BlogQuota => write!(
+                f,
+                "Quota of blog '{}' reached: {needed} needed, {available} left",
+                self.blog
+            ),
+            LimitKind::TotalQuota => write!(
+                f,
+                "Total download quota reached while downloading '{}': {needed} needed, {available} left",
+                self.blog
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StorageLimitError {}
+
+pub fn storage_limit(err: &anyhow::Error) -> Option<&StorageLimitError> {
+    err.chain().find_map(|cause| cause.downcast_ref())
+}
+
+/// Whether the error ends a run over several blogs: a full blog only stops that blog,
+/// the next one has its own quota.
+pub(crate) fn ends_run(err: &anyhow::Error) -> bool {
+    match storage_limit(err) {
+        Some(limit) => limit.kind != LimitKind::BlogQuota,
+        None => crate::is_cancelled_error(err),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * MB {
+        format!("{:.1} GB", bytes as f64 / (1024 * MB) as f64)
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    }
+}
+
+/// Bytes the session running in an event scope may still write, per limit.
+pub(crate) struct Budget {
+    blog: String,
+    disk: u64,
+    blog_quota: Option<u64>,
+    total_quota: Option<u64>,
+    /// Download folder whose cached size grows with the writes, with a total quota.
+    archive: Option<PathBuf>,
+}
+
+/// Keeps the session's budget for [`consume`] until dropped.
+pub struct BudgetGuard {
+    scope: Arc<EventScope>,
+}
+
+impl BudgetGuard {
+    fn new(budget: Budget) -> Self {
+        let scope = events::current();
+        *scope.budget.lock().unwrap() = Some(budget);
+        Self { scope }
+    }
+}
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        *self.scope.budget.lock().unwrap() = None;
+    }
+}
+
+/// Charges `bytes` about to be written to the session of the current event scope.
+pub fn consume(bytes: u64) -> Result<()> {
+    let scope = events::current();
+    let mut slot = scope.budget.lock().unwrap();
+    let Some(budget) = slot.as_mut() else {
+        return Ok(());
+    };
+    check_estimate(budget, bytes)?;
+    budget.disk -= bytes;
+    for left in [budget.blog_quota.as_mut(), budget.total_quota.as_mut()]
+        .into_iter()
+        .flatten()
+    {
+        *left -= bytes;
+    }
+    if let Some(archive) = &budget.archive
+        && let Some(size) = ARCHIVE_SIZES.lock().unwrap().get_mut(archive)
+    {
+        *size += bytes;
+    }
+    Ok(())
+}
+
+/// Makes the next session measure the download folders again, after files were deleted
+/// or moved.
+pub(crate) fn forget_archive_sizes() {
+    ARCHIVE_SIZES.lock().unwrap().clear();
+}
+
+async fn archive_size(download_path: &Path) -> Result<u64> {
+    if let Some(size) = ARCHIVE_SIZES.lock().unwrap().get(download_path) {
+        return Ok(*size);
+    }
+    let size = dir_size(download_path).await?;
+    ARCHIVE_SIZES
+        .lock()
+        .unwrap()
+        .insert(download_path.to_path_buf(), size);
+    Ok(size)
+}
+
+/// Checks the estimated size of the posts against free space and quotas and starts
+/// charging the session's writes against them.
+pub async fn start_session(
+    cfg: &AppConfig,
+    download_path: &Path,
+    blog: &str,
+    posts: &[&Post],
+    download_options: &DownloadOptions,
+    cancel_token: &CancellationToken,
+) -> Result<BudgetGuard> {
+    let storage = &cfg.storage;
+    let blog_dir = download_path.join(blog);
+
+    let disk =
+        available_space(download_path)?.saturating_sub(storage.reserve_mb.saturating_mul(MB));
+    let blog_quota = match storage.blog_quota_mb {
+        Some(quota) => Some(
+            quota
+                .saturating_mul(MB)
+                .saturating_sub(dir_size(&blog_dir).await?),
+        ),
+        None => None,
+    };
+    let total_quota = match storage.total_quota_mb {
+        Some(quota) => Some(
+            quota
+                .saturating_mul(MB)
+                .saturating_sub(archive_size(download_path).await?),
+        ),
+        None => None,
+    };
+    let budget = Budget {
+        blog: blog.to_string(),
+        disk,
+        blog_quota,
+        total_quota,
+        archive: total_quota.map(|_| download_path.to_path_buf()),
+    };
+
+    if storage.preflight_check {
+        let index = blog_index::load(download_path, blog).await?;
+        let new_posts: Vec<&Post> = posts
+            .iter()
+            .copied()
+            .filter(|p| !p.not_available() && !index.posts.contains_key(&p.id))
+            .collect();
+        if !new_posts.is_empty() {
+            let (needed, unknown) =
+                estimate_bytes(&new_posts, download_options, cancel_token).await?;
+            log_info!(
+                "{} new posts need about {}{}",
+                new_posts.len(),
+                format_size(needed),
+                if unknown > 0 {
+                    format!(" ({unknown} files of unknown size)")
+                } else {
+                    String::new()
+                }
+            );
+            check_estimate(&budget, needed)?;
+        }
+    }
+
+    Ok(BudgetGuard::new(budget))
+}
+
+fn check_estimate(budget: &Budget, needed: u64) -> Result<()> {
+    let limits = [
+        (LimitKind::DiskSpace, Some(budget.disk)),
+        (LimitKind::BlogQuota, budget.blog_quota),
+        (LimitKind::TotalQuota, budget.total_quota),
+    ];
+    for (kind, left) in limits {
+        if let Some(available) = left
+            && needed > available
+        {
+            return Err(StorageLimitError {
+                kind,
+                blog: budget.blog.clone(),
+                available,
+                needed,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Free space of the disk holding `path`, which may not exist yet.
+fn available_space(path: &Path) -> Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or(Path::new("."));
+    fs4::available_space(existing)
+        .with_context(|| format!("Failed to get free space of '{}'", existing.display()))
+}
+
+async fn dir_size(dir: &Path) -> Result<u64> {
+    if !fs::try_exists(dir).await.unwrap_or(false) {
+        return Ok(0);
+    }
+    let mut total = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut rd = fs::read_dir(&current)
+            .await
+            .with_context(|| format!("Failed to read folder '{}'", current.display()))?;
+        while let Some(entry) = rd.next_entry().await? {
+            let meta = entry.metadata().await?;
+            if meta.is_dir() {
+                pending.push(entry.path());
+            } else {
+                total += meta.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Sum of `Content-Length` of the posts' media and the number of files without one.
+///
+/// HEAD requests wait for their slot under the API request rate limit, and only the first
+/// [`MAX_HEAD_REQUESTS`] files are asked for; the others count with the average size.
+async fn estimate_bytes(
+    posts: &[&Post],
+    download_options: &DownloadOptions,
+    cancel_token: &CancellationToken,
+) -> Result<(u64, usize)> {
+    let urls: Vec<String> = posts
+        .iter()
+        .flat_map(|post| {
+            let items =
+                download_options::filter_content_items(post.extract_content(), download_options);
+            let mut urls = Vec::new();
+            media_urls(&items, &post.signed_query, &mut urls);
+            urls
+        })
+        .collect();
+
+    let Ok(client) = media_client::media_client() else {
+        return Ok((0, urls.len()));
+    };
+    let total = urls.len();
+    let sizes: Vec<Option<u64>> =
+        futures_util::stream::iter(urls.into_iter().take(MAX_HEAD_REQUESTS))
+            .map(|url| {
+                let client = client.clone();
+                async move {
+                    throttle::api_request().await;
+                    let resp = client.head(&url).send().await.ok()?;
+                    log_trace!("HEAD {} -> {}", logger::redact_url(&url), resp.status());
+                    if !resp.status().is_success() {
+                        return None;
+                    }
+                    resp.headers()
+                        .get(CONTENT_LENGTH)?
+                        .to_str()
+                        .ok()?
+                        .parse()
+                        .ok()
+                }
+            })
+            .buffer_unordered(HEAD_CONCURRENCY)
+            .take_until(cancel_token.cancelled())
+            .collect()
+            .await;
+    crate::ensure_not_cancelled(cancel_token)?;
+
+    let unknown = sizes.iter().filter(|s| s.is_none()).count();
+    if unknown == sizes.len() && !sizes.is_empty() {
+        log_warn!("Could not get the size of any file, the estimate is incomplete");
+    }
+    let known: Vec<u64> = sizes.into_iter().flatten().collect();
+    let mut needed: u64 = known.iter().sum();
+    let skipped = total.saturating_sub(MAX_HEAD_REQUESTS);
+    if skipped > 0 && !known.is_empty() {
+        needed += needed / known.len() as u64 * skipped as u64;
+        log_trace!("Size of {skipped} more files estimated from the average");
+    }
+    Ok((needed, unknown))
+}
+
+fn media_urls(items: &[ContentItem], signed_query: &str, out: &mut Vec<String>) {
+    for item in items {
+        match item {
+            ContentItem::Image { url, .. } | ContentItem::OkVideo { url, .. } => {
+                out.push(url.clone())
+            }
+            ContentItem::Audio { url, .. } | ContentItem::File { url, .. } => {
+                out.push(format!("{url}{signed_query}"))
+            }
+            ContentItem::Smile { small_url, .. } => out.push(small_url.clone()),
+            ContentItem::List { items, .. } => {
+                for group in items {
+                    media_urls(group, signed_query, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn budget_stops_at_the_tightest_limit() {
+        let budget = Budget {
+            blog: "author".into(),
+            disk: 100 * MB,
+            blog_quota: Some(10 * MB),
+            total_quota: None,
+            archive: None,
+        };
+        assert!(check_estimate(&budget, 5 * MB).is_ok());
+        let err = check_estimate(&budget, 20 * MB).unwrap_err();
+        let limit = storage_limit(&err).unwrap();
+        assert_eq!(limit.kind, LimitKind::BlogQuota);
+        assert!(err.to_string().contains("author"));
+
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        events::with_sink(Arc::new(tx), async {
+            let guard = BudgetGuard::new(budget);
+            assert!(consume(6 * MB).is_ok());
+            let err = consume(6 * MB).unwrap_err();
+            assert_eq!(storage_limit(&err).unwrap().kind, LimitKind::BlogQuota);
+            drop(guard);
+            assert!(consume(u64::MAX).is_ok());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn archive_size_grows_with_the_writes() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root =
+            std::env::temp_dir().join(format!("boosty_storage_{}_{nanos}", std::process::id()));
+        fs::create_dir_all(root.join("author")).await.unwrap();
+        fs::write(root.join("author").join("a.jpg"), vec![0; 100])
+            .await
+            .unwrap();
+        assert_eq!(archive_size(&root).await.unwrap(), 100);
+
+        let budget = Budget {
+            blog: "author".into(),
+            disk: u64::MAX,
+            blog_quota: None,
+            total_quota: Some(MB),
+            archive: Some(root.clone()),
+        };
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        events::with_sink(Arc::new(tx), async {
+            let _guard = BudgetGuard::new(budget);
+            consume(50).unwrap();
+        })
+        .await;
+        assert_eq!(archive_size(&root).await.unwrap(), 150);
+
+        ARCHIVE_SIZES.lock().unwrap().remove(&root);
+        assert_eq!(archive_size(&root).await.unwrap(), 100);
+        ARCHIVE_SIZES.lock().unwrap().remove(&root);
+        let _ = fs::remove_dir_all(&root).await;
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use boosty_api::api_client::ApiClient;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio_util::sync::CancellationToken;
+
+use crate::account::{self, Subscription};
+use crate::config::AppConfig;
+use crate::parser::BoostyUrl;
+use crate::paths;
+use crate::{DownloadOptions, auth, log_error, log_info, log_warn, menu_handler};
+
+const SUBSCRIPTIONS_FILE: &str = "subscriptions.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscribedBlog {
+    pub blog: String,
+    pub title: String,
+    pub level_name: String,
+    pub added_at: i64,
+    #[serde(default)]
+    pub last_synced_at: Option<i64>,
+}
+
+/// Subscribed blogs per auth profile, as of the last sync.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubscriptionList {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Vec<SubscribedBlog>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubscriptionsResult {
+    pub blogs: usize,
+    pub posts: usize,
+    pub new_blogs: Vec<String>,
+    pub failed_blogs: Vec<String>,
+}
+
+impl SubscriptionList {
+    /// Replaces the profile's list with the fetched subscriptions; returns the blogs that are new.
+    fn merge(&mut self, profile: &str, fetched: Vec<Subscription>, now: i64) -> Vec<String> {
+        let known = self.profiles.remove(profile).unwrap_or_default();
+        let mut new_blogs = Vec::new();
+        let merged = fetched
+            .into_iter()
+            .map(|sub| match known.iter().find(|b| b.blog == sub.blog) {
+                Some(existing) => SubscribedBlog {
+                    title: sub.blog_title,
+                    level_name: sub.level_name,
+                    ..existing.clone()
+                },
+                None => {
+                    new_blogs.push(sub.blog.clone());
+                    SubscribedBlog {
+                        blog: sub.blog,
+                        title: sub.blog_title,
+                        level_name: sub.level_name,
+                        added_at: now,
+                        last_synced_at: None,
+                    }
+                }
+            })
+            .collect();
+        self.profiles.insert(profile.to_string(), merged);
+        new_blogs
+    }
+}
+
+fn list_path() -> Result<PathBuf> {
+    paths::app_file_path(SUBSCRIPTIONS_FILE)
+}
+
+pub async fn load_list() -> Result<SubscriptionList> {
+    let path = list_path()?;
+    if !fs::try_exists(&path).await.unwrap_or(false) {
+        return Ok(SubscriptionList::default());
+    }
+    let data = fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read subscriptions file '{}'", path.display()))?;
+    serde_json::from_slice(&data)
+        .with_context(|| format!("Failed to parse subscriptions file '{}'", path.display()))
+}
+
+async fn save_list(list: &SubscriptionList) -> Result<()> {
+    let path = list_path()?;
+    let data = serde_json::to_vec_pretty(list).context("Failed to serialize subscriptions")?;
+    fs::write(&path, data)
+        .await
+        .with_context(|| format!("Failed to write subscriptions file '{}'", path.display()))
+}
+
+/// Downloads every blog the active profile is subscribed to.
+///
+/// The subscription list is refreshed from the API first and stored, so blogs subscribed
+/// to since the last run are picked up; if the API can't be reached the stored list is used.
+pub async fn download_all(
+    client: &ApiClient,
+    cfg: &AppConfig,
+    download_options: DownloadOptions,
+    cancel_token: &CancellationToken,
+) -> Result<SubscriptionsResult> {
+    let profile = cfg.active_profile.clone();
+    auth::use_profile(client, &profile).await?;
+
+    let mut list = load_list().await?;
+    let mut result = SubscriptionsResult::default();
+    match account::fetch_subscriptions(client, &cfg.proxy).await {
+        Ok(fetched) => {
+            result.new_blogs = list.merge(&profile, fetched, Utc::now().timestamp());
+            save_list(&list).await?;
+        }
+        Err(e) => log_warn!("Failed to fetch subscriptions, using the stored list: {e:#}"),
+    }
+
+    let blogs: Vec<String> = list
+        .profiles
+        .get(&profile)
+        .map(|blogs| blogs.iter().map(|b| b.blog.clone()).collect())
+        .unwrap_or_default();
+    if blogs.is_empty() {
+        log_info!("No subscriptions found for profile '{profile}'");
+        return Ok(result);
+    }
+    log_info!("Downloading {} subscribed blogs...", blogs.len());
+
+    for blog in blogs {
+        crate::pause::wait_if_paused(cancel_token).await?;
+        log_info!("Processing subscription: {blog}");
+
+        match menu_handler::download_url(
+            client,
+            cfg,
+            &BoostyUrl::Blog(blog.clone()),
+            None,
+            download_options.clone(),
+            false,
+            cancel_token,
+        )
+        .await
+        {
+            Ok(report) => {
+                result.posts += report.posts_downloaded as usize;
+                result.blogs += 1;
+                mark_synced(&profile, &blog).await;
+            }
+            Err(e) => {
+                if crate::storage::ends_run(&e) {
+                    return Err(e);
+                }
+                log_error!("Failed to download subscription '{blog}': {e:#}");
+                result.failed_blogs.push(blog);
+            }
+        }
+    }
+
+    log_info!(
+        "Subscriptions done: {} blogs, {} posts, {} failed",
+        result.blogs,
+        result.posts,
+        result.failed_blogs.len()
+    );
+    Ok(result)
+}
+
+async fn mark_synced(profile: &str, blog: &str) {
+    let update = async {
+        let mut list = load_list().await?;
+        if let Some(entry) = list
+            .profiles
+            .get_mut(profile)
+            .and_then(|blogs| blogs.iter_mut().find(|b| b.blog == blog))
+        {
+            entry.last_synced_at = Some(Utc::now().timestamp());
+        }
+        save_list(&list).await
+    };
+    if let Err(e) = update.await {
+        log_warn!("{e:#}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub(blog: &str, level: &str) -> Subscription {
+        Subscription {
+            blog: blog.to_string(),
+            blog_title: blog.to_uppercase(),
+            level_name: level.to_string(),
+            level_price: 100,
+        }
+    }
+
+    #[test]
+    fn merge_reports_new_blogs_and_keeps_history() {
+        let mut list = SubscriptionList::default();
+        let new = list.merge("default", vec![sub("a", "basic")], 10);
+        assert_eq!(new, vec!["a"]);
+        list.profiles.get_mut("default").unwrap()[0].last_synced_at = Some(20);
+
+        let new = list.merge("default", vec![sub("a", "gold"), sub("b", "basic")], 30);
+        assert_eq!(new, vec!["b"]);
+        let blogs = &list.profiles["default"];
+        assert_eq!(blogs[0].added_at, 10);
+        assert_eq!(blogs[0].last_synced_at, Some(20));
+        assert_eq!(blogs[0].level_name, "gold");
+        assert_eq!(blogs[1].added_at, 30);
+    }
+}
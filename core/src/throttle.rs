@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::context;
+
+/// Download speed and API request rate limits, shared by everything running in one context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThrottleConfig {
+    /// Cap on the total download speed of media files, bytes per second.
+    pub download_bytes_per_sec: Option<u64>,
+    /// Cap on Boosty API requests per minute; the size requests of the storage preflight
+    /// check count too.
+    pub api_requests_per_minute: Option<u32>,
+    /// Random extra delay of up to this many milliseconds before a rate-limited API request.
+    pub api_jitter_ms: u64,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            download_bytes_per_sec: None,
+            api_requests_per_minute: None,
+            api_jitter_ms: 500,
+        }
+    }
+}
+
+/// Hands out evenly spaced start times, each taking `cost` of the schedule.
+struct Pacer {
+    next: Instant,
+}
+
+impl Pacer {
+    fn reserve(&mut self, now: Instant, cost: Duration) -> Instant {
+        let start = self.next.max(now);
+        self.next = start + cost;
+        start
+    }
+}
+
+/// Limits and schedules of one [`Context`](crate::context::Context).
+pub(crate) struct Throttle {
+    config: ThrottleConfig,
+    downloads: Pacer,
+    api: Pacer,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            config: ThrottleConfig::default(),
+            downloads: Pacer { next: now },
+            api: Pacer { next: now },
+        }
+    }
+}
+
+/// Applies the limits of `cfg` to the downloads and API calls of the current context
+/// made from now on.
+pub fn configure(cfg: &ThrottleConfig) {
+    context::current().throttle.lock().unwrap().config = cfg.clone();
+}
+
+/// Waits until `bytes` just read from a download fit under the speed cap.
+pub async fn download_chunk(bytes: u64) {
+    let context = context::current();
+    let start = {
+        let mut throttle = context.throttle.lock().unwrap();
+        let Some(rate) = throttle.config.download_bytes_per_sec.filter(|r| *r > 0) else {
+            return;
+        };
+        let cost = Duration::from_secs_f64(bytes as f64 / rate as f64);
+        throttle.downloads.reserve(Instant::now(), cost)
+    };
+    tokio::time::sleep_until(start).await;
+}
+
+/// Waits for the next API request slot, plus a random jitter.
+pub async fn api_request() {
+    let context = context::current();
+    let start = {
+        let mut throttle = context.throttle.lock().unwrap();
+        let Some(per_minute) = throttle.config.api_requests_per_minute.filter(|r| *r > 0) else {
+            return;
+        };
+        let jitter = match throttle.config.api_jitter_ms {
+            0 => Duration::ZERO,
+            max => Duration::from_millis(fastrand::u64(0..=max)),
+        };
+        let interval = Duration::from_secs(60) / per_minute;
+        throttle.api.reserve(Instant::now(), interval) + jitter
+    };
+    tokio::time::sleep_until(start).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pacer_spaces_requests_by_their_cost() {
+        let now = Instant::now();
+        let mut pacer = Pacer { next: now };
+        let second = Duration::from_secs(1);
+        assert_eq!(pacer.reserve(now, second), now);
+        assert_eq!(pacer.reserve(now, second), now + second);
+        assert_eq!(pacer.reserve(now, second), now + second * 2);
+
+        // Idle time is not saved up for a burst later.
+        let later = now + second * 10;
+        assert_eq!(pacer.reserve(later, second), later);
+    }
+}
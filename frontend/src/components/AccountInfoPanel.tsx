@@ -0,0 +1,37 @@
+import { TOKEN_STATUS_LABEL, type AccountInfo } from '@/types/account';
+
+interface AccountInfoPanelProps {
+  info: AccountInfo;
+}
+
+export function AccountInfoPanel({ info }: AccountInfoPanelProps) {
+  const expires = info.token_expires_at
+    ? new Date(info.token_expires_at * 1000).toLocaleString()
+    : 'неизвестно';
+
+  return (
+    <div className="ml-5 flex flex-col gap-1 rounded-lg border border-(--border) bg-(--secondary-bg) p-2 text-sm">
+      <span>Профиль: {info.profile}</span>
+      <span>Токен: {TOKEN_STATUS_LABEL[info.token_status]}</span>
+      {info.refresh_error && (
+        <span className="text-red-500">
+          Не удалось обновить токен: {info.refresh_error}
+        </span>
+      )}
+      <span>Истекает: {expires}</span>
+      {info.user && (
+        <span>
+          Аккаунт: {info.user.name} (id {info.user.id})
+        </span>
+      )}
+      {info.user && info.subscriptions.length === 0 && (
+        <span className="text-(--meta-text)">Активных подписок нет</span>
+      )}
+      {info.subscriptions.map((sub) => (
+        <span key={sub.blog} className="text-(--meta-text)">
+          {sub.blog_title || sub.blog}: {sub.level_name}, {sub.level_price} ₽
+        </span>
+      ))}
+    </div>
+  );
+}
@@ -0,0 +1,209 @@
+import { useState } from 'react';
+import { Save } from 'lucide-react';
+import { Button } from './Button';
+import { Checkbox } from './Checkbox';
+import { ConfigLabel } from './ConfigLabel';
+import { DownloadOptionsPanel } from './DownloadOptionsPanel';
+import { Input } from './Input';
+import { Label } from './Label';
+import type { BlogSettings, OutputFormat } from '@/types/blogSettings';
+import { DEFAULT_DOWNLOAD_OPTIONS } from '@/types/downloadOptions';
+
+interface BlogSettingsPanelProps {
+  settings: BlogSettings;
+  disabled: boolean;
+  onSave: (settings: BlogSettings) => void;
+}
+
+const selectClassName =
+  'rounded-lg border border-(--border) bg-(--secondary-bg) p-2 text-(--text) focus:outline-none focus:ring-(--button-bg) focus:ring-2';
+
+const FORMATS: { key: OutputFormat; label: string }[] = [
+  { key: 'html', label: 'index.html' },
+  { key: 'text', label: 'post.txt' },
+];
+
+function parseLimit(value: string): number | null {
+  const n = Number.parseInt(value, 10);
+  return Number.isNaN(n) || n <= 0 ? null : n;
+}
+
+function parseWords(value: string): string[] {
+  return value
+    .split(',')
+    .map((word) => word.trim())
+    .filter(Boolean);
+}
+
+export function BlogSettingsPanel({
+  settings,
+  disabled,
+  onSave,
+}: BlogSettingsPanelProps) {
+  const [draft, setDraft] = useState(settings);
+  const [includes, setIncludes] = useState(
+    settings.filter.title_includes.join(', '),
+  );
+  const [excludes, setExcludes] = useState(
+    settings.filter.title_excludes.join(', '),
+  );
+  const [tags, setTags] = useState(settings.filter.tags.join(', '));
+
+  const formats: OutputFormat[] =
+    draft.output_formats.length > 0 ? draft.output_formats : ['html'];
+
+  const toggleFormat = (format: OutputFormat) => {
+    const next = formats.includes(format)
+      ? formats.filter((f) => f !== format)
+      : [...formats, format];
+    setDraft({ ...draft, output_formats: next });
+  };
+
+  const limitInput = (
+    label: string,
+    key: 'posts_limit' | 'comments_limit' | 'reply_limit' | 'quota_mb',
+  ) => (
+    <Label>
+      <ConfigLabel label={label} />
+      <Input
+        value={draft[key] ?? ''}
+        placeholder="как в настройках"
+        disabled={disabled}
+        onChange={(value) =>
+          setDraft({ ...draft, [key]: parseLimit(String(value)) })
+        }
+        className="ml-2 flex-1"
+      />
+    </Label>
+  );
+
+  const save = () =>
+    onSave({
+      ...draft,
+      output_formats: formats,
+      filter: {
+        ...draft.filter,
+        title_includes: parseWords(includes),
+        title_excludes: parseWords(excludes),
+        tags: parseWords(tags),
+      },
+    });
+
+  return (
+    <div className="mt-3 flex flex-col gap-3 rounded-md border border-(--border) p-3">
+      <Label>
+        <Checkbox
+          checked={draft.download_options !== null}
+          disabled={disabled}
+          onCheckedChange={(checked) =>
+            setDraft({
+              ...draft,
+              download_options: checked ? [...DEFAULT_DOWNLOAD_OPTIONS] : null,
+            })
+          }
+        />
+        <span className="ml-2">Свои типы контента для этого блога</span>
+      </Label>
+      {draft.download_options !== null && (
+        <DownloadOptionsPanel
+          value={draft.download_options}
+          onChange={(value) => setDraft({ ...draft, download_options: value })}
+          disabled={disabled}
+        />
+      )}
+
+      <Label>
+        <ConfigLabel label="Комментарии:" />
+        <select
+          value={
+            draft.comments_enabled === null
+              ? 'global'
+              : String(draft.comments_enabled)
+          }
+          disabled={disabled}
+          onChange={(e) =>
+            setDraft({
+              ...draft,
+              comments_enabled:
+                e.target.value === 'global' ? null : e.target.value === 'true',
+            })
+          }
+          className={`ml-2 flex-1 ${selectClassName}`}
+        >
+          <option value="global">как в настройках</option>
+          <option value="true">скачивать</option>
+          <option value="false">не скачивать</option>
+        </select>
+      </Label>
+
+      {limitInput('Лимит постов:', 'posts_limit')}
+      {limitInput('Лимит комментариев:', 'comments_limit')}
+      {limitInput('Лимит ответов:', 'reply_limit')}
+      {limitInput('Квота папки, МБ:', 'quota_mb')}
+
+      <Label>
+        <ConfigLabel label="В названии есть:" />
+        <Input
+          value={includes}
+          placeholder="слова через запятую"
+          disabled={disabled}
+          onChange={(value) => setIncludes(String(value))}
+          className="ml-2 flex-1"
+        />
+      </Label>
+      <Label>
+        <ConfigLabel label="В названии нет:" />
+        <Input
+          value={excludes}
+          placeholder="слова через запятую"
+          disabled={disabled}
+          onChange={(value) => setExcludes(String(value))}
+          className="ml-2 flex-1"
+        />
+      </Label>
+      <Label>
+        <ConfigLabel label="Теги:" />
+        <Input
+          value={tags}
+          placeholder="теги через запятую"
+          disabled={disabled}
+          onChange={(value) => setTags(String(value))}
+          className="ml-2 flex-1"
+        />
+      </Label>
+
+      <div className="flex flex-wrap items-center gap-4">
+        <Label>
+          <Checkbox
+            checked={draft.filter.skip_paid}
+            disabled={disabled}
+            onCheckedChange={(checked) =>
+              setDraft({
+                ...draft,
+                filter: { ...draft.filter, skip_paid: checked === true },
+              })
+            }
+          />
+          <span className="ml-2">Пропускать платные посты</span>
+        </Label>
+        {FORMATS.map(({ key, label }) => (
+          <Label key={key}>
+            <Checkbox
+              checked={formats.includes(key)}
+              disabled={disabled}
+              onCheckedChange={() => toggleFormat(key)}
+            />
+            <span className="ml-2">{label}</span>
+          </Label>
+        ))}
+      </div>
+
+      <Button className="self-start" disabled={disabled} onClick={save}>
+        <div className="flex items-center gap-2">
+          <Save size={16} />
+          Сохранить
+        </div>
+      </Button>
+    </div>
+  );
+}
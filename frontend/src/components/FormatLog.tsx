@@ -13,6 +13,10 @@ export function formatLog(msg: LogMessage) {
     case 'Info':
       color = 'text-blue-500';
       break;
+    case 'Debug':
+    case 'Trace':
+      color = 'text-gray-500';
+      break;
     default:
       color = 'text-blue-500';
       break;
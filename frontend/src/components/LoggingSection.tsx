@@ -0,0 +1,48 @@
+import { FileDown } from 'lucide-react';
+import { Button } from './Button';
+import { ConfigLabel } from './ConfigLabel';
+import { HintIcon } from './HintIcon';
+import { Label } from './Label';
+import type { LogVerbosity } from '@/types/logLevel';
+
+interface LoggingSectionProps {
+  verbosity: LogVerbosity;
+  disabled: boolean;
+  onChange: (verbosity: LogVerbosity) => void;
+  onExport: () => void;
+}
+
+const selectClassName =
+  'rounded-lg border border-(--border) bg-(--secondary-bg) p-2 text-(--text) focus:outline-none focus:ring-(--button-bg) focus:ring-2';
+
+export function LoggingSection({
+  verbosity,
+  disabled,
+  onChange,
+  onExport,
+}: LoggingSectionProps) {
+  return (
+    <Label>
+      <HintIcon text="Журнал пишется в файл boosty_downloader.log в папке данных приложения. Отладка добавляет коды ответов, адреса запросов (без токенов) и время загрузки, трассировка — ещё и каждый запрос к API. Файл диагностики собирает версию, настройки без токенов и журналы для отчёта об ошибке" />
+      <ConfigLabel label="Подробность журнала:" />
+      <div className="ml-2 flex flex-1 gap-2">
+        <select
+          value={verbosity}
+          disabled={disabled}
+          onChange={(e) => onChange(e.target.value as LogVerbosity)}
+          className={`flex-1 ${selectClassName}`}
+        >
+          <option value="normal">обычная</option>
+          <option value="debug">отладка</option>
+          <option value="trace">трассировка</option>
+        </select>
+        <Button className="px-3" disabled={disabled} onClick={onExport}>
+          <div className="flex items-center gap-2">
+            <FileDown size={16} />
+            Экспорт диагностики
+          </div>
+        </Button>
+      </div>
+    </Label>
+  );
+}
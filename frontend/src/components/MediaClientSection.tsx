@@ -0,0 +1,139 @@
+import { useState } from 'react';
+import { ConfigLabel } from './ConfigLabel';
+import { HintIcon } from './HintIcon';
+import { Input } from './Input';
+import { Label } from './Label';
+import { Switch } from './Switch';
+import type { MediaClientConfig } from '@/types/mediaClient';
+
+interface MediaClientSectionProps {
+  media: MediaClientConfig;
+  disabled: boolean;
+  onChange: (media: MediaClientConfig) => void;
+}
+
+function formatHeaders(headers: Record<string, string>): string {
+  return Object.entries(headers)
+    .map(([name, value]) => `${name}: ${value}`)
+    .join('; ');
+}
+
+function parseHeaders(value: string): Record<string, string> {
+  const headers: Record<string, string> = {};
+  for (const pair of value.split(';')) {
+    const colon = pair.indexOf(':');
+    if (colon <= 0) continue;
+    headers[pair.slice(0, colon).trim()] = pair.slice(colon + 1).trim();
+  }
+  return headers;
+}
+
+function parseList(value: string): string[] {
+  return value
+    .split(',')
+    .map((item) => item.trim())
+    .filter(Boolean);
+}
+
+export function MediaClientSection({
+  media,
+  disabled,
+  onChange,
+}: MediaClientSectionProps) {
+  const [headers, setHeaders] = useState(formatHeaders(media.extra_headers));
+  const [certificates, setCertificates] = useState(
+    media.ca_certificates.join(', '),
+  );
+
+  const timeoutInput = (
+    label: string,
+    hint: string,
+    key: 'connect_timeout_secs' | 'read_timeout_secs',
+  ) => (
+    <Label>
+      <HintIcon text={hint} />
+      <ConfigLabel label={label} />
+      <Input
+        type="number"
+        value={media[key]}
+        disabled={disabled}
+        onChange={(value) =>
+          onChange({ ...media, [key]: Math.max(1, Number(value)) })
+        }
+        className="ml-2 flex-1"
+      />
+    </Label>
+  );
+
+  return (
+    <>
+      {timeoutInput(
+        'Таймаут соединения, с:',
+        'Сколько ждать подключения к серверу с файлами',
+        'connect_timeout_secs',
+      )}
+      {timeoutInput(
+        'Таймаут чтения, с:',
+        'Сколько ждать следующую часть файла, прежде чем считать попытку загрузки неудачной',
+        'read_timeout_secs',
+      )}
+
+      <Label>
+        <HintIcon text="User-Agent, с которым скачиваются файлы" />
+        <ConfigLabel label="User-Agent:" />
+        <Input
+          value={media.user_agent}
+          disabled={disabled}
+          onChange={(value) => onChange({ ...media, user_agent: String(value) })}
+          className="ml-2 flex-1 font-mono"
+        />
+      </Label>
+
+      <Label>
+        <HintIcon text="Дополнительные заголовки запросов за файлами в виде «Имя: значение», через точку с запятой" />
+        <ConfigLabel label="Заголовки:" />
+        <Input
+          value={headers}
+          placeholder="Referer: https://boosty.to/"
+          disabled={disabled}
+          onChange={(value) => {
+            setHeaders(String(value));
+            onChange({ ...media, extra_headers: parseHeaders(String(value)) });
+          }}
+          className="ml-2 flex-1 font-mono"
+        />
+      </Label>
+
+      <Label>
+        <HintIcon text="Пути к PEM-файлам с дополнительными корневыми сертификатами (например, корпоративного прокси), через запятую" />
+        <ConfigLabel label="Сертификаты CA:" />
+        <Input
+          value={certificates}
+          placeholder="не заданы"
+          disabled={disabled}
+          onChange={(value) => {
+            setCertificates(String(value));
+            onChange({ ...media, ca_certificates: parseList(String(value)) });
+          }}
+          className="ml-2 flex-1 font-mono"
+        />
+      </Label>
+
+      <div className="flex items-center">
+        <HintIcon text="Разрешить HTTP/2, если сервер его поддерживает. Выключите, если загрузки обрываются" />
+        <div className="flex flex-1 items-center gap-2">
+          <ConfigLabel label="HTTP/2 для файлов:" className="w-auto" />
+          <span className="text-(--meta-text)">
+            {media.http2 ? 'Включено' : 'Выключено'}
+          </span>
+        </div>
+        <Switch
+          id="media-http2"
+          checked={media.http2}
+          disabled={disabled}
+          onCheckedChange={(checked) => onChange({ ...media, http2: checked })}
+        />
+      </div>
+    </>
+  );
+}
@@ -0,0 +1,86 @@
+import { FolderSync } from 'lucide-react';
+import { Button } from './Button';
+import { ConfigLabel } from './ConfigLabel';
+import { HintIcon } from './HintIcon';
+import { Input } from './Input';
+import { Label } from './Label';
+import type { CollisionPolicy, NamingConfig } from '@/types/naming';
+
+interface NamingSectionProps {
+  naming: NamingConfig;
+  disabled: boolean;
+  onChange: (naming: NamingConfig) => void;
+  onRename: () => void;
+}
+
+const selectClassName =
+  'rounded-lg border border-(--border) bg-(--secondary-bg) p-2 text-(--text) focus:outline-none focus:ring-(--button-bg) focus:ring-2';
+
+const PLACEHOLDERS =
+  '{blog}, {author}, {date}, {year}, {month}, {day}, {time}, {title}, {id}, {tag}';
+
+const TEMPLATES: { key: keyof Omit<NamingConfig, 'collision'>; label: string }[] =
+  [
+    { key: 'post_folder', label: 'Папка поста:' },
+    { key: 'image_file', label: 'Изображения:' },
+    { key: 'video_file', label: 'Видео:' },
+    { key: 'media_file', label: 'Аудио и файлы:' },
+  ];
+
+export function NamingSection({
+  naming,
+  disabled,
+  onChange,
+  onRename,
+}: NamingSectionProps) {
+  return (
+    <>
+      {TEMPLATES.map(({ key, label }) => (
+        <Label key={key}>
+          <HintIcon
+            text={
+              key === 'post_folder'
+                ? `Шаблон папки поста внутри папки блога, «/» создаёт вложенные папки. Поля: ${PLACEHOLDERS}`
+                : `Шаблон имени файла без расширения. Поля: ${PLACEHOLDERS}, {post_id}, {index} (номер в посте), {filename} (исходное имя); {id} — id файла`
+            }
+          />
+          <ConfigLabel label={label} />
+          <Input
+            type="text"
+            value={naming[key]}
+            disabled={disabled}
+            onChange={(value) => onChange({ ...naming, [key]: String(value) })}
+            className="ml-2 flex-1 font-mono"
+          />
+        </Label>
+      ))}
+
+      <Label>
+        <HintIcon text="Что делать, если у двух постов или файлов получилось одинаковое имя" />
+        <ConfigLabel label="Совпадение имён:" />
+        <div className="ml-2 flex flex-1 gap-2">
+          <select
+            value={naming.collision}
+            disabled={disabled}
+            onChange={(e) =>
+              onChange({
+                ...naming,
+                collision: e.target.value as CollisionPolicy,
+              })
+            }
+            className={`flex-1 ${selectClassName}`}
+          >
+            <option value="suffix">добавить номер: имя (2)</option>
+            <option value="skip">пропустить</option>
+          </select>
+          <Button className="px-3" disabled={disabled} onClick={onRename}>
+            <div className="flex items-center gap-2">
+              <FolderSync size={16} />
+              Переименовать скачанное
+            </div>
+          </Button>
+        </div>
+      </Label>
+    </>
+  );
+}
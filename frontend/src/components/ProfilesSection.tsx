@@ -0,0 +1,136 @@
+import { useState } from 'react';
+import { Plus, Trash2 } from 'lucide-react';
+import { Button } from './Button';
+import { ConfigLabel } from './ConfigLabel';
+import { HintIcon } from './HintIcon';
+import { Input } from './Input';
+import { Label } from './Label';
+import type { AppConfig } from '@/types/config';
+
+interface ProfilesSectionProps {
+  config: AppConfig;
+  disabled: boolean;
+  onSwitch: (name: string) => void;
+  onDelete: (name: string) => void;
+  onBlogProfileChange: (blog: string, profile: string | null) => void;
+}
+
+const selectClassName =
+  'rounded-lg border border-(--border) bg-(--secondary-bg) p-2 text-(--text) focus:outline-none focus:ring-(--button-bg) focus:ring-2';
+
+export function ProfilesSection({
+  config,
+  disabled,
+  onSwitch,
+  onDelete,
+  onBlogProfileChange,
+}: ProfilesSectionProps) {
+  const [newProfile, setNewProfile] = useState('');
+  const [newBlog, setNewBlog] = useState('');
+
+  const names = [config.active_profile, ...Object.keys(config.profiles)].sort();
+
+  return (
+    <>
+      <Label>
+        <HintIcon text="Набор токенов для отдельного аккаунта Boosty. Поля токенов выше относятся к активному профилю" />
+        <ConfigLabel label="Профиль:" />
+        <div className="ml-2 flex flex-1 gap-2">
+          <select
+            value={config.active_profile}
+            disabled={disabled}
+            onChange={(e) => onSwitch(e.target.value)}
+            className={`flex-1 ${selectClassName}`}
+          >
+            {names.map((name) => (
+              <option key={name} value={name}>
+                {name}
+              </option>
+            ))}
+          </select>
+          <Input
+            value={newProfile}
+            onChange={(value) => setNewProfile(String(value))}
+            placeholder="Новый профиль"
+            className="flex-1"
+          />
+          <Button
+            className="px-3"
+            disabled={disabled || !newProfile.trim()}
+            onClick={() => {
+              onSwitch(newProfile);
+              setNewProfile('');
+            }}
+          >
+            <Plus size={16} />
+          </Button>
+        </div>
+      </Label>
+
+      {Object.keys(config.profiles).map((name) => (
+        <Label key={name} className="ml-5">
+          <ConfigLabel label={name} />
+          <span className="flex-1 text-(--meta-text)">
+            {config.profiles[name].access_token ||
+            config.profiles[name].refresh_token
+              ? 'Токены заданы'
+              : 'Без токенов'}
+          </span>
+          <Button
+            className="px-3"
+            disabled={disabled}
+            onClick={() => onDelete(name)}
+          >
+            <Trash2 size={16} />
+          </Button>
+        </Label>
+      ))}
+
+      <Label>
+        <HintIcon text="Блоги, которые скачиваются и проверяются через другой профиль. Остальные блоги используют активный профиль" />
+        <ConfigLabel label="Профили блогов:" />
+        <div className="ml-2 flex flex-1 gap-2">
+          <Input
+            value={newBlog}
+            onChange={(value) => setNewBlog(String(value))}
+            placeholder="Имя блога"
+            className="flex-1"
+          />
+          <Button
+            className="px-3"
+            disabled={!newBlog.trim()}
+            onClick={() => {
+              onBlogProfileChange(newBlog.trim(), config.active_profile);
+              setNewBlog('');
+            }}
+          >
+            <Plus size={16} />
+          </Button>
+        </div>
+      </Label>
+
+      {Object.entries(config.blog_profiles).map(([blog, profile]) => (
+        <Label key={blog} className="ml-5">
+          <ConfigLabel label={blog} />
+          <select
+            value={profile}
+            onChange={(e) => onBlogProfileChange(blog, e.target.value)}
+            className={`flex-1 ${selectClassName}`}
+          >
+            {names.map((name) => (
+              <option key={name} value={name}>
+                {name}
+              </option>
+            ))}
+          </select>
+          <Button
+            className="ml-2 px-3"
+            onClick={() => onBlogProfileChange(blog, null)}
+          >
+            <Trash2 size={16} />
+          </Button>
+        </Label>
+      ))}
+    </>
+  );
+}
@@ -0,0 +1,123 @@
+import { useState } from 'react';
+import { ConfigLabel } from './ConfigLabel';
+import { HintIcon } from './HintIcon';
+import { Input } from './Input';
+import { Label } from './Label';
+import { Switch } from './Switch';
+import type { ProxyConfig, ProxyScheme } from '@/types/proxy';
+
+interface ProxySectionProps {
+  proxy: ProxyConfig;
+  disabled: boolean;
+  onChange: (proxy: ProxyConfig) => void;
+}
+
+const selectClassName =
+  'rounded-lg border border-(--border) bg-(--secondary-bg) p-2 text-(--text) focus:outline-none focus:ring-(--button-bg) focus:ring-2';
+
+function parseList(value: string): string[] {
+  return value
+    .split(',')
+    .map((item) => item.trim())
+    .filter(Boolean);
+}
+
+export function ProxySection({ proxy, disabled, onChange }: ProxySectionProps) {
+  const [noProxy, setNoProxy] = useState(proxy.no_proxy.join(', '));
+  const fieldsDisabled = disabled || !proxy.enabled;
+
+  return (
+    <>
+      <div className="flex items-center">
+        <HintIcon text="Все запросы к API Boosty и загрузка файлов идут через прокси. После изменения настроек их нужно сохранить" />
+        <div className="flex flex-1 items-center gap-2">
+          <ConfigLabel label="Прокси:" className="w-auto" />
+          <span className="text-(--meta-text)">
+            {proxy.enabled ? 'Включено' : 'Выключено'}
+          </span>
+        </div>
+        <Switch
+          id="proxy-enabled"
+          checked={proxy.enabled}
+          disabled={disabled}
+          onCheckedChange={(checked) => onChange({ ...proxy, enabled: checked })}
+        />
+      </div>
+
+      <Label>
+        <HintIcon text="Тип прокси. Для SOCKS5 имена сайтов разрешает сам прокси" />
+        <ConfigLabel label="Адрес прокси:" />
+        <div className="ml-2 flex flex-1 gap-2">
+          <select
+            value={proxy.scheme}
+            disabled={fieldsDisabled}
+            onChange={(e) =>
+              onChange({ ...proxy, scheme: e.target.value as ProxyScheme })
+            }
+            className={selectClassName}
+          >
+            <option value="http">HTTP</option>
+            <option value="https">HTTPS</option>
+            <option value="socks5">SOCKS5</option>
+          </select>
+          <Input
+            value={proxy.host}
+            placeholder="хост"
+            disabled={fieldsDisabled}
+            onChange={(value) => onChange({ ...proxy, host: String(value) })}
+            className="flex-1"
+          />
+          <Input
+            type="number"
+            value={proxy.port}
+            disabled={fieldsDisabled}
+            onChange={(value) =>
+              onChange({
+                ...proxy,
+                port: Math.min(65535, Math.max(0, Number(value))),
+              })
+            }
+            className="w-28"
+          />
+        </div>
+      </Label>
+
+      <Label>
+        <HintIcon text="Логин и пароль прокси, если он их требует. Хранятся в конфиге открытым текстом" />
+        <ConfigLabel label="Логин и пароль:" />
+        <div className="ml-2 flex flex-1 gap-2">
+          <Input
+            value={proxy.username}
+            placeholder="логин"
+            disabled={fieldsDisabled}
+            onChange={(value) => onChange({ ...proxy, username: String(value) })}
+            className="flex-1"
+          />
+          <Input
+            type="password"
+            value={proxy.password}
+            placeholder="пароль"
+            disabled={fieldsDisabled}
+            onChange={(value) => onChange({ ...proxy, password: String(value) })}
+            className="flex-1"
+          />
+        </div>
+      </Label>
+
+      <Label>
+        <HintIcon text="Хосты, домены (.example.com) и диапазоны IP, к которым нужно подключаться напрямую, через запятую" />
+        <ConfigLabel label="Без прокси:" />
+        <Input
+          value={noProxy}
+          placeholder="localhost, 192.168.0.0/16"
+          disabled={fieldsDisabled}
+          onChange={(value) => {
+            setNoProxy(String(value));
+            onChange({ ...proxy, no_proxy: parseList(String(value)) });
+          }}
+          className="ml-2 flex-1"
+        />
+      </Label>
+    </>
+  );
+}
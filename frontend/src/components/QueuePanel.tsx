@@ -0,0 +1,117 @@
+import { ArrowDown, ArrowUp, Square, Trash2 } from 'lucide-react';
+import { Button } from './Button';
+import type { QueueControls } from '@/hooks/useQueue';
+import { ERROR_KIND_HINT } from '@/types/error';
+import { JOB_STATUS_LABEL, type Job } from '@/types/queue';
+
+interface QueuePanelProps {
+  queue: QueueControls;
+}
+
+function describeJob(job: Job): string {
+  switch (job.kind.type) {
+    case 'download':
+      return job.kind.url;
+    case 'sync':
+      return `Проверка: ${job.kind.blog}`;
+    case 'download_posts':
+      return `${job.kind.blog}: постов ${job.kind.post_ids.length}`;
+    case 'retry_failures':
+      return `${job.kind.blog}: повтор ошибок`;
+    case 'subscriptions':
+      return 'Все подписки';
+    case 'move_archive':
+      return `Перенос архива: ${job.kind.target}`;
+  }
+}
+
+function describeResult(job: Job): string | null {
+  if (job.error) {
+    const hint = job.error_kind && ERROR_KIND_HINT[job.error_kind];
+    return hint ? `${hint}: ${job.error}` : job.error;
+  }
+  if (!job.result) return null;
+  switch (job.result.type) {
+    case 'download': {
+      const report = job.result.report;
+      if (!report) return `Постов: ${job.result.posts}`;
+      const failed = report.posts_failed.length + report.files_failed.length;
+      return `Постов: ${report.posts_downloaded}, файлов: ${report.files_downloaded}, ошибок: ${failed}`;
+    }
+    case 'sync':
+      return `Постов: ${job.result.snapshot.posts.length}`;
+    case 'download_posts':
+      return `Скачано: ${job.result.result.downloaded}, пропущено: ${job.result.result.skipped}`;
+    case 'subscriptions':
+      return `Блогов: ${job.result.result.blogs}, постов: ${job.result.result.posts}, с ошибкой: ${job.result.result.failed_blogs.length}`;
+    case 'move_archive':
+      return `Блогов: ${job.result.result.blogs}, скопировано файлов: ${job.result.result.files_copied}`;
+  }
+}
+
+export function QueuePanel({ queue }: QueuePanelProps) {
+  const { jobs, moveJob, cancelJob, removeJob, clearFinished } = queue;
+
+  if (jobs.length === 0) return null;
+
+  const hasFinished = jobs.some(
+    (job) => !['queued', 'running'].includes(job.status),
+  );
+
+  return (
+    <div className="flex max-h-48 shrink-0 flex-col gap-1 overflow-y-auto rounded-lg border border-(--border) bg-(--secondary-bg) p-2">
+      <div className="flex items-center justify-between">
+        <span className="font-semibold text-sm">Очередь</span>
+        {hasFinished && (
+          <Button className="px-2 py-1 text-sm" onClick={clearFinished}>
+            Очистить завершённые
+          </Button>
+        )}
+      </div>
+      {jobs.map((job, index) => {
+        const result = describeResult(job);
+        return (
+          <div key={job.id} className="flex items-center gap-2 text-sm">
+            <span className="w-28 shrink-0 text-(--meta-text)">
+              {JOB_STATUS_LABEL[job.status]}
+            </span>
+            <span className="min-w-0 flex-1 truncate" title={result ?? ''}>
+              {describeJob(job)}
+              {result && (
+                <span className="ml-2 text-(--meta-text)">{result}</span>
+              )}
+            </span>
+            {job.status === 'queued' && (
+              <>
+                <Button
+                  className="px-2 py-1"
+                  onClick={() => moveJob(job.id, index - 1)}
+                  disabled={index === 0}
+                >
+                  <ArrowUp size={14} />
+                </Button>
+                <Button
+                  className="px-2 py-1"
+                  onClick={() => moveJob(job.id, index + 1)}
+                  disabled={index === jobs.length - 1}
+                >
+                  <ArrowDown size={14} />
+                </Button>
+              </>
+            )}
+            {(job.status === 'queued' || job.status === 'running') && (
+              <Button className="px-2 py-1" onClick={() => cancelJob(job.id)}>
+                <Square size={14} className="fill-current" />
+              </Button>
+            )}
+            {job.status !== 'running' && (
+              <Button className="px-2 py-1" onClick={() => removeJob(job.id)}>
+                <Trash2 size={14} />
+              </Button>
+            )}
+          </div>
+        );
+      })}
+    </div>
+  );
+}
@@ -0,0 +1,49 @@
+import { X } from 'lucide-react';
+import { Button } from './Button';
+import type { SessionReport } from '@/types/sessionReport';
+import { formatBytes } from '@/utils/formatBytes';
+
+interface SessionReportPanelProps {
+  report: SessionReport;
+  onClose: () => void;
+}
+
+export function SessionReportPanel({ report, onClose }: SessionReportPanelProps) {
+  const seconds = (report.duration_ms / 1000).toFixed(1);
+
+  return (
+    <div className="flex max-h-48 shrink-0 flex-col gap-1 overflow-y-auto rounded-lg border border-(--border) bg-(--secondary-bg) p-2 text-sm">
+      <div className="flex items-center justify-between">
+        <span className="font-semibold">
+          Отчёт: {report.blog}, {seconds} с
+        </span>
+        <Button className="px-2 py-1" onClick={onClose}>
+          <X size={14} />
+        </Button>
+      </div>
+      <span>
+        Постов: скачано {report.posts_downloaded}, пропущено{' '}
+        {report.posts_skipped}, нет доступа {report.posts_unavailable.length},
+        с ошибкой {report.posts_failed.length}
+      </span>
+      <span>
+        Файлов: скачано {report.files_downloaded}, пропущено{' '}
+        {report.files_skipped}, с ошибкой {report.files_failed.length},
+        записано {formatBytes(report.bytes)}
+      </span>
+      {report.posts_failed.map((post) => (
+        <span key={post.post_id} className="text-(--error)">
+          {post.title}: {post.reason}
+        </span>
+      ))}
+      {report.files_failed.map((file) => (
+        <span
+          key={`${file.post_title}-${file.file}`}
+          className="text-(--error)"
+        >
+          {file.post_title} / {file.file}: {file.reason}
+        </span>
+      ))}
+    </div>
+  );
+}
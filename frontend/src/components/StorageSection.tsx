@@ -0,0 +1,92 @@
+import { ConfigLabel } from './ConfigLabel';
+import { HintIcon } from './HintIcon';
+import { Input } from './Input';
+import { Label } from './Label';
+import { Switch } from './Switch';
+import type { StorageConfig } from '@/types/storage';
+
+interface StorageSectionProps {
+  storage: StorageConfig;
+  disabled: boolean;
+  onChange: (storage: StorageConfig) => void;
+}
+
+function parseMegabytes(value: string): number | null {
+  const n = Number.parseInt(value, 10);
+  return Number.isNaN(n) || n <= 0 ? null : n;
+}
+
+const QUOTAS: {
+  key: 'blog_quota_mb' | 'total_quota_mb';
+  label: string;
+  hint: string;
+}[] = [
+  {
+    key: 'blog_quota_mb',
+    label: 'Квота блога, МБ:',
+    hint: 'Наибольший размер папки одного блога. Когда квота исчерпана, загрузка блога останавливается, а загрузка подписок переходит к следующему блогу',
+  },
+  {
+    key: 'total_quota_mb',
+    label: 'Общая квота, МБ:',
+    hint: 'Наибольший размер всей папки сохранения. Когда квота исчерпана, загрузка останавливается',
+  },
+];
+
+export function StorageSection({
+  storage,
+  disabled,
+  onChange,
+}: StorageSectionProps) {
+  return (
+    <>
+      <div className="flex items-center">
+        <HintIcon text="Перед загрузкой узнаёт размер файлов новых постов и сравнивает его со свободным местом и квотами. Если не помещается, загрузка не начинается" />
+        <div className="flex flex-1 items-center gap-2">
+          <ConfigLabel label="Проверять свободное место:" className="w-auto" />
+          <span className="text-(--meta-text)">
+            {storage.preflight_check ? 'Включено' : 'Выключено'}
+          </span>
+        </div>
+        <Switch
+          id="storage-preflight"
+          checked={storage.preflight_check}
+          disabled={disabled}
+          onCheckedChange={(checked) =>
+            onChange({ ...storage, preflight_check: checked })
+          }
+        />
+      </div>
+
+      <Label>
+        <HintIcon text="Сколько места оставить свободным на диске. Загрузка останавливается, не доходя до этого запаса" />
+        <ConfigLabel label="Запас на диске, МБ:" />
+        <Input
+          type="number"
+          value={storage.reserve_mb}
+          disabled={disabled}
+          onChange={(value) =>
+            onChange({ ...storage, reserve_mb: Math.max(0, Number(value)) })
+          }
+          className="ml-2 flex-1"
+        />
+      </Label>
+
+      {QUOTAS.map(({ key, label, hint }) => (
+        <Label key={key}>
+          <HintIcon text={hint} />
+          <ConfigLabel label={label} />
+          <Input
+            value={storage[key] ?? ''}
+            placeholder="без ограничения"
+            disabled={disabled}
+            onChange={(value) =>
+              onChange({ ...storage, [key]: parseMegabytes(String(value)) })
+            }
+            className="ml-2 flex-1"
+          />
+        </Label>
+      ))}
+    </>
+  );
+}
@@ -0,0 +1,113 @@
+import { ConfigLabel } from './ConfigLabel';
+import { HintIcon } from './HintIcon';
+import { Input } from './Input';
+import { Label } from './Label';
+import type { RetryConfig } from '@/types/retry';
+import type { ThrottleConfig } from '@/types/throttle';
+
+interface ThrottleSectionProps {
+  throttle: ThrottleConfig;
+  retry: RetryConfig;
+  disabled: boolean;
+  onChange: (throttle: ThrottleConfig) => void;
+  onRetryChange: (retry: RetryConfig) => void;
+}
+
+function parseLimit(value: string): number | null {
+  const n = Number.parseInt(value, 10);
+  return Number.isNaN(n) || n <= 0 ? null : n;
+}
+
+export function ThrottleSection({
+  throttle,
+  retry,
+  disabled,
+  onChange,
+  onRetryChange,
+}: ThrottleSectionProps) {
+  const speedKb =
+    throttle.download_bytes_per_sec === null
+      ? ''
+      : Math.round(throttle.download_bytes_per_sec / 1024);
+
+  return (
+    <>
+      <Label>
+        <HintIcon text="Общий предел скорости загрузки файлов. Пусто — без ограничения" />
+        <ConfigLabel label="Скорость, КБ/с:" />
+        <Input
+          value={speedKb}
+          placeholder="без ограничения"
+          disabled={disabled}
+          onChange={(value) => {
+            const kb = parseLimit(String(value));
+            onChange({
+              ...throttle,
+              download_bytes_per_sec: kb === null ? null : kb * 1024,
+            });
+          }}
+          className="ml-2 flex-1"
+        />
+      </Label>
+
+      <Label>
+        <HintIcon text="Сколько запросов к API Boosty можно сделать в минуту (лента, посты, комментарии, проверка блогов). Помогает, если Boosty ограничивает запросы в часы нагрузки" />
+        <ConfigLabel label="Запросов к API в минуту:" />
+        <Input
+          value={throttle.api_requests_per_minute ?? ''}
+          placeholder="без ограничения"
+          disabled={disabled}
+          onChange={(value) =>
+            onChange({
+              ...throttle,
+              api_requests_per_minute: parseLimit(String(value)),
+            })
+          }
+          className="ml-2 flex-1"
+        />
+      </Label>
+
+      <Label>
+        <HintIcon text="Случайная добавка к паузе перед запросом к API, до указанного числа миллисекунд. Действует, только если задан лимит запросов" />
+        <ConfigLabel label="Разброс паузы, мс:" />
+        <Input
+          type="number"
+          value={throttle.api_jitter_ms}
+          disabled={disabled || throttle.api_requests_per_minute === null}
+          onChange={(value) =>
+            onChange({ ...throttle, api_jitter_ms: Math.max(0, Number(value)) })
+          }
+          className="ml-2 flex-1"
+        />
+      </Label>
+
+      <Label>
+        <HintIcon text="Сколько раз пытаться скачать файл, если сервер занят, не отвечает или ограничивает запросы (429, 503). Ошибки вроде 404 не повторяются" />
+        <ConfigLabel label="Попыток на файл:" />
+        <Input
+          type="number"
+          value={retry.max_attempts}
+          disabled={disabled}
+          onChange={(value) =>
+            onRetryChange({ ...retry, max_attempts: Math.max(1, Number(value)) })
+          }
+          className="ml-2 flex-1"
+        />
+      </Label>
+
+      <Label>
+        <HintIcon text="Пауза между попытками растёт вдвое с каждой попыткой (со случайным разбросом), но не больше этого значения. Столько же ждём, если сервер сам просит подождать дольше (Retry-After)" />
+        <ConfigLabel label="Пауза между попытками до, с:" />
+        <Input
+          type="number"
+          value={retry.max_delay_secs}
+          disabled={disabled}
+          onChange={(value) =>
+            onRetryChange({ ...retry, max_delay_secs: Math.max(0, Number(value)) })
+          }
+          className="ml-2 flex-1"
+        />
+      </Label>
+    </>
+  );
+}
@@ -2,6 +2,12 @@ import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { open } from '@tauri-apps/plugin-dialog';
 import type { AppConfig } from '@/types/config';
+import type { AccountInfo } from '@/types/account';
+import type { RenameReport } from '@/types/naming';
+import type { MoveArchiveResult, MoveJournal } from '@/types/archiveMove';
+import { confirmAction } from '@/utils/confirmAction';
+import { toCoreError } from '@/utils/toCoreError';
+import { warnIfPlainTokens } from '@/utils/warnIfPlainTokens';
 import { toast } from 'sonner';
 
 export function useConfig() {
@@ -9,6 +15,10 @@ export function useConfig() {
   const [isLoading, setLoading] = useState(true);
   const [isSaving, setSaving] = useState(false);
   const [downloadPath, setDownloadPath] = useState<string | null>(null);
+  const [accountInfo, setAccountInfo] = useState<AccountInfo | null>(null);
+  const [isCheckingAuth, setCheckingAuth] = useState(false);
+  const [pendingMove, setPendingMove] = useState<MoveJournal | null>(null);
+  const [isMoving, setMoving] = useState(false);
 
   const refreshDownloadPath = async () => {
     try {
@@ -26,6 +36,7 @@ export function useConfig() {
       try {
         const cfg = await invoke<AppConfig>('get_config');
         setConfig(cfg);
+        setPendingMove(await invoke<MoveJournal | null>('get_pending_move'));
       } catch (err) {
         console.error('Failed to fetch config:', err);
       } finally {
@@ -77,6 +88,7 @@ export function useConfig() {
       setConfig(updated);
       await refreshDownloadPath();
       toast.success('Настройки сохранены');
+      void warnIfPlainTokens();
     } catch (err) {
       toast.error('Не удалось сохранить настройки');
       console.error('Failed to update config:', err);
@@ -107,15 +119,170 @@ export function useConfig() {
     }
   };
 
+  const handleSwitchProfile = async (name: string) => {
+    if (!name.trim()) return;
+    setSaving(true);
+    try {
+      setConfig(await invoke<AppConfig>('switch_profile', { name }));
+      toast.success(`Активный профиль: ${name.trim()}`);
+    } catch (err) {
+      toast.error('Не удалось переключить профиль');
+      console.error('Failed to switch profile:', err);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleDeleteProfile = async (name: string) => {
+    setSaving(true);
+    try {
+      setConfig(await invoke<AppConfig>('delete_profile', { name }));
+      toast.success(`Профиль ${name} удалён`);
+    } catch (err) {
+      toast.error('Не удалось удалить профиль');
+      console.error('Failed to delete profile:', err);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleCheckAuth = async () => {
+    setCheckingAuth(true);
+    try {
+      setAccountInfo(await invoke<AccountInfo>('check_auth'));
+    } catch (err) {
+      toast.error('Не удалось проверить авторизацию');
+      console.error('Failed to check auth:', err);
+    } finally {
+      setCheckingAuth(false);
+    }
+  };
+
+  const handleRenameFolders = async () => {
+    setSaving(true);
+    try {
+      const planned = await invoke<RenameReport>('rename_downloaded_folders', {
+        dryRun: true,
+      });
+      if (planned.moves.length === 0) {
+        toast.info('Скачанные папки уже соответствуют шаблону');
+        return;
+      }
+      const confirmed = await confirmAction(
+        `Переименовать папки постов по сохранённому шаблону: ${planned.moves.length}?`,
+      );
+      if (!confirmed) return;
+
+      const report = await invoke<RenameReport>('rename_downloaded_folders', {
+        dryRun: false,
+      });
+      toast.success(`Переименовано папок: ${report.moves.length}`);
+      if (report.skipped.length > 0 || report.failed.length > 0) {
+        toast.warning(
+          `Не переименованы: ${[...report.skipped, ...report.failed].join(', ')}`,
+        );
+      }
+    } catch (err) {
+      toast.error('Не удалось переименовать папки');
+      console.error('Failed to rename folders:', err);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const runMoveArchive = async (target: string) => {
+    setMoving(true);
+    try {
+      toast.info('Перенос архива добавлен в очередь');
+      const result = await invoke<MoveArchiveResult>('move_archive', {
+        target,
+      });
+      setPendingMove(null);
+      const updated = await invoke<AppConfig>('get_config');
+      setConfig(updated);
+      await refreshDownloadPath();
+      toast.success(`Архив перенесён, блогов: ${result.blogs}`);
+    } catch (err) {
+      setPendingMove(await invoke<MoveJournal | null>('get_pending_move'));
+      toast.error(`Не удалось перенести архив: ${toCoreError(err).message}`);
+      console.error('Failed to move archive:', err);
+    } finally {
+      setMoving(false);
+    }
+  };
+
+  const handleMoveArchive = async () => {
+    const target = config?.download_path?.trim();
+    if (!target) {
+      toast.error('Укажите новую папку в поле пути сохранения');
+      return;
+    }
+    const confirmed = await confirmAction(
+      `Перенести все скачанные блоги из ${downloadPath} в ${target}? Новая папка станет путём сохранения.`,
+    );
+    if (confirmed) await runMoveArchive(target);
+  };
+
+  const handleResumeMove = async () => {
+    if (pendingMove) await runMoveArchive(pendingMove.to);
+  };
+
+  const handleDiscardMove = async () => {
+    try {
+      await invoke('discard_pending_move');
+      setPendingMove(null);
+    } catch (err) {
+      toast.error('Не удалось отменить перенос');
+      console.error('Failed to discard move:', err);
+    }
+  };
+
+  const handleExportDiagnostics = async () => {
+    try {
+      const target = await open({
+        directory: true,
+        multiple: false,
+        defaultPath: downloadPath || undefined,
+      });
+      if (!target || typeof target !== 'string') return;
+      const path = await invoke<string>('export_diagnostics', { target });
+      toast.success(`Файл диагностики сохранён: ${path}`);
+    } catch (err) {
+      toast.error('Не удалось сохранить диагностику');
+      console.error('Failed to export diagnostics:', err);
+    }
+  };
+
+  const setBlogProfile = (blog: string, profile: string | null) => {
+    if (!config) return;
+    const blogProfiles = { ...config.blog_profiles };
+    if (profile) blogProfiles[blog] = profile;
+    else delete blogProfiles[blog];
+    setConfig({ ...config, blog_profiles: blogProfiles });
+  };
+
   return {
     config,
     setConfig,
     handleChange,
     handleSave,
     handleClearAuth,
+    handleSwitchProfile,
+    handleDeleteProfile,
+    setBlogProfile,
+    handleRenameFolders,
+    pendingMove,
+    isMoving,
+    handleMoveArchive,
+    handleResumeMove,
+    handleDiscardMove,
+    accountInfo,
+    isCheckingAuth,
+    handleCheckAuth,
     isLoading,
     isSaving,
     downloadPath,
     handleSelectDirectory,
+    handleExportDiagnostics,
   };
 }
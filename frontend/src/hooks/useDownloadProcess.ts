@@ -2,9 +2,13 @@ import { useState, useEffect, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { toast } from 'sonner';
 import { useUrlValidation } from '@/hooks/useUrlValidation';
+import { describeError } from '@/utils/describeError';
 import { isBlogUrl } from '@/utils/isBlogUrl';
 import { isSameBlogUrl } from '@/utils/isSameBlogUrl';
+import { resolveInterruptedSession } from '@/utils/resolveInterruptedSession';
+import { toCoreError } from '@/utils/toCoreError';
 import type { DownloadSession } from '@/hooks/useDownloadingContent';
+import type { SessionReport } from '@/types/sessionReport';
 
 export function useDownloadProcess(session: DownloadSession) {
   const {
@@ -25,6 +29,8 @@ export function useDownloadProcess(session: DownloadSession) {
     return sessionStorage.getItem('offsetUrl') ?? '';
   });
 
+  const [report, setReport] = useState<SessionReport | null>(null);
+
   const { urlError, validateUrl } = useUrlValidation();
 
   useEffect(() => {
@@ -45,21 +51,30 @@ export function useDownloadProcess(session: DownloadSession) {
     }
 
     resetDownloadUi();
+    setReport(null);
     setDownloading(true);
 
     try {
-      await invoke('download_content', {
+      const resume = await resolveInterruptedSession(url.trim());
+      const result = await invoke<SessionReport>('download_content', {
         url: url.trim(),
         offsetUrl: offsetUrl.trim() !== '' ? offsetUrl : undefined,
         downloadOptions,
+        resume,
       });
-      toast.success('Загрузка завершена');
+      setReport(result);
+      if (result.posts_failed.length > 0 || result.files_failed.length > 0) {
+        toast.warning('Загрузка завершена с ошибками, подробности в отчёте');
+      } else {
+        toast.success('Загрузка завершена');
+      }
     } catch (e) {
       console.error(e);
-      if (String(e) === 'Download cancelled by user') {
+      const error = toCoreError(e);
+      if (error.kind === 'cancelled') {
         toast.info('Загрузка отменена');
       } else {
-        toast.error('Не удалось произвести загрузку');
+        toast.error(describeError(error, 'Не удалось произвести загрузку'));
       }
     } finally {
       setDownloading(false);
@@ -92,5 +107,7 @@ export function useDownloadProcess(session: DownloadSession) {
     cancelDownload,
     isOffsetUrlDisabled,
     isDifferentBlogs,
+    report,
+    closeReport: () => setReport(null),
   };
 }
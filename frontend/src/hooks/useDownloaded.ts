@@ -1,11 +1,14 @@
 import { useCallback, useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { toast } from 'sonner';
+import type { BlogSettings } from '@/types/blogSettings';
 import type { BlogSnapshot, DownloadPostsResult } from '@/types/downloaded';
 import type { DownloadSession } from '@/hooks/useDownloadingContent';
 import type { Page } from '@/constants/pages';
 import { useDownloadedStore } from '@/store/downloaded';
 import { confirmAction } from '@/utils/confirmAction';
+import { describeError } from '@/utils/describeError';
+import { toCoreError } from '@/utils/toCoreError';
 
 export function useDownloaded(
   session: DownloadSession,
@@ -56,6 +59,12 @@ export function useDownloaded(
     result: DownloadPostsResult,
     okMessage: string,
   ) => {
+    if (result.failed > 0) {
+      toast.warning(
+        `Скачано: ${result.downloaded}, пропущено: ${result.skipped}, с ошибкой: ${result.failed}`,
+      );
+      return;
+    }
     if (result.downloaded === 0 && result.skipped > 0) {
       toast.info(
         result.skipped === 1
@@ -79,10 +88,11 @@ export function useDownloaded(
       toast.success('Проверка завершена');
     } catch (e) {
       console.error(e);
-      if (String(e) === 'Download cancelled by user') {
+      const error = toCoreError(e);
+      if (error.kind === 'cancelled') {
         toast.info('Проверка отменена');
       } else {
-        toast.error('Не удалось проверить блог');
+        toast.error(describeError(error, 'Не удалось проверить блог'));
       }
     } finally {
       setChecking(false);
@@ -100,6 +110,21 @@ export function useDownloaded(
       toast.info('Нет постов для загрузки');
       return;
     }
+    await startDownload(blog, okMessage, () =>
+      invoke('download_downloaded_posts', {
+        blog,
+        postIds,
+        downloadOptions,
+        force,
+      }),
+    );
+  };
+
+  const startDownload = async (
+    blog: string,
+    okMessage: string,
+    request: () => Promise<unknown>,
+  ) => {
     if (downloadOptions.length === 0) {
       toast.error('Выберите типы контента');
       return;
@@ -109,12 +134,7 @@ export function useDownloaded(
     setDownloading(true);
     setCurrentPage('main');
     try {
-      const result = (await invoke('download_downloaded_posts', {
-        blog,
-        postIds,
-        downloadOptions,
-        force,
-      })) as DownloadPostsResult;
+      const result = (await request()) as DownloadPostsResult;
       toastDownloadResult(result, okMessage);
       try {
         await fetchRefresh(blog);
@@ -124,10 +144,11 @@ export function useDownloaded(
       }
     } catch (e) {
       console.error(e);
-      if (String(e) === 'Download cancelled by user') {
+      const error = toCoreError(e);
+      if (error.kind === 'cancelled') {
         toast.info('Загрузка отменена');
       } else {
-        toast.error('Не удалось загрузить');
+        toast.error(describeError(error, 'Не удалось загрузить'));
       }
     } finally {
       setDownloading(false);
@@ -165,6 +186,19 @@ export function useDownloaded(
     return runDownload(blog, [postId], force, okMessage);
   };
 
+  const retryFailures = async (blog: string) => {
+    const current = useDownloadedStore
+      .getState()
+      .blogs.find((item) => item.blog === blog);
+    if (!current?.failures.length) {
+      toast.info('Нет ошибок для повтора');
+      return;
+    }
+    await startDownload(blog, 'Ошибки загружены повторно', () =>
+      invoke('retry_failed_downloads', { blog, downloadOptions }),
+    );
+  };
+
   const deletePost = async (blog: string, postId: string, title: string) => {
     const ok = await confirmAction(`Удалить пост «${title}»?`, 'Удалить пост');
     if (!ok) return;
@@ -194,6 +228,20 @@ export function useDownloaded(
     }
   };
 
+  const saveSettings = async (blog: string, settings: BlogSettings) => {
+    try {
+      await invoke('update_blog_settings', { blog, settings });
+      const current = useDownloadedStore
+        .getState()
+        .blogs.find((item) => item.blog === blog);
+      if (current) replaceBlog({ ...current, settings });
+      toast.success('Настройки блога сохранены');
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось сохранить настройки блога');
+    }
+  };
+
   return {
     blogs,
     loading: loading && !initialized,
@@ -201,7 +249,9 @@ export function useDownloaded(
     refreshBlog,
     downloadNew,
     downloadPost,
+    retryFailures,
     deletePost,
     deleteBlog,
+    saveSettings,
   };
 }
@@ -2,7 +2,10 @@ import { useEffect, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { toast } from 'sonner';
-import type { DownloadOptions } from '@/types/downloadOptions';
+import {
+  DEFAULT_DOWNLOAD_OPTIONS,
+  type DownloadOptions,
+} from '@/types/downloadOptions';
 import type { LogMessage } from '@/types/logMessage';
 import type { ProgressMessage } from '@/types/progressMessage';
 
@@ -12,20 +15,13 @@ const EMPTY_PROGRESS: ProgressMessage = {
   file_name: null,
   current: 0,
   total: 0,
+  paused: false,
 };
 
-const DEFAULT_OPTIONS: DownloadOptions = [
-  'Video',
-  'Audio',
-  'Images',
-  'Texts',
-  'Files',
-];
-
 export function useDownloadingContent() {
   const [isDownloading, setDownloading] = useState(false);
   const [downloadOptions, setDownloadOptions] =
-    useState<DownloadOptions>(DEFAULT_OPTIONS);
+    useState<DownloadOptions>(DEFAULT_DOWNLOAD_OPTIONS);
   const [logs, setLogs] = useState<LogMessage[]>([]);
   const [progress, setProgress] = useState(EMPTY_PROGRESS);
   const logsEndRef = useRef<HTMLDivElement>(null);
@@ -57,6 +53,15 @@ export function useDownloadingContent() {
     setProgress(EMPTY_PROGRESS);
   };
 
+  const togglePause = async () => {
+    try {
+      await invoke(progress.paused ? 'resume_download' : 'pause_download');
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось поставить загрузку на паузу');
+    }
+  };
+
   const cancelDownload = async () => {
     try {
       await invoke('cancel_download');
@@ -77,6 +82,7 @@ export function useDownloadingContent() {
     resetDownloadUi,
     resetProgress,
     cancelDownload,
+    togglePause,
   };
 }
 
@@ -2,6 +2,7 @@ import type { Page } from '@/constants/pages';
 import { invoke } from '@tauri-apps/api/core';
 import { useEffect, useState } from 'react';
 import { toast } from 'sonner';
+import { warnIfPlainTokens } from '@/utils/warnIfPlainTokens';
 
 export function useInitApp() {
   const [clientReady, setClientReady] = useState(false);
@@ -14,6 +15,7 @@ export function useInitApp() {
         await invoke('init_client');
         console.log('Client initialized');
         setClientReady(true);
+        void warnIfPlainTokens();
       } catch (err) {
         toast.error('Не удалось инициализировать клиент');
         console.error('Failed to init client:', err);
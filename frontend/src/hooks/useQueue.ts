@@ -0,0 +1,127 @@
+import { useCallback, useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { toast } from 'sonner';
+import type { Job } from '@/types/queue';
+import type { DownloadOptions } from '@/types/downloadOptions';
+import { resolveInterruptedSession } from '@/utils/resolveInterruptedSession';
+
+function upsertJob(jobs: Job[], job: Job): Job[] {
+  const index = jobs.findIndex((item) => item.id === job.id);
+  if (index === -1) return [...jobs, job];
+  const next = [...jobs];
+  next[index] = job;
+  return next;
+}
+
+export function useQueue() {
+  const [jobs, setJobs] = useState<Job[]>([]);
+
+  const load = useCallback(async () => {
+    try {
+      setJobs(await invoke<Job[]>('list_jobs'));
+    } catch (e) {
+      console.error(e);
+    }
+  }, []);
+
+  useEffect(() => {
+    void load();
+    const unlisten = listen<Job>('job', (event) => {
+      setJobs((prev) => upsertJob(prev, event.payload));
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, [load]);
+
+  const enqueueDownload = async (
+    url: string,
+    offsetUrl: string | undefined,
+    downloadOptions: DownloadOptions,
+  ) => {
+    try {
+      const resume = await resolveInterruptedSession(url);
+      await invoke<Job>('enqueue_download', {
+        url,
+        offsetUrl,
+        downloadOptions,
+        resume,
+      });
+      toast.success('Добавлено в очередь');
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось добавить в очередь');
+    }
+  };
+
+  const enqueueSubscriptions = async (downloadOptions: DownloadOptions) => {
+    try {
+      await invoke<Job>('enqueue_subscriptions', { downloadOptions });
+      toast.success('Загрузка подписок добавлена в очередь');
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось добавить в очередь');
+    }
+  };
+
+  const enqueueSync = async (blog: string) => {
+    try {
+      await invoke<Job>('enqueue_sync', { blog });
+      toast.success('Проверка добавлена в очередь');
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось добавить в очередь');
+    }
+  };
+
+  const moveJob = async (id: number, index: number) => {
+    try {
+      setJobs(await invoke<Job[]>('move_job', { id, index }));
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось переместить задачу');
+    }
+  };
+
+  const cancelJob = async (id: number) => {
+    try {
+      await invoke('cancel_job', { id });
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось отменить задачу');
+    }
+  };
+
+  const removeJob = async (id: number) => {
+    try {
+      await invoke('remove_job', { id });
+      setJobs((prev) => prev.filter((job) => job.id !== id));
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось удалить задачу');
+    }
+  };
+
+  const clearFinished = async () => {
+    try {
+      setJobs(await invoke<Job[]>('clear_finished_jobs'));
+    } catch (e) {
+      console.error(e);
+      toast.error('Не удалось очистить очередь');
+    }
+  };
+
+  return {
+    jobs,
+    enqueueDownload,
+    enqueueSubscriptions,
+    enqueueSync,
+    moveJob,
+    cancelJob,
+    removeJob,
+    clearFinished,
+  };
+}
+
+export type QueueControls = ReturnType<typeof useQueue>;
@@ -17,8 +17,11 @@ export function useConfigValidation() {
     const checkToken = (key: string, label: string) => {
       const value = (config as unknown as Record<string, string>)[key]?.trim();
       if (!value) return;
+      // The whole `auth` value from Local Storage is accepted as the access token.
+      const maxLength = value.startsWith('{') ? 1000 : 255;
       if (value.length < 10) newErrors[key] = `${label} слишком короткий`;
-      else if (value.length > 255) newErrors[key] = `${label} слишком длинный`;
+      else if (value.length > maxLength)
+        newErrors[key] = `${label} слишком длинный`;
     };
 
     checkToken('access_token', 'Access token');
@@ -3,11 +3,19 @@ import { Input } from '../components/Input';
 import { Label } from '../components/Label';
 import { ConfigLabel } from '../components/ConfigLabel';
 import { Switch } from '../components/Switch';
+import { ProfilesSection } from '../components/ProfilesSection';
+import { ProxySection } from '../components/ProxySection';
+import { AccountInfoPanel } from '../components/AccountInfoPanel';
+import { MediaClientSection } from '../components/MediaClientSection';
+import { NamingSection } from '../components/NamingSection';
+import { StorageSection } from '../components/StorageSection';
+import { ThrottleSection } from '../components/ThrottleSection';
 import { useConfig } from '@/hooks/useConfig';
 import { HintIcon } from '@/components/HintIcon';
+import { LoggingSection } from '@/components/LoggingSection';
 import { toast } from 'sonner';
 import { useConfigValidation } from '@/hooks/useValidateInputConfig';
-import { FolderOpen } from 'lucide-react';
+import { FolderInput, FolderOpen } from 'lucide-react';
 
 export default function ConfigPage() {
   const {
@@ -15,10 +23,23 @@ export default function ConfigPage() {
     handleChange,
     handleSave,
     handleClearAuth,
+    handleSwitchProfile,
+    handleDeleteProfile,
+    setBlogProfile,
+    handleRenameFolders,
+    pendingMove,
+    isMoving,
+    handleMoveArchive,
+    handleResumeMove,
+    handleDiscardMove,
+    accountInfo,
+    isCheckingAuth,
+    handleCheckAuth,
     isLoading,
     isSaving,
     downloadPath,
     handleSelectDirectory,
+    handleExportDiagnostics,
   } = useConfig();
   const { errors, validate } = useConfigValidation();
 
@@ -55,6 +76,14 @@ export default function ConfigPage() {
           <p className="text-(--error) text-sm">{errors.posts_limit}</p>
         )}
 
+        <ProfilesSection
+          config={config}
+          disabled={isSaving}
+          onSwitch={handleSwitchProfile}
+          onDelete={handleDeleteProfile}
+          onBlogProfileChange={setBlogProfile}
+        />
+
         <Label>
           <HintIcon
             text={
@@ -113,6 +142,19 @@ export default function ConfigPage() {
           <p className="text-(--error) text-sm">{errors.device_id}</p>
         )}
 
+        <Label>
+          <HintIcon text="Проверяет токены активного профиля и показывает аккаунт и блоги с активной подпиской" />
+          <ConfigLabel label="Авторизация:" />
+          <Button
+            className="ml-2"
+            onClick={handleCheckAuth}
+            disabled={isCheckingAuth}
+          >
+            {isCheckingAuth ? 'Проверяем...' : 'Проверить'}
+          </Button>
+        </Label>
+        {accountInfo && <AccountInfoPanel info={accountInfo} />}
+
         <div className="flex items-center">
           <HintIcon text="Позволяет загружать комментарии. Загрузка производится в папку с постом. Фильтры контента для постов применяются и к содержимому комментариев" />
           <div className="flex flex-1 items-center gap-2">
@@ -136,8 +178,46 @@ export default function ConfigPage() {
           />
         </div>
 
+        <div className="flex items-center">
+          <HintIcon text="Догружает все ответы в длинных ветках комментариев постранично, с паузой между запросами. Без этого ветка обрезается по лимиту ответов, а в index.html отмечается, что показаны не все ответы" />
+          <div className="flex flex-1 items-center gap-2">
+            <ConfigLabel label="Все ответы в комментариях:" className="w-auto" />
+            <span className="text-(--meta-text)">
+              {config.comments.fetch_all_replies ? 'Включено' : 'Выключено'}
+            </span>
+          </div>
+          <Switch
+            id="comments-all-replies"
+            checked={config.comments.fetch_all_replies}
+            disabled={!config.comments.enabled}
+            onCheckedChange={(checked) =>
+              handleChange('comments', {
+                ...config.comments,
+                fetch_all_replies: checked,
+              })
+            }
+          />
+        </div>
+
+        <div className="flex items-center">
+          <HintIcon text="После каждой загрузки сохраняет отчёт session_report.json в папку блога: сколько постов и файлов скачано, пропущено и не удалось скачать, с причинами ошибок" />
+          <div className="flex flex-1 items-center gap-2">
+            <ConfigLabel label="Сохранять отчёт о загрузке:" className="w-auto" />
+            <span className="text-(--meta-text)">
+              {config.save_session_report ? 'Включено' : 'Выключено'}
+            </span>
+          </div>
+          <Switch
+            id="save-session-report"
+            checked={config.save_session_report}
+            onCheckedChange={(checked) =>
+              handleChange('save_session_report', checked)
+            }
+          />
+        </div>
+
         <Label>
-          <HintIcon text="Путь для сохранения загружаемого контента. Выберите папку или введите путь вручную." />
+          <HintIcon text="Путь для сохранения загружаемого контента. Выберите папку или введите путь вручную. Кнопка со стрелкой переносит уже скачанные блоги в указанную папку и делает её путём сохранения." />
           <ConfigLabel label="Путь сохранения:" />
           <div className="ml-2 flex flex-1 gap-2">
             {' '}
@@ -152,15 +232,85 @@ export default function ConfigPage() {
             <Button className="px-3" onClick={handleSelectDirectory}>
               <FolderOpen size={16} />
             </Button>
+            <Button
+              className="px-3"
+              onClick={handleMoveArchive}
+              disabled={isMoving || isSaving}
+            >
+              <FolderInput size={16} />
+            </Button>
           </div>
         </Label>
 
+        {pendingMove && (
+          <Label className="ml-5">
+            <span className="flex-1 text-(--meta-text)">
+              Перенос архива в {pendingMove.to} не завершён (готово блогов:{' '}
+              {pendingMove.moved_blogs.length})
+            </span>
+            <Button
+              className="ml-2"
+              onClick={handleResumeMove}
+              disabled={isMoving}
+            >
+              Продолжить
+            </Button>
+            <Button
+              className="ml-2"
+              onClick={handleDiscardMove}
+              disabled={isMoving}
+            >
+              Забыть
+            </Button>
+          </Label>
+        )}
+
         <Label className="ml-5">
           <ConfigLabel label="Текущий путь:" />
           <div>
             <span className="font-mono">{downloadPath || '—'}</span>
           </div>
         </Label>
+
+        <NamingSection
+          naming={config.naming}
+          disabled={isSaving}
+          onChange={(naming) => handleChange('naming', naming)}
+          onRename={handleRenameFolders}
+        />
+
+        <StorageSection
+          storage={config.storage}
+          disabled={isSaving}
+          onChange={(storage) => handleChange('storage', storage)}
+        />
+
+        <ThrottleSection
+          throttle={config.throttle}
+          retry={config.retry}
+          disabled={isSaving}
+          onChange={(throttle) => handleChange('throttle', throttle)}
+          onRetryChange={(retry) => handleChange('retry', retry)}
+        />
+
+        <MediaClientSection
+          media={config.media_client}
+          disabled={isSaving}
+          onChange={(media) => handleChange('media_client', media)}
+        />
+
+        <ProxySection
+          proxy={config.proxy}
+          disabled={isSaving}
+          onChange={(proxy) => handleChange('proxy', proxy)}
+        />
+
+        <LoggingSection
+          verbosity={config.log_verbosity}
+          disabled={isSaving}
+          onChange={(verbosity) => handleChange('log_verbosity', verbosity)}
+          onExport={handleExportDiagnostics}
+        />
       </div>
 
       <div className="flex shrink-0 justify-center gap-3">
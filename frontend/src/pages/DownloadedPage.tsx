@@ -1,3 +1,4 @@
+import { BlogSettingsPanel } from '@/components/BlogSettingsPanel';
 import { Button } from '@/components/Button';
 import { DownloadOptionsPanel } from '@/components/DownloadOptionsPanel';
 import { useDownloaded } from '@/hooks/useDownloaded';
@@ -11,9 +12,12 @@ import {
   DownloadIcon,
   FolderOpen,
   RefreshCw,
+  RotateCcw,
+  Settings,
   Square,
   Trash2,
 } from 'lucide-react';
+import { useState } from 'react';
 
 interface DownloadedPageProps {
   session: DownloadSession;
@@ -46,6 +50,7 @@ export default function DownloadedPage({
     session;
   const expandedBlogs = useDownloadedStore((state) => state.expandedBlogs);
   const setBlogExpanded = useDownloadedStore((state) => state.setBlogExpanded);
+  const [settingsBlog, setSettingsBlog] = useState<string | null>(null);
   const {
     blogs,
     loading,
@@ -53,8 +58,10 @@ export default function DownloadedPage({
     refreshBlog,
     downloadNew,
     downloadPost,
+    retryFailures,
     deletePost,
     deleteBlog,
+    saveSettings,
   } = useDownloaded(session, setCurrentPage, active);
 
   if (loading) {
@@ -120,6 +127,29 @@ export default function DownloadedPage({
                     </div>
                   </Button>
                 )}
+                {blog.failures.length > 0 && (
+                  <Button
+                    disabled={isDownloading || downloadOptions.length === 0}
+                    onClick={() => retryFailures(blog.blog)}
+                  >
+                    <div className="flex items-center gap-2">
+                      <RotateCcw size={16} />
+                      Повторить ошибки ({blog.failures.length})
+                    </div>
+                  </Button>
+                )}
+                <Button
+                  onClick={() =>
+                    setSettingsBlog(
+                      settingsBlog === blog.blog ? null : blog.blog,
+                    )
+                  }
+                >
+                  <div className="flex items-center gap-2">
+                    <Settings size={16} />
+                    Настройки блога
+                  </div>
+                </Button>
                 <Button
                   disabled={isDownloading}
                   onClick={() => deleteBlog(blog.blog)}
@@ -130,6 +160,13 @@ export default function DownloadedPage({
                   </div>
                 </Button>
               </div>
+              {settingsBlog === blog.blog && (
+                <BlogSettingsPanel
+                  settings={blog.settings}
+                  disabled={isDownloading}
+                  onSave={(settings) => saveSettings(blog.blog, settings)}
+                />
+              )}
 
               <ul className="mt-3 flex flex-col gap-2">
                 {blog.posts.length === 0 && (
@@ -2,12 +2,22 @@ import { Button } from '../components/Button';
 import { DownloadProgress } from '@/components/DownloadProgress';
 import { formatLog } from '@/components/FormatLog';
 import { OpenFolderButton } from '@/components/OpenFolderButton';
-import { DownloadIcon, Square } from 'lucide-react';
+import {
+  DownloadIcon,
+  Library,
+  ListPlus,
+  Pause,
+  Play,
+  Square,
+} from 'lucide-react';
 import { Input } from '@/components/Input';
 import { useDownloadProcess } from '@/hooks/useDownloadProcess';
 import { HintIcon } from '@/components/HintIcon';
 import { DownloadOptionsPanel } from '@/components/DownloadOptionsPanel';
 import type { DownloadSession } from '@/hooks/useDownloadingContent';
+import { useQueue } from '@/hooks/useQueue';
+import { QueuePanel } from '@/components/QueuePanel';
+import { SessionReportPanel } from '@/components/SessionReportPanel';
 
 interface MainPageProps {
   session: DownloadSession;
@@ -21,6 +31,7 @@ export default function MainPage({ session }: MainPageProps) {
     logs,
     progress,
     logsEndRef,
+    togglePause,
   } = session;
   const {
     url,
@@ -32,7 +43,10 @@ export default function MainPage({ session }: MainPageProps) {
     isOffsetUrlDisabled,
     isDifferentBlogs,
     urlError,
+    report,
+    closeReport,
   } = useDownloadProcess(session);
+  const queue = useQueue();
 
   return (
     <div className="flex min-h-0 flex-1 flex-col gap-4 rounded-lg border border-(--border) bg-(--background) p-4 text-(--text)">
@@ -40,7 +54,6 @@ export default function MainPage({ session }: MainPageProps) {
         <DownloadOptionsPanel
           value={downloadOptions}
           onChange={setDownloadOptions}
-          disabled={isDownloading}
         />
 
         <div className="flex flex-row gap-4">
@@ -48,13 +61,45 @@ export default function MainPage({ session }: MainPageProps) {
             placeholder="URL адрес блога или конкретного поста"
             value={url}
             onChange={(value) => setUrl(String(value))}
-            disabled={isDownloading}
             className="flex-1"
           />
+          <Button
+            onClick={() =>
+              queue.enqueueDownload(
+                url.trim(),
+                offsetUrl.trim() !== '' ? offsetUrl : undefined,
+                downloadOptions,
+              )
+            }
+            disabled={
+              !url ||
+              !!urlError ||
+              isDifferentBlogs ||
+              downloadOptions.length === 0
+            }
+            aria-label="Add to queue"
+          >
+            <ListPlus />
+          </Button>
+          <Button
+            onClick={() => queue.enqueueSubscriptions(downloadOptions)}
+            disabled={downloadOptions.length === 0}
+            aria-label="Download all subscriptions"
+          >
+            <Library />
+          </Button>
           {isDownloading ? (
-            <Button onClick={cancelDownload} aria-label="Stop">
-              <Square className="fill-current" />
-            </Button>
+            <>
+              <Button
+                onClick={togglePause}
+                aria-label={progress.paused ? 'Resume' : 'Pause'}
+              >
+                {progress.paused ? <Play /> : <Pause />}
+              </Button>
+              <Button onClick={cancelDownload} aria-label="Stop">
+                <Square className="fill-current" />
+              </Button>
+            </>
           ) : (
             <Button
               onClick={startDownload}
@@ -72,7 +117,7 @@ export default function MainPage({ session }: MainPageProps) {
             placeholder="URL адрес поста для отступа"
             value={offsetUrl}
             onChange={(value) => setOffsetUrl(String(value))}
-            disabled={isDownloading || isOffsetUrlDisabled}
+            disabled={isOffsetUrlDisabled}
             className="flex-1"
           />
 
@@ -95,6 +140,10 @@ export default function MainPage({ session }: MainPageProps) {
         </div>
       </div>
 
+      {report && <SessionReportPanel report={report} onClose={closeReport} />}
+
+      <QueuePanel queue={queue} />
+
       <div className="min-h-0 flex-1 overflow-y-auto rounded-lg border border-(--border) bg-(--secondary-bg) p-2">
         {logs.map((msg, index) => (
           <p
@@ -42,6 +42,7 @@ function mergeBlog(scanned: BlogSnapshot, prev?: BlogSnapshot): BlogSnapshot {
     blog: scanned.blog,
     last_checked_at: prev.last_checked_at ?? scanned.last_checked_at,
     posts,
+    settings: scanned.settings,
   };
 }
 
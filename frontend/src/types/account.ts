@@ -0,0 +1,30 @@
+export type TokenStatus = 'valid' | 'refreshed' | 'expired' | 'missing';
+
+export const TOKEN_STATUS_LABEL: Record<TokenStatus, string> = {
+  valid: 'действителен',
+  refreshed: 'был просрочен, обновлён',
+  expired: 'просрочен или отозван — введите токены заново',
+  missing: 'не задан',
+};
+
+export interface AccountUser {
+  id: number;
+  name: string;
+  blogUrl: string | null;
+}
+
+export interface Subscription {
+  blog: string;
+  blog_title: string;
+  level_name: string;
+  level_price: number;
+}
+
+export interface AccountInfo {
+  profile: string;
+  token_status: TokenStatus;
+  token_expires_at: number | null;
+  refresh_error: string | null;
+  user: AccountUser | null;
+  subscriptions: Subscription[];
+}
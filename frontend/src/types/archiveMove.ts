@@ -0,0 +1,15 @@
+export interface MoveJournal {
+  from: string;
+  to: string;
+  started_at: number;
+  moved_blogs: string[];
+}
+
+export interface MoveArchiveResult {
+  from: string;
+  to: string;
+  blogs: number;
+  files_copied: number;
+  bytes_copied: number;
+  resumed: boolean;
+}
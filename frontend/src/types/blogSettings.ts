@@ -0,0 +1,21 @@
+import type { DownloadOption } from './downloadOptions';
+
+export type OutputFormat = 'html' | 'text';
+
+export interface PostFilter {
+  title_includes: string[];
+  title_excludes: string[];
+  tags: string[];
+  skip_paid: boolean;
+}
+
+export interface BlogSettings {
+  download_options: DownloadOption[] | null;
+  comments_enabled: boolean | null;
+  reply_limit: number | null;
+  comments_limit: number | null;
+  posts_limit: number | null;
+  filter: PostFilter;
+  output_formats: OutputFormat[];
+  quota_mb: number | null;
+}
@@ -1,15 +1,44 @@
+import type { LogVerbosity } from './logLevel';
+import type { MediaClientConfig } from './mediaClient';
+import type { NamingConfig } from './naming';
+import type { ProxyConfig } from './proxy';
+import type { RetryConfig } from './retry';
+import type { StorageConfig } from './storage';
+import type { ThrottleConfig } from './throttle';
+
 export interface CommentsConfig {
   enabled: boolean;
   reply_limit?: number;
   limit?: number;
   order?: string;
+  fetch_all_replies: boolean;
+  replies_page_delay_ms?: number;
+}
+
+export interface AuthProfile {
+  access_token: string;
+  refresh_token: string;
+  device_id: string;
 }
 
 export interface AppConfig {
+  version: number;
   posts_limit: number;
   access_token: string;
   refresh_token: string;
   device_id: string;
   comments: CommentsConfig;
   download_path: string | null;
+  active_profile: string;
+  profiles: Record<string, AuthProfile>;
+  blog_profiles: Record<string, string>;
+  token_storage: 'plain' | 'keyring' | 'encrypted_file';
+  naming: NamingConfig;
+  storage: StorageConfig;
+  throttle: ThrottleConfig;
+  media_client: MediaClientConfig;
+  proxy: ProxyConfig;
+  retry: RetryConfig;
+  save_session_report: boolean;
+  log_verbosity: LogVerbosity;
 }
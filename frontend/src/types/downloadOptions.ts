@@ -1,3 +1,11 @@
 export type DownloadOption = 'Video' | 'Audio' | 'Images' | 'Texts' | 'Files';
 
 export type DownloadOptions = DownloadOption[];
+
+export const DEFAULT_DOWNLOAD_OPTIONS: DownloadOptions = [
+  'Video',
+  'Audio',
+  'Images',
+  'Texts',
+  'Files',
+];
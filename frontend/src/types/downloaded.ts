@@ -1,4 +1,6 @@
+import type { BlogSettings } from './blogSettings';
 import type { DownloadOption } from './downloadOptions';
+import type { UrlKind } from './sessionReport';
 
 export type PostSyncStatus =
   | 'up_to_date'
@@ -21,15 +23,26 @@ export interface PostSnapshot {
   is_paid: boolean;
 }
 
+export interface FailedItem {
+  post_id: string;
+  title: string;
+  item_id: string | null;
+  url_kind: UrlKind | null;
+  error: string;
+}
+
 export interface BlogSnapshot {
   blog: string;
   last_checked_at: number | null;
   posts: PostSnapshot[];
+  settings: BlogSettings;
+  failures: FailedItem[];
 }
 
 export interface DownloadPostsResult {
   downloaded: number;
   skipped: number;
+  failed: number;
 }
 
 export const STATUS_LABEL: Record<PostSyncStatus, string> = {
@@ -0,0 +1,31 @@
+export type ErrorKind =
+  | 'network'
+  | 'auth'
+  | 'not_found'
+  | 'paywalled'
+  | 'filesystem'
+  | 'parse'
+  | 'cancelled'
+  | 'storage_limit'
+  | 'other';
+
+export interface CoreError {
+  kind: ErrorKind;
+  message: string;
+  status?: number;
+  blog?: string;
+  post_id?: string;
+  path?: string;
+}
+
+export const ERROR_KIND_HINT: Record<ErrorKind, string | null> = {
+  network: 'Нет связи с Boosty: проверьте подключение или прокси',
+  auth: 'Нужна авторизация: проверьте токен доступа',
+  not_found: 'Пост или блог не найден',
+  paywalled: 'Пост недоступен на вашем уровне подписки',
+  filesystem: 'Ошибка чтения или записи файлов',
+  parse: 'Неожиданный ответ сервера',
+  cancelled: 'Отменено',
+  storage_limit: 'Не хватает места на диске или достигнута квота',
+  other: null,
+};
@@ -1,2 +1,4 @@
-export const LOG_LEVELS = ['Info', 'Warn', 'Error'] as const;
+export const LOG_LEVELS = ['Info', 'Warn', 'Error', 'Debug', 'Trace'] as const;
 export type LogLevel = (typeof LOG_LEVELS)[number];
+
+export type LogVerbosity = 'normal' | 'debug' | 'trace';
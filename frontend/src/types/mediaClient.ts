@@ -0,0 +1,8 @@
+export interface MediaClientConfig {
+  connect_timeout_secs: number;
+  read_timeout_secs: number;
+  user_agent: string;
+  extra_headers: Record<string, string>;
+  http2: boolean;
+  ca_certificates: string[];
+}
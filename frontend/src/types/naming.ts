@@ -0,0 +1,22 @@
+export type CollisionPolicy = 'suffix' | 'skip';
+
+export interface NamingConfig {
+  post_folder: string;
+  image_file: string;
+  video_file: string;
+  media_file: string;
+  collision: CollisionPolicy;
+}
+
+export interface FolderMove {
+  blog: string;
+  post_id: string;
+  from: string;
+  to: string;
+}
+
+export interface RenameReport {
+  moves: FolderMove[];
+  skipped: string[];
+  failed: string[];
+}
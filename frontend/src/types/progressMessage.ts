@@ -4,4 +4,5 @@ export interface ProgressMessage {
   file_name: string | null;
   current: number;
   total: number;
+  paused: boolean;
 }
@@ -0,0 +1,11 @@
+export type ProxyScheme = 'http' | 'https' | 'socks5';
+
+export interface ProxyConfig {
+  enabled: boolean;
+  scheme: ProxyScheme;
+  host: string;
+  port: number;
+  username: string;
+  password: string;
+  no_proxy: string[];
+}
@@ -0,0 +1,61 @@
+import type { MoveArchiveResult } from './archiveMove';
+import type { DownloadOptions } from './downloadOptions';
+import type { BlogSnapshot, DownloadPostsResult } from './downloaded';
+import type { ErrorKind } from './error';
+import type { SessionReport } from './sessionReport';
+
+export interface SubscriptionsResult {
+  blogs: number;
+  posts: number;
+  new_blogs: string[];
+  failed_blogs: string[];
+}
+
+export type JobKind =
+  | {
+      type: 'download';
+      url: string;
+      offset_url: string | null;
+      download_options: DownloadOptions;
+      resume: boolean;
+    }
+  | { type: 'sync'; blog: string }
+  | {
+      type: 'download_posts';
+      blog: string;
+      post_ids: string[];
+      download_options: DownloadOptions;
+      force: boolean;
+    }
+  | { type: 'retry_failures'; blog: string; download_options: DownloadOptions }
+  | { type: 'subscriptions'; download_options: DownloadOptions }
+  | { type: 'move_archive'; target: string };
+
+export type JobStatus = 'queued' | 'running' | 'done' | 'failed' | 'cancelled';
+
+export type JobResult =
+  | { type: 'download'; posts: number; report: SessionReport | null }
+  | { type: 'sync'; snapshot: BlogSnapshot }
+  | { type: 'download_posts'; result: DownloadPostsResult }
+  | { type: 'subscriptions'; result: SubscriptionsResult }
+  | { type: 'move_archive'; result: MoveArchiveResult };
+
+export interface Job {
+  id: number;
+  kind: JobKind;
+  status: JobStatus;
+  created_at: number;
+  started_at: number | null;
+  finished_at: number | null;
+  error: string | null;
+  error_kind: ErrorKind | null;
+  result: JobResult | null;
+}
+
+export const JOB_STATUS_LABEL: Record<JobStatus, string> = {
+  queued: 'в очереди',
+  running: 'выполняется',
+  done: 'готово',
+  failed: 'ошибка',
+  cancelled: 'отменено',
+};
@@ -0,0 +1,5 @@
+export interface RetryConfig {
+  max_attempts: number;
+  base_delay_ms: number;
+  max_delay_secs: number;
+}
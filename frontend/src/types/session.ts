@@ -0,0 +1,15 @@
+export type CommentTarget = {
+  blog: string;
+  post_id: string;
+  title: string;
+};
+
+export type SessionState = {
+  blog: string;
+  feed_offset: string | null;
+  post_ids: string[];
+  completed_posts: string[];
+  pending_comments: CommentTarget[];
+  started_at: number;
+  updated_at: number;
+};
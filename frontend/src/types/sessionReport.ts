@@ -0,0 +1,38 @@
+import type { ErrorKind } from './error';
+
+export interface ReportedPost {
+  post_id: string;
+  title: string;
+}
+
+export interface FailedPost {
+  post_id: string;
+  title: string;
+  kind: ErrorKind;
+  reason: string;
+}
+
+export type UrlKind = 'image' | 'video' | 'audio' | 'file' | 'smile';
+
+export interface FailedFile {
+  post_id: string;
+  post_title: string;
+  item_id: string;
+  url_kind: UrlKind;
+  file: string;
+  reason: string;
+}
+
+export interface SessionReport {
+  blog: string;
+  started_at: number;
+  duration_ms: number;
+  posts_downloaded: number;
+  posts_skipped: number;
+  posts_unavailable: ReportedPost[];
+  posts_failed: FailedPost[];
+  files_downloaded: number;
+  files_skipped: number;
+  files_failed: FailedFile[];
+  bytes: number;
+}
@@ -0,0 +1,6 @@
+export interface StorageConfig {
+  preflight_check: boolean;
+  reserve_mb: number;
+  blog_quota_mb: number | null;
+  total_quota_mb: number | null;
+}
@@ -0,0 +1,5 @@
+export interface ThrottleConfig {
+  download_bytes_per_sec: number | null;
+  api_requests_per_minute: number | null;
+  api_jitter_ms: number;
+}
@@ -0,0 +1,5 @@
+import { ERROR_KIND_HINT, type CoreError } from '@/types/error';
+
+export function describeError(error: CoreError, fallback: string): string {
+  return ERROR_KIND_HINT[error.kind] ?? fallback;
+}
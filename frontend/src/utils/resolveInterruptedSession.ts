@@ -0,0 +1,23 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { SessionState } from '@/types/session';
+import { confirmAction } from '@/utils/confirmAction';
+
+/**
+ * Asks whether to continue an interrupted blog download; a refusal drops its saved state.
+ * Returns whether the download should resume it.
+ */
+export async function resolveInterruptedSession(url: string): Promise<boolean> {
+  const state = await invoke<SessionState | null>('get_interrupted_session', {
+    url,
+  });
+  if (!state) return false;
+
+  const resume = await confirmAction(
+    `Найдена прерванная загрузка блога «${state.blog}» (${state.completed_posts.length}/${state.post_ids.length} постов). Продолжить её?`,
+    'Продолжить загрузку',
+  );
+  if (!resume) {
+    await invoke('discard_interrupted_session', { blog: state.blog });
+  }
+  return resume;
+}
@@ -0,0 +1,9 @@
+import type { CoreError } from '@/types/error';
+
+/** Errors of queued commands come as `CoreError`, the rest as plain strings. */
+export function toCoreError(e: unknown): CoreError {
+  if (typeof e === 'object' && e !== null && 'kind' in e && 'message' in e) {
+    return e as CoreError;
+  }
+  return { kind: 'other', message: String(e) };
+}
@@ -0,0 +1,19 @@
+import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
+
+let warned = false;
+
+/** Warns once per run when the tokens had to be saved to the config unencrypted. */
+export async function warnIfPlainTokens(): Promise<void> {
+  if (warned) return;
+  try {
+    if (await invoke<boolean>('tokens_in_plain_text')) {
+      warned = true;
+      toast.warning(
+        'Токены сохранены в config.json в открытом виде: системное хранилище ключей недоступно. Задайте BOOSTY_SECRETS_PASSPHRASE, чтобы хранить их в зашифрованном файле.',
+      );
+    }
+  } catch (err) {
+    console.error('Failed to check token storage:', err);
+  }
+}
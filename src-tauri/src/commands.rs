@@ -1,50 +1,60 @@
 use std::sync::Arc;
 
-use boosty_api::api_client::ApiClient;
 use boosty_downloader_core::{
-    AppConfig, BlogSnapshot, DownloadOptions, DownloadPostsResult, log_error, log_info,
-    scan_downloaded,
+    AccountInfo, AppConfig, BlogSettings, BlogSnapshot, CoreError, DownloadOptions,
+    DownloadPostsResult, ErrorKind, MoveArchiveResult, MoveJournal, RenameReport, SessionReport,
+    SessionState, log_error, log_info, scan_downloaded,
 };
-use tauri::State;
-use tokio::sync::Mutex;
-use tokio_util::sync::CancellationToken;
+use tauri::{AppHandle, State};
+use tokio::sync::{Mutex, oneshot};
 
-use crate::state::AppState;
+use crate::{
+    queue::{self, Job, JobKind, JobOutcome, JobResult},
+    state::AppState,
+};
 
-async fn take_work_slot(
+async fn enqueue_job(
+    app: &AppHandle,
     state: &State<'_, Arc<Mutex<AppState>>>,
-) -> Result<(ApiClient, AppConfig, CancellationToken), String> {
-    let mut state = state.lock().await;
-    if state.download_token.is_some() {
-        return Err("Download is already in progress".to_string());
-    }
-
-    let token = CancellationToken::new();
-    state.download_token = Some(token.clone());
+    kind: JobKind,
+    waiter: Option<oneshot::Sender<JobOutcome>>,
+) -> Job {
+    let (job, snapshot, notify) = {
+        let mut state = state.lock().await;
+        let job = state.queue.enqueue(kind);
+        if let Some(waiter) = waiter {
+            state.waiters.insert(job.id, waiter);
+        }
+        (job, state.queue.clone(), state.queue_notify.clone())
+    };
+    queue::persist(&snapshot).await;
+    queue::emit_job(app, &job);
+    notify.notify_one();
+    job
+}
 
-    let client = state
-        .client
-        .as_ref()
-        .ok_or("Client not initialized")?
-        .clone();
-    let cfg = state.config.clone();
-    Ok((client, cfg, token))
+/// Enqueues a job and waits for the worker to finish it.
+async fn run_queued(
+    app: &AppHandle,
+    state: &State<'_, Arc<Mutex<AppState>>>,
+    kind: JobKind,
+) -> Result<JobResult, CoreError> {
+    let (tx, rx) = oneshot::channel();
+    enqueue_job(app, state, kind, Some(tx)).await;
+    rx.await
+        .map_err(|_| CoreError::new(ErrorKind::Other, "Job was removed from the queue"))?
 }
 
-async fn release_work_slot(state: &State<'_, Arc<Mutex<AppState>>>) {
-    let mut state = state.lock().await;
-    state.download_token = None;
+fn unexpected_result() -> CoreError {
+    CoreError::new(ErrorKind::Other, "Unexpected job result")
 }
 
-fn map_work_err(e: anyhow::Error) -> String {
-    if !boosty_downloader_core::is_cancelled_error(&e) {
-        log_error!("{e:#}");
-    }
-    if boosty_downloader_core::is_cancelled_error(&e) {
-        boosty_downloader_core::DOWNLOAD_CANCELLED_MESSAGE.to_string()
-    } else {
-        e.to_string()
+async fn ensure_idle(state: &State<'_, Arc<Mutex<AppState>>>) -> Result<AppConfig, String> {
+    let state = state.lock().await;
+    if state.running.is_some() {
+        return Err("Download is already in progress".to_string());
     }
+    Ok(state.config.clone())
 }
 
 #[tauri::command]
@@ -58,8 +68,18 @@ pub async fn update_config(
     state: State<'_, Arc<Mutex<AppState>>>,
     new_config: AppConfig,
 ) -> Result<(), String> {
+    new_config.naming.validate().map_err(|e| e.to_string())?;
+    boosty_downloader_core::build_media_client(&new_config.media_client, &new_config.proxy)
+        .map_err(|e| e.to_string())?;
+
     let mut state = state.lock().await;
     let previous = state.config.clone();
+    if new_config.proxy != previous.proxy && state.client.is_some() {
+        let client = boosty_downloader_core::build_api_client(&new_config.proxy)
+            .map_err(|e| e.to_string())?;
+        state.client = Some(client);
+    }
+    boosty_downloader_core::set_log_verbosity(new_config.log_verbosity);
     state.config = new_config;
 
     let client = state
@@ -79,6 +99,97 @@ pub async fn update_config(
     Ok(())
 }
 
+/// Makes `name` the active auth profile (creating it if needed) and returns the updated config.
+#[tauri::command]
+pub async fn switch_profile(
+    name: String,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<AppConfig, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    let (client, mut cfg) = {
+        let mut state = state.lock().await;
+        if state.running.is_some() {
+            return Err("Download is already in progress".to_string());
+        }
+        if state.switching_profile {
+            return Err("Profile is already being switched".to_string());
+        }
+        let client = state
+            .client
+            .as_ref()
+            .ok_or("Client not initialized")?
+            .clone();
+        state.switching_profile = true;
+        (client, state.config.clone())
+    };
+
+    // The lock is not held over the network call; `switching_profile` keeps jobs from
+    // starting with the client half switched.
+    cfg.switch_profile(name);
+    let synced = boosty_downloader_core::sync_auth(&client, &mut cfg, None).await;
+
+    let mut state = state.lock().await;
+    state.switching_profile = false;
+    state.queue_notify.notify_one();
+    synced.map_err(|e| e.to_string())?;
+    boosty_downloader_core::save_config(&cfg)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.config = cfg;
+    Ok(state.config.clone())
+}
+
+#[tauri::command]
+pub async fn delete_profile(
+    name: String,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<AppConfig, String> {
+    let mut state = state.lock().await;
+    state
+        .config
+        .remove_profile(&name)
+        .map_err(|e| e.to_string())?;
+    boosty_downloader_core::save_config(&state.config)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(state.config.clone())
+}
+
+/// Validates the credentials in use and returns the account and its subscriptions.
+#[tauri::command]
+pub async fn check_auth(state: State<'_, Arc<Mutex<AppState>>>) -> Result<AccountInfo, String> {
+    let (client, cfg) = {
+        let state = state.lock().await;
+        let client = state
+            .client
+            .as_ref()
+            .ok_or("Client not initialized")?
+            .clone();
+        (client, state.config.clone())
+    };
+    let info = boosty_downloader_core::check_auth(&client, &cfg)
+        .await
+        .map_err(|e| {
+            log_error!("{e:#}");
+            e.to_string()
+        })?;
+
+    // The check may have refreshed the tokens and saved them to the config file.
+    if let Ok(saved) = boosty_downloader_core::load_config().await {
+        queue::take_saved_tokens(&mut state.lock().await.config, saved);
+    }
+    Ok(info)
+}
+
+/// Whether the tokens had to be saved to the config in plain text, for a warning in the UI.
+#[tauri::command]
+pub async fn tokens_in_plain_text() -> Result<bool, String> {
+    Ok(boosty_downloader_core::tokens_in_plain_text())
+}
+
 #[tauri::command]
 pub async fn init_client(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     let client = boosty_downloader_core::make_client()
@@ -94,6 +205,7 @@ pub async fn init_client(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), S
     let mut state = state.lock().await;
     state.client = Some(client);
     state.config = config;
+    state.queue_notify.notify_one();
     log_info!("Client initialized");
     Ok(())
 }
@@ -103,42 +215,44 @@ pub async fn download_content(
     url: String,
     offset_url: Option<String>,
     download_options: DownloadOptions,
+    resume: bool,
+    app: AppHandle,
     state: State<'_, Arc<Mutex<AppState>>>,
-) -> Result<(), String> {
-    let (client, cfg, token) = take_work_slot(&state).await?;
-
-    let ctx = boosty_downloader_core::build_url_context(&url, offset_url.as_deref()).map_err(|e| {
-        log_error!("{e}");
-        e.to_string()
-    });
-    let ctx = match ctx {
-        Ok(ctx) => ctx,
-        Err(e) => {
-            release_work_slot(&state).await;
-            return Err(e);
-        }
-    };
+) -> Result<SessionReport, CoreError> {
+    let kind = download_job(url, offset_url, download_options, resume)
+        .map_err(|e| CoreError::new(ErrorKind::Parse, e))?;
+    match run_queued(&app, &state, kind).await? {
+        JobResult::Download { report, .. } => Ok(report.unwrap_or_default()),
+        _ => Err(unexpected_result()),
+    }
+}
 
-    let result = boosty_downloader_core::process_boosty_url(
-        &client,
-        &cfg,
-        &ctx.url,
-        ctx.offset,
-        download_options,
-        &token,
-    )
-    .await;
+#[tauri::command]
+pub async fn cancel_download(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
+    let state = state.lock().await;
+    if let Some(running) = &state.running {
+        running.token.cancel();
+        log_info!("Cancelling the running download");
+    }
+    Ok(())
+}
 
-    release_work_slot(&state).await;
-    result.map(|_| ()).map_err(map_work_err)
+#[tauri::command]
+pub async fn pause_download(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
+    let state = state.lock().await;
+    if state.running.is_some() && !state.pause.is_paused() {
+        state.pause.pause();
+        log_info!("Download paused");
+    }
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn cancel_download(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
+pub async fn resume_download(state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
     let state = state.lock().await;
-    if let Some(token) = &state.download_token {
-        token.cancel();
-        log_info!("{}", boosty_downloader_core::DOWNLOAD_CANCELLED_MESSAGE);
+    if state.pause.is_paused() {
+        state.pause.resume();
+        log_info!("Download resumed");
     }
     Ok(())
 }
@@ -151,6 +265,15 @@ pub async fn get_download_path(state: State<'_, Arc<Mutex<AppState>>>) -> Result
     Ok(path.to_string_lossy().to_string())
 }
 
+/// Writes the diagnostics file for a bug report to `target` and returns its path.
+#[tauri::command]
+pub async fn export_diagnostics(target: String) -> Result<String, String> {
+    let path = boosty_downloader_core::export_diagnostics(std::path::Path::new(&target))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 pub async fn list_downloaded(
     state: State<'_, Arc<Mutex<AppState>>>,
@@ -166,13 +289,13 @@ pub async fn list_downloaded(
 #[tauri::command]
 pub async fn refresh_downloaded_blog(
     blog: String,
+    app: AppHandle,
     state: State<'_, Arc<Mutex<AppState>>>,
-) -> Result<BlogSnapshot, String> {
-    let (client, cfg, token) = take_work_slot(&state).await?;
-    let result =
-        boosty_downloader_core::refresh_downloaded_blog(&client, &cfg, &blog, &token).await;
-    release_work_slot(&state).await;
-    result.map_err(map_work_err)
+) -> Result<BlogSnapshot, CoreError> {
+    match run_queued(&app, &state, JobKind::Sync { blog }).await? {
+        JobResult::Sync { snapshot } => Ok(snapshot),
+        _ => Err(unexpected_result()),
+    }
 }
 
 #[tauri::command]
@@ -181,21 +304,36 @@ pub async fn download_downloaded_posts(
     post_ids: Vec<String>,
     download_options: DownloadOptions,
     force: bool,
+    app: AppHandle,
     state: State<'_, Arc<Mutex<AppState>>>,
-) -> Result<DownloadPostsResult, String> {
-    let (client, cfg, token) = take_work_slot(&state).await?;
-    let result = boosty_downloader_core::download_downloaded_posts(
-        &client,
-        &cfg,
-        &blog,
-        &post_ids,
+) -> Result<DownloadPostsResult, CoreError> {
+    let kind = JobKind::DownloadPosts {
+        blog,
+        post_ids,
         download_options,
         force,
-        &token,
-    )
-    .await;
-    release_work_slot(&state).await;
-    result.map_err(map_work_err)
+    };
+    match run_queued(&app, &state, kind).await? {
+        JobResult::DownloadPosts { result } => Ok(result),
+        _ => Err(unexpected_result()),
+    }
+}
+
+#[tauri::command]
+pub async fn retry_failed_downloads(
+    blog: String,
+    download_options: DownloadOptions,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<DownloadPostsResult, CoreError> {
+    let kind = JobKind::RetryFailures {
+        blog,
+        download_options,
+    };
+    match run_queued(&app, &state, kind).await? {
+        JobResult::DownloadPosts { result } => Ok(result),
+        _ => Err(unexpected_result()),
+    }
 }
 
 #[tauri::command]
@@ -204,13 +342,7 @@ pub async fn delete_downloaded_post(
     post_id: String,
     state: State<'_, Arc<Mutex<AppState>>>,
 ) -> Result<(), String> {
-    let cfg = {
-        let state = state.lock().await;
-        if state.download_token.is_some() {
-            return Err("Download is already in progress".to_string());
-        }
-        state.config.clone()
-    };
+    let cfg = ensure_idle(&state).await?;
     boosty_downloader_core::delete_downloaded_post(&cfg, &blog, &post_id)
         .await
         .map_err(|e| e.to_string())
@@ -221,14 +353,222 @@ pub async fn delete_downloaded_blog(
     blog: String,
     state: State<'_, Arc<Mutex<AppState>>>,
 ) -> Result<(), String> {
-    let cfg = {
-        let state = state.lock().await;
-        if state.download_token.is_some() {
-            return Err("Download is already in progress".to_string());
-        }
-        state.config.clone()
-    };
+    let cfg = ensure_idle(&state).await?;
     boosty_downloader_core::delete_downloaded_blog(&cfg, &blog)
         .await
         .map_err(|e| e.to_string())
 }
+
+/// Saves the per-blog overrides (content types, comments, limits, filters, output formats).
+#[tauri::command]
+pub async fn update_blog_settings(
+    blog: String,
+    settings: BlogSettings,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<(), String> {
+    let cfg = state.lock().await.config.clone();
+    boosty_downloader_core::update_blog_settings(&cfg, &blog, settings)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Moves the downloaded blogs to `target` through the queue and makes it the download path.
+#[tauri::command]
+pub async fn move_archive(
+    target: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<MoveArchiveResult, CoreError> {
+    let target = target.trim().to_string();
+    if target.is_empty() {
+        return Err(CoreError::new(
+            ErrorKind::Filesystem,
+            "Target folder is empty",
+        ));
+    }
+    match run_queued(&app, &state, JobKind::MoveArchive { target }).await? {
+        JobResult::MoveArchive { result } => Ok(result),
+        _ => Err(unexpected_result()),
+    }
+}
+
+#[tauri::command]
+pub async fn get_pending_move() -> Result<Option<MoveJournal>, String> {
+    boosty_downloader_core::pending_move()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn discard_pending_move() -> Result<(), String> {
+    boosty_downloader_core::discard_pending_move()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Renames downloaded post folders to the current post folder template.
+#[tauri::command]
+pub async fn rename_downloaded_folders(
+    dry_run: bool,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<RenameReport, String> {
+    let cfg = state.lock().await.config.clone();
+    boosty_downloader_core::rename_downloaded_folders(&cfg, dry_run)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_interrupted_session(
+    url: String,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Option<SessionState>, String> {
+    let cfg = state.lock().await.config.clone();
+    let path = boosty_downloader_core::get_download_path(&cfg);
+    boosty_downloader_core::load_interrupted_session(&path, url.trim())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn discard_interrupted_session(
+    blog: String,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<(), String> {
+    let cfg = state.lock().await.config.clone();
+    let path = boosty_downloader_core::get_download_path(&cfg);
+    boosty_downloader_core::discard_interrupted_session(&path, &blog)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn download_job(
+    url: String,
+    offset_url: Option<String>,
+    download_options: DownloadOptions,
+    resume: bool,
+) -> Result<JobKind, String> {
+    boosty_downloader_core::build_url_context(&url, offset_url.as_deref()).map_err(|e| {
+        log_error!("{e}");
+        e.to_string()
+    })?;
+    Ok(JobKind::Download {
+        url,
+        offset_url,
+        download_options,
+        resume,
+    })
+}
+
+#[tauri::command]
+pub async fn list_jobs(state: State<'_, Arc<Mutex<AppState>>>) -> Result<Vec<Job>, String> {
+    let state = state.lock().await;
+    Ok(state.queue.jobs().to_vec())
+}
+
+#[tauri::command]
+pub async fn enqueue_download(
+    url: String,
+    offset_url: Option<String>,
+    download_options: DownloadOptions,
+    resume: bool,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Job, String> {
+    let kind = download_job(url, offset_url, download_options, resume)?;
+    Ok(enqueue_job(&app, &state, kind, None).await)
+}
+
+#[tauri::command]
+pub async fn enqueue_sync(
+    blog: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Job, String> {
+    Ok(enqueue_job(&app, &state, JobKind::Sync { blog }, None).await)
+}
+
+#[tauri::command]
+pub async fn enqueue_subscriptions(
+    download_options: DownloadOptions,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Job, String> {
+    Ok(enqueue_job(
+        &app,
+        &state,
+        JobKind::Subscriptions { download_options },
+        None,
+    )
+    .await)
+}
+
+#[tauri::command]
+pub async fn move_job(
+    id: u64,
+    index: usize,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Vec<Job>, String> {
+    let (job, snapshot) = {
+        let mut state = state.lock().await;
+        let job = state.queue.move_to(id, index)?;
+        (job, state.queue.clone())
+    };
+    queue::persist(&snapshot).await;
+    queue::emit_job(&app, &job);
+    Ok(snapshot.jobs().to_vec())
+}
+
+#[tauri::command]
+pub async fn cancel_job(
+    id: u64,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<(), String> {
+    let (cancelled, waiter, snapshot) = {
+        let mut state = state.lock().await;
+        if let Some(running) = state.running.as_ref().filter(|r| r.id == id) {
+            running.token.cancel();
+            log_info!("Cancelling download #{id}");
+            return Ok(());
+        }
+        let cancelled = state.queue.cancel_queued(id);
+        let waiter = state.waiters.remove(&id);
+        (cancelled, waiter, state.queue.clone())
+    };
+    let Some(job) = cancelled else {
+        return Err("Job is not queued".to_string());
+    };
+    queue::persist(&snapshot).await;
+    queue::emit_job(&app, &job);
+    if let Some(waiter) = waiter {
+        let _ = waiter.send(Err(CoreError::cancelled()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_job(id: u64, state: State<'_, Arc<Mutex<AppState>>>) -> Result<(), String> {
+    let snapshot = {
+        let mut state = state.lock().await;
+        state.queue.remove(id)?;
+        state.waiters.remove(&id);
+        state.queue.clone()
+    };
+    queue::persist(&snapshot).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn clear_finished_jobs(
+    state: State<'_, Arc<Mutex<AppState>>>,
+) -> Result<Vec<Job>, String> {
+    let snapshot = {
+        let mut state = state.lock().await;
+        state.queue.clear_finished();
+        state.queue.clone()
+    };
+    queue::persist(&snapshot).await;
+    Ok(snapshot.jobs().to_vec())
+}
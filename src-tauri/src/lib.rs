@@ -5,19 +5,39 @@ use tokio::sync::Mutex;
 use crate::state::AppState;
 
 mod commands;
+mod queue;
 mod state;
 mod tauri_logger;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if let Some(path) = boosty_downloader_core::config_arg(std::env::args().skip(1)) {
+        boosty_downloader_core::set_config_path(path);
+    }
     let state = Arc::new(Mutex::new(AppState::default()));
+    let worker_state = state.clone();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .setup(|app| {
             let logger = tauri_logger::TauriLogger::new(app.handle().clone());
-            boosty_downloader_core::set_logger(logger);
+            boosty_downloader_core::set_default_sink(logger);
+            if let Err(e) = boosty_downloader_core::init_file_log() {
+                boosty_downloader_core::log_warn!("Logs are not written to a file: {e:#}");
+            }
+
+            // Loaded before any command can run, so no job is enqueued into a queue
+            // that the saved one then replaces.
+            tauri::async_runtime::block_on(async {
+                match queue::load_queue().await {
+                    Ok(loaded) => worker_state.lock().await.queue = loaded,
+                    Err(e) => boosty_downloader_core::log_error!("{e:#}"),
+                }
+            });
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(queue::run_worker(handle, worker_state));
             Ok(())
         })
         .manage(state)
@@ -26,14 +46,37 @@ pub fn run() {
             commands::get_config,
             commands::update_config,
             commands::init_client,
+            commands::switch_profile,
+            commands::delete_profile,
+            commands::check_auth,
+            commands::tokens_in_plain_text,
             commands::download_content,
             commands::cancel_download,
+            commands::pause_download,
+            commands::resume_download,
             commands::get_download_path,
+            commands::export_diagnostics,
             commands::list_downloaded,
             commands::refresh_downloaded_blog,
             commands::download_downloaded_posts,
+            commands::retry_failed_downloads,
             commands::delete_downloaded_post,
             commands::delete_downloaded_blog,
+            commands::update_blog_settings,
+            commands::rename_downloaded_folders,
+            commands::move_archive,
+            commands::get_pending_move,
+            commands::discard_pending_move,
+            commands::get_interrupted_session,
+            commands::discard_interrupted_session,
+            commands::list_jobs,
+            commands::enqueue_download,
+            commands::enqueue_sync,
+            commands::enqueue_subscriptions,
+            commands::move_job,
+            commands::cancel_job,
+            commands::remove_job,
+            commands::clear_finished_jobs,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,537 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use boosty_api::api_client::ApiClient;
+use boosty_downloader_core::{
+    AppConfig, BlogSnapshot, CoreError, DownloadOptions, DownloadPostsResult, ErrorKind,
+    MoveArchiveResult, SessionReport, SubscriptionsResult, log_error, log_info,
+    with_event_sink_and_pause,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::{fs, sync::Mutex};
+use tokio_util::sync::CancellationToken;
+
+use crate::state::{AppState, RunningJob};
+use crate::tauri_logger::TauriLogger;
+
+const QUEUE_FILE: &str = "queue.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobKind {
+    Download {
+        url: String,
+        offset_url: Option<String>,
+        download_options: DownloadOptions,
+        /// Continue the interrupted session of the blog, if there is one.
+        #[serde(default)]
+        resume: bool,
+    },
+    Sync {
+        blog: String,
+    },
+    DownloadPosts {
+        blog: String,
+        post_ids: Vec<String>,
+        download_options: DownloadOptions,
+        force: bool,
+    },
+    RetryFailures {
+        blog: String,
+        download_options: DownloadOptions,
+    },
+    Subscriptions {
+        download_options: DownloadOptions,
+    },
+    MoveArchive {
+        target: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobResult {
+    Download {
+        posts: usize,
+        #[serde(default)]
+        report: Option<SessionReport>,
+    },
+    Sync {
+        snapshot: BlogSnapshot,
+    },
+    DownloadPosts {
+        result: DownloadPostsResult,
+    },
+    Subscriptions {
+        result: SubscriptionsResult,
+    },
+    MoveArchive {
+        result: MoveArchiveResult,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub created_at: i64,
+    #[serde(default)]
+    pub started_at: Option<i64>,
+    #[serde(default)]
+    pub finished_at: Option<i64>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Class of `error`, so the UI can react to auth, network or paywall failures.
+    #[serde(default)]
+    pub error_kind: Option<ErrorKind>,
+    #[serde(default)]
+    pub result: Option<JobResult>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobQueue {
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    jobs: Vec<Job>,
+}
+
+impl JobQueue {
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|j| j.id == id)
+    }
+
+    pub fn enqueue(&mut self, kind: JobKind) -> Job {
+        self.next_id += 1;
+        let job = Job {
+            id: self.next_id,
+            kind,
+            status: JobStatus::Queued,
+            created_at: now(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+            error_kind: None,
+            result: None,
+        };
+        self.jobs.push(job.clone());
+        job
+    }
+
+    /// Moves a queued job to `index` among all jobs; running and finished jobs stay put.
+    pub fn move_to(&mut self, id: u64, index: usize) -> Result<Job, String> {
+        let from = self
+            .jobs
+            .iter()
+            .position(|j| j.id == id)
+            .ok_or("Job not found")?;
+        if self.jobs[from].status != JobStatus::Queued {
+            return Err("Only queued jobs can be moved".to_string());
+        }
+        let job = self.jobs.remove(from);
+        let index = index.min(self.jobs.len());
+        self.jobs.insert(index, job.clone());
+        Ok(job)
+    }
+
+    /// Marks a queued job as cancelled. Running jobs are cancelled through their token.
+    pub fn cancel_queued(&mut self, id: u64) -> Option<Job> {
+        let job = self.get_mut(id)?;
+        if job.status != JobStatus::Queued {
+            return None;
+        }
+        job.status = JobStatus::Cancelled;
+        job.finished_at = Some(now());
+        Some(job.clone())
+    }
+
+    pub fn remove(&mut self, id: u64) -> Result<(), String> {
+        let pos = self
+            .jobs
+            .iter()
+            .position(|j| j.id == id)
+            .ok_or("Job not found")?;
+        if self.jobs[pos].status == JobStatus::Running {
+            return Err("Running job can't be removed, cancel it first".to_string());
+        }
+        self.jobs.remove(pos);
+        Ok(())
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|j| !j.status.is_finished());
+    }
+
+    fn start_next(&mut self) -> Option<Job> {
+        let job = self
+            .jobs
+            .iter_mut()
+            .find(|j| j.status == JobStatus::Queued)?;
+        job.status = JobStatus::Running;
+        job.started_at = Some(now());
+        job.error = None;
+        job.error_kind = None;
+        job.result = None;
+        Some(job.clone())
+    }
+
+    fn finish(&mut self, id: u64, outcome: &JobOutcome) -> Option<Job> {
+        let job = self.get_mut(id)?;
+        job.finished_at = Some(now());
+        match outcome {
+            Ok(result) => {
+                job.status = JobStatus::Done;
+                job.result = Some(result.clone());
+            }
+            Err(e) if e.is_cancelled() => {
+                job.status = JobStatus::Cancelled;
+            }
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(e.message.clone());
+                job.error_kind = Some(e.kind);
+            }
+        }
+        Some(job.clone())
+    }
+
+    /// Jobs interrupted by an app shutdown go back to the queue.
+    fn requeue_interrupted(&mut self) {
+        for job in self.jobs.iter_mut() {
+            if job.status == JobStatus::Running {
+                job.status = JobStatus::Queued;
+                job.started_at = None;
+            }
+        }
+    }
+}
+
+pub type JobOutcome = Result<JobResult, CoreError>;
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn queue_path() -> Result<PathBuf> {
+    boosty_downloader_core::app_file_path(QUEUE_FILE)
+}
+
+pub async fn load_queue() -> Result<JobQueue> {
+    let path = queue_path()?;
+    if !fs::try_exists(&path).await.unwrap_or(false) {
+        return Ok(JobQueue::default());
+    }
+    let data = fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read queue file '{}'", path.display()))?;
+    let mut queue: JobQueue = serde_json::from_slice(&data)
+        .with_context(|| format!("Failed to parse queue file '{}'", path.display()))?;
+    queue.requeue_interrupted();
+    Ok(queue)
+}
+
+pub async fn save_queue(queue: &JobQueue) -> Result<()> {
+    let path = queue_path()?;
+    let data = serde_json::to_vec_pretty(queue).context("Failed to serialize queue")?;
+    fs::write(&path, data)
+        .await
+        .with_context(|| format!("Failed to write queue file '{}'", path.display()))?;
+    Ok(())
+}
+
+pub async fn persist(queue: &JobQueue) {
+    if let Err(e) = save_queue(queue).await {
+        log_error!("{e:#}");
+    }
+}
+
+pub fn emit_job(app: &AppHandle, job: &Job) {
+    let _ = app.emit("job", job);
+}
+
+/// Puts the credentials the core refreshed and saved into the config the app keeps in
+/// memory: tokens, their expiry and device id of the active profile and of the others,
+/// and where they are stored.
+pub fn take_saved_tokens(config: &mut AppConfig, saved: AppConfig) {
+    config.active_profile = saved.active_profile;
+    config.access_token = saved.access_token;
+    config.refresh_token = saved.refresh_token;
+    config.device_id = saved.device_id;
+    config.token_expires_at = saved.token_expires_at;
+    config.profiles = saved.profiles;
+    config.token_storage = saved.token_storage;
+}
+
+/// Runs queued jobs one at a time for the lifetime of the app.
+///
+/// Sleeps until `queue_notify` fires when the queue is empty or the client is not ready yet.
+pub async fn run_worker(app: AppHandle, state: Arc<Mutex<AppState>>) {
+    loop {
+        let notify = state.lock().await.queue_notify.clone();
+
+        let Some((job, client, cfg, token)) = take_next(&state).await else {
+            notify.notified().await;
+            continue;
+        };
+        emit_job(&app, &job);
+
+        let pause = state.lock().await.pause.clone();
+        let sink = Arc::new(TauriLogger::new(app.clone()));
+        let outcome = with_event_sink_and_pause(
+            sink,
+            pause.clone(),
+            run_job(&client, &cfg, &job.kind, &token),
+        )
+        .await;
+        if let Err(e) = &outcome
+            && !e.is_cancelled()
+        {
+            log_error!("{e}");
+        }
+        // A pause left over from a cancelled job must not hold up the next one.
+        pause.resume();
+
+        // The core refreshes expired tokens mid-job and saves them to the config file;
+        // moving the archive saves the new download path.
+        let saved = boosty_downloader_core::load_config().await;
+
+        let (finished, waiter, snapshot) = {
+            let mut state = state.lock().await;
+            state.running = None;
+            if let Ok(saved) = saved {
+                state.config.download_path = saved.download_path.clone();
+                take_saved_tokens(&mut state.config, saved);
+            }
+            let finished = state.queue.finish(job.id, &outcome);
+            let waiter = state.waiters.remove(&job.id);
+            (finished, waiter, state.queue.clone())
+        };
+        persist(&snapshot).await;
+        if let Some(finished) = finished {
+            emit_job(&app, &finished);
+        }
+        if let Some(waiter) = waiter {
+            let _ = waiter.send(outcome);
+        }
+    }
+}
+
+async fn take_next(
+    state: &Arc<Mutex<AppState>>,
+) -> Option<(Job, ApiClient, AppConfig, CancellationToken)> {
+    let (job, client, cfg, token, snapshot) = {
+        let mut state = state.lock().await;
+        if state.switching_profile {
+            return None;
+        }
+        let client = state.client.clone()?;
+        let job = state.queue.start_next()?;
+        let token = CancellationToken::new();
+        state.running = Some(RunningJob {
+            id: job.id,
+            token: token.clone(),
+        });
+        (
+            job,
+            client,
+            state.config.clone(),
+            token,
+            state.queue.clone(),
+        )
+    };
+    persist(&snapshot).await;
+    Some((job, client, cfg, token))
+}
+
+async fn run_job(
+    client: &ApiClient,
+    cfg: &AppConfig,
+    kind: &JobKind,
+    token: &CancellationToken,
+) -> Result<JobResult, CoreError> {
+    match kind {
+        JobKind::Download {
+            url,
+            offset_url,
+            download_options,
+            resume,
+        } => {
+            log_info!("Starting download: {url}");
+            let ctx = boosty_downloader_core::build_url_context(url, offset_url.as_deref())?;
+            let report = boosty_downloader_core::process_boosty_url(
+                client,
+                cfg,
+                &ctx.url,
+                ctx.offset,
+                download_options.clone(),
+                *resume,
+                token,
+            )
+            .await?;
+            Ok(JobResult::Download {
+                posts: report.posts_downloaded as usize,
+                report: Some(report),
+            })
+        }
+        JobKind::Sync { blog } => {
+            let snapshot =
+                boosty_downloader_core::refresh_downloaded_blog(client, cfg, blog, token).await?;
+            Ok(JobResult::Sync { snapshot })
+        }
+        JobKind::DownloadPosts {
+            blog,
+            post_ids,
+            download_options,
+            force,
+        } => {
+            let result = boosty_downloader_core::download_downloaded_posts(
+                client,
+                cfg,
+                blog,
+                post_ids,
+                download_options.clone(),
+                *force,
+                token,
+            )
+            .await?;
+            Ok(JobResult::DownloadPosts { result })
+        }
+        JobKind::RetryFailures {
+            blog,
+            download_options,
+        } => {
+            let result = boosty_downloader_core::retry_failed_downloads(
+                client,
+                cfg,
+                blog,
+                download_options.clone(),
+                token,
+            )
+            .await?;
+            Ok(JobResult::DownloadPosts { result })
+        }
+        JobKind::Subscriptions { download_options } => {
+            let result = boosty_downloader_core::download_subscriptions(
+                client,
+                cfg,
+                download_options.clone(),
+                token,
+            )
+            .await?;
+            Ok(JobResult::Subscriptions { result })
+        }
+        JobKind::MoveArchive { target } => {
+            let result =
+                boosty_downloader_core::move_archive(cfg, Path::new(target), token).await?;
+            Ok(JobResult::MoveArchive { result })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sync_kind(blog: &str) -> JobKind {
+        JobKind::Sync {
+            blog: blog.to_string(),
+        }
+    }
+
+    #[test]
+    fn jobs_start_in_queue_order() {
+        let mut queue = JobQueue::default();
+        let a = queue.enqueue(sync_kind("a"));
+        let b = queue.enqueue(sync_kind("b"));
+        queue.move_to(b.id, 0).unwrap();
+
+        let started = queue.start_next().unwrap();
+        assert_eq!(started.id, b.id);
+        assert_eq!(queue.get(a.id).unwrap().status, JobStatus::Queued);
+        assert!(queue.move_to(b.id, 1).is_err());
+    }
+
+    #[test]
+    fn cancelled_and_finished_jobs_are_skipped_and_cleared() {
+        let mut queue = JobQueue::default();
+        let a = queue.enqueue(sync_kind("a"));
+        let b = queue.enqueue(sync_kind("b"));
+        queue.cancel_queued(a.id).unwrap();
+
+        let started = queue.start_next().unwrap();
+        assert_eq!(started.id, b.id);
+        assert!(queue.remove(b.id).is_err());
+        queue.finish(b.id, &Err(CoreError::new(ErrorKind::Network, "boom")));
+        let failed = queue.get(b.id).unwrap();
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert_eq!(failed.error_kind, Some(ErrorKind::Network));
+
+        queue.clear_finished();
+        assert!(queue.jobs().is_empty());
+    }
+
+    #[test]
+    fn saved_credentials_replace_the_ones_in_memory() {
+        let mut config = AppConfig {
+            access_token: "old".into(),
+            token_expires_at: Some(1),
+            posts_limit: 7,
+            ..AppConfig::default()
+        };
+        let saved = AppConfig {
+            access_token: "new".into(),
+            refresh_token: "refresh".into(),
+            device_id: "device".into(),
+            token_expires_at: None,
+            ..AppConfig::default()
+        };
+        take_saved_tokens(&mut config, saved);
+        assert_eq!(config.access_token, "new");
+        assert_eq!(config.refresh_token, "refresh");
+        assert_eq!(config.device_id, "device");
+        assert_eq!(config.token_expires_at, None);
+        assert_eq!(config.posts_limit, 7);
+    }
+
+    #[test]
+    fn running_jobs_are_requeued_after_restart() {
+        let mut queue = JobQueue::default();
+        let a = queue.enqueue(sync_kind("a"));
+        queue.start_next().unwrap();
+        queue.requeue_interrupted();
+        assert_eq!(queue.get(a.id).unwrap().status, JobStatus::Queued);
+    }
+}
@@ -1,10 +1,28 @@
+use std::{collections::HashMap, sync::Arc};
+
 use boosty_api::api_client::ApiClient;
-use boosty_downloader_core::AppConfig;
+use boosty_downloader_core::{AppConfig, PauseHandle};
+use tokio::sync::{Notify, oneshot};
 use tokio_util::sync::CancellationToken;
 
+use crate::queue::{JobOutcome, JobQueue};
+
+#[derive(Debug)]
+pub struct RunningJob {
+    pub id: u64,
+    pub token: CancellationToken,
+}
+
 #[derive(Debug, Default)]
 pub struct AppState {
     pub client: Option<ApiClient>,
     pub config: AppConfig,
-    pub download_token: Option<CancellationToken>,
+    pub queue: JobQueue,
+    pub running: Option<RunningJob>,
+    /// Pauses the running job only, not sessions of other frontends in the process.
+    pub pause: PauseHandle,
+    /// Set while `switch_profile` talks to Boosty; no job starts meanwhile.
+    pub switching_profile: bool,
+    pub waiters: HashMap<u64, oneshot::Sender<JobOutcome>>,
+    pub queue_notify: Arc<Notify>,
 }
@@ -1,8 +1,7 @@
-use std::any::Any;
-
-use boosty_downloader_core::{LogLevel, LogMessage, Logger, ProgressMessage};
+use boosty_downloader_core::{Event, EventSink, LogMessage};
 use tauri::{AppHandle, Emitter};
 
+/// Forwards log lines and progress of the core to the webview.
 pub struct TauriLogger {
     app: AppHandle,
 }
@@ -13,17 +12,20 @@ impl TauriLogger {
     }
 }
 
-impl Logger for TauriLogger {
-    fn log(&self, level: LogLevel, message: &str) {
-        let msg = LogMessage { level, message };
-        let _ = self.app.emit("log", msg);
-    }
-
-    fn progress(&self, msg: ProgressMessage) {
-        let _ = self.app.emit("progress", msg);
-    }
-
-    fn as_any(&self) -> &(dyn Any + 'static) {
-        self
+impl EventSink for TauriLogger {
+    fn send(&self, event: Event) {
+        match event {
+            Event::Log { level, message } => {
+                let msg = LogMessage {
+                    level,
+                    message: &message,
+                };
+                let _ = self.app.emit("log", msg);
+            }
+            Event::Progress(msg) => {
+                let _ = self.app.emit("progress", msg);
+            }
+            _ => {}
+        }
     }
 }